@@ -1,5 +1,6 @@
 #[macro_use] extern crate maplit;
 
+use std::collections::HashSet;
 use std::env;
 use std::fs::File;
 use std::io::Write;
@@ -7,50 +8,22 @@ use std::path::Path;
 
 
 const MIN_ARGS: usize = 1;
-const MAX_ARGS: usize = 12;
+const MAX_ARGS: usize = 16;
 
 
-fn generate_matcher_macro(max_args: usize) -> String {
-    assert!(max_args >= MIN_ARGS && max_args <= MAX_ARGS);
-
-    let arg_nums: Vec<usize> = (MIN_ARGS..MAX_ARGS).collect();
-    let macro_cases: Vec<String> = arg_nums.iter().map(
-        |&i| generate_matcher_macro_case_n(i)
-    ).collect();
-    format!(
-        "#[macro_export]\nmacro_rules! matcher {{\n{}\n\n}}",
-        macro_cases.join("\n"))
-}
-
-fn generate_matcher_macro_case_n(n_args: usize) -> String {
-    let arg_nums: Vec<usize> = (MIN_ARGS..n_args + 1).collect();
-    let case_args: Vec<String> = arg_nums.iter().map(
-        |&i| format!("$m{}:expr", i.to_string())
-    ).collect();
-    let match_impl_func_args: Vec<String> = arg_nums.iter().map(
-        |&i| format!("$m{}", i.to_string())
-    ).collect();
-
-    format!("
-    ({}) => (
-        &|args| -> bool {{ match_impl_{}(args, ({})) }}
-    );",
-        case_args.join(", "),
-        n_args.to_string(),
-        match_impl_func_args.join(", "))
-}
-
 fn generate_match_impls(max_args: usize) -> String {
     assert!(max_args >= MIN_ARGS && max_args <= MAX_ARGS);
 
-    let arg_nums: Vec<usize> = (MIN_ARGS..MAX_ARGS).collect();
+    let arg_nums: Vec<usize> = (MIN_ARGS..max_args + 1).collect();
     let match_impls: Vec<String> = arg_nums.iter().map(
         |&i| generate_match_impl_n(i)
     ).collect();
     match_impls.join("\n")
 }
 
-fn generate_match_impl_n(n_args: usize) -> String {
+// Returns a unique generic type parameter name for each of `n_args`
+// arguments.
+fn generic_type_names(n_args: usize) -> Vec<String> {
     let arg_num_to_generic_type = hashmap!(
         0usize => "A",
         1usize => "B",
@@ -63,10 +36,24 @@ fn generate_match_impl_n(n_args: usize) -> String {
         8usize => "I",
         9usize => "J",
         10usize => "K",
-        11usize => "J"
+        11usize => "L",
+        12usize => "M",
+        13usize => "N",
+        14usize => "O",
+        15usize => "P"
     );
     assert!(arg_num_to_generic_type.len() == MAX_ARGS);
+    let unique_type_names: HashSet<&&str> = arg_num_to_generic_type.values().collect();
+    assert!(unique_type_names.len() == MAX_ARGS, "generic type names must be unique");
+
+    (0..n_args).map(
+        |i| arg_num_to_generic_type.get(&i)
+            .expect("not enough num -> type name mappings")
+            .to_string()
+    ).collect()
+}
 
+fn generate_match_impl_n(n_args: usize) -> String {
     // We need a special case for one argument. The rust compile won't treat
     // the input arg as a one-tuple and will treat it is a single arg instead.
     if n_args == 1 {
@@ -77,25 +64,18 @@ pub fn match_impl_1<A>(arg: &A, arg_matcher: &dyn Fn(&A) -> bool) -> bool {
     }
 
     let arg_number_range: Vec<usize> = (0..n_args).collect();
-    let type_param_names: Vec<String> = arg_number_range.iter().map(
-        |&i| arg_num_to_generic_type.get(&i)
-            .expect("not enough num -> type name mappings")
-            .to_owned()
-            .to_owned()
-    ).collect();
+    let type_param_names = generic_type_names(n_args);
 
     let matcher_params: Vec<String> = type_param_names.iter().map(
         |ref t| format!("&dyn Fn(&{}) -> bool", t)
     ).collect();
 
     let matcher_invocations: Vec<String> = arg_number_range.iter().map(
-        |&i| format!(
-            "arg_matchers.{}(&args.{})",
-            i.to_string(),
-            i.to_string())
+        |&i| format!("arg_matchers.{}(&args.{})", i, i)
     ).collect();
 
     format!("
+#[allow(clippy::type_complexity)]
 pub fn match_impl_{}<{}>(args: &(
         {}
     ),
@@ -107,38 +87,154 @@ pub fn match_impl_{}<{}>(args: &(
     );
     !matches.iter().any(|is_match| !is_match)
 }}",
-        n_args.to_string(),
+        n_args,
         type_param_names.join(","),
         type_param_names.join(",\n        "),
         matcher_params.join(",\n        "),
         matcher_invocations.join(",\n        "))
 }
 
-fn generate_p_macro(max_args: usize) -> String {
+// `matcher!` calls `MatchImpl::<N>::matches` for `N` matchers, which calls
+// `match_impl_N`. This lets the macro pick the function for the number of
+// matchers without an arm for each number.
+fn generate_match_impl_dispatch(max_args: usize) -> String {
     assert!(max_args >= MIN_ARGS && max_args <= MAX_ARGS);
 
-    let arg_nums: Vec<usize> = (MIN_ARGS - 1..MAX_ARGS).collect();
+    let arg_nums: Vec<usize> = (MIN_ARGS..max_args + 1).collect();
+    let impls: Vec<String> = arg_nums.iter().map(
+        |&i| generate_match_impl_dispatch_n(i)
+    ).collect();
+    format!(
+        "/// Selects the `match_impl_n` function for `N` arguments. Used by `matcher!`.
+pub struct MatchImpl<const N: usize>;
+{}",
+        impls.join("\n"))
+}
+
+fn generate_match_impl_dispatch_n(n_args: usize) -> String {
+    let type_param_names = generic_type_names(n_args);
+    let (args_type, matchers_type) = if n_args == 1 {
+        ("A".to_owned(), "&dyn Fn(&A) -> bool".to_owned())
+    } else {
+        let matcher_types: Vec<String> = type_param_names.iter().map(
+            |t| format!("&dyn Fn(&{}) -> bool", t)
+        ).collect();
+        (format!("({})", type_param_names.join(", ")),
+         format!("({})", matcher_types.join(", ")))
+    };
+
+    format!("
+#[allow(clippy::type_complexity)]
+impl MatchImpl<{}> {{
+    pub fn matches<{}>(args: &{}, arg_matchers: {}) -> bool {{
+        match_impl_{}(args, arg_matchers)
+    }}
+}}",
+        n_args,
+        type_param_names.join(", "),
+        args_type,
+        matchers_type,
+        n_args)
+}
+
+// `Mock` methods that take a call's arguments convert a tuple of arguments
+// element by element, e.g. `("a", "b")` to `(String, String)`. This
+// generates an `IntoCallArgs` impl for each tuple size. An impl only applies
+// if at least one element is converted, so it never overlaps with the impl
+// for arguments that are already the mock's argument type.
+fn generate_into_call_args_impls(max_args: usize) -> String {
+    assert!((MIN_ARGS..=MAX_ARGS).contains(&max_args));
+
+    // A single argument isn't a tuple, so it's converted with `Into`.
+    let arg_nums: Vec<usize> = (MIN_ARGS + 1..max_args + 1).collect();
+    let impls: Vec<String> = arg_nums.iter().map(
+        |&i| generate_into_call_args_impl_n(i)
+    ).collect();
+    impls.join("\n")
+}
+
+fn generate_into_call_args_impl_n(n_args: usize) -> String {
+    let arg_number_range: Vec<usize> = (0..n_args).collect();
+    let from_types: Vec<String> = generic_type_names(n_args).iter().map(
+        |t| format!("{}1", t)
+    ).collect();
+    let to_types: Vec<String> = generic_type_names(n_args).iter().map(
+        |t| format!("{}2", t)
+    ).collect();
+
+    let type_params: Vec<String> = from_types.iter().chain(to_types.iter())
+        .cloned()
+        .collect();
+    let element_bounds: Vec<String> = arg_number_range.iter().map(
+        |&i| format!("{}: IntoCallArg<{}>", from_types[i], to_types[i])
+    ).collect();
+    // Nested pairs of each element's `Converts` flag, e.g.
+    // `(A1::Converts, (B1::Converts, ()))`.
+    let converts_flags = arg_number_range.iter().rev().fold(
+        "()".to_owned(),
+        |rest, &i| format!(
+            "(<{} as IntoCallArg<{}>>::Converts, {})",
+            from_types[i], to_types[i], rest)
+    );
+    let conversions: Vec<String> = arg_number_range.iter().map(
+        |&i| format!("self.{}.into_call_arg()", i)
+    ).collect();
+
+    format!("
+#[allow(clippy::type_complexity)]
+impl<{}> IntoCallArgs<({}), ConvertElements> for ({})
+    where {},
+          {}: AnyConverted
+{{
+    fn into_call_args(self) -> ({}) {{
+        ({})
+    }}
+}}",
+        type_params.join(", "),
+        to_types.join(", "),
+        from_types.join(", "),
+        element_bounds.join(",\n          "),
+        converts_flags,
+        to_types.join(", "),
+        conversions.join(", "))
+}
+
+fn generate_p_macro(max_args: usize, by_ref: bool) -> String {
+    assert!(max_args >= MIN_ARGS && max_args <= MAX_ARGS);
+
+    let macro_name = if by_ref {
+        "pr"
+    } else {
+        "p"
+    };
+
+    let arg_nums: Vec<usize> = (MIN_ARGS - 1..max_args + 1).collect();
     let macro_cases: Vec<String> = arg_nums.iter().map(
-        |&i| generate_p_macro_case_n(i)
+        |&i| generate_p_macro_case_n(i, by_ref)
     ).collect();
     format!(
-        "#[macro_export]\nmacro_rules! p {{\n{}\n\n}}",
+        "#[macro_export]\nmacro_rules! {} {{\n{}\n\n}}",
+        macro_name,
         macro_cases.join("\n"))
 }
 
-fn generate_p_macro_case_n(n_args: usize) -> String {
+fn generate_p_macro_case_n(n_args: usize, by_ref: bool) -> String {
     if n_args == 0 {
+        // Not passed through `format!`, so braces aren't escaped.
         return "
         ($func:ident) => (
-            &|potential_match| -> bool {{ $func(potential_match) }}
+            &|potential_match| -> bool { $func(potential_match) }
         );".to_owned()
     } else {
         let arg_nums: Vec<usize> = (MIN_ARGS..n_args + 1).collect();
         let case_args: Vec<String> = arg_nums.iter().map(
-            |&i| format!("$arg{}:expr", i.to_string())
+            |&i| format!("$arg{}:expr", i)
         ).collect();
+        // `pr!` passes matcher args by reference, so the closure borrows
+        // them instead of needing its own copy.
+        let arg_prefix = if by_ref { "&" } else { "" };
         let impl_func_call_args: Vec<String> = arg_nums.iter().map(
-            |&i| format!("$arg{}", i.to_string())
+            |&i| format!("{}$arg{}", arg_prefix, i)
         ).collect();
 
         format!("
@@ -159,7 +255,7 @@ fn generate_mock_func_macro(max_args: usize, use_default: bool) -> String {
         "mock_func_no_default"
     };
 
-    let arg_nums: Vec<usize> = (MIN_ARGS - 1..MAX_ARGS).collect();
+    let arg_nums: Vec<usize> = (MIN_ARGS - 1..max_args + 1).collect();
     let macro_cases: Vec<String> = arg_nums.iter().map(
         |&i| generate_mock_func_macro_case_n(i, use_default)
     ).collect();
@@ -172,16 +268,16 @@ fn generate_mock_func_macro(max_args: usize, use_default: bool) -> String {
 fn generate_mock_func_macro_case_n(n_args: usize, use_default: bool) -> String {
     let arg_nums: Vec<usize> = (MIN_ARGS..n_args + 1).collect();
     let case_args: Vec<String> = arg_nums.iter().map(
-        |&i| format!("$arg{}_type:ty", i.to_string())
+        |&i| format!("$arg{}_type:ty", i)
     ).collect();
     let mock_obj_arg_types: Vec<String> = arg_nums.iter().map(
-        |&i| format!("$arg{}_type", i.to_string())
+        |&i| format!("$arg{}_type", i)
     ).collect();
     let closure_args: Vec<String> = arg_nums.iter().map(
-        |&i| format!("arg{}: $arg{}_type", i.to_string(), i.to_string())
+        |&i| format!("arg{}: $arg{}_type", i, i)
     ).collect();
     let mock_obj_func_call_args: Vec<String> = arg_nums.iter().map(
-        |&i| format!("arg{}.clone()", i.to_string())
+        |&i| format!("arg{}.clone()", i)
     ).collect();
 
     let case_retval_default_arg = if use_default {
@@ -199,10 +295,15 @@ fn generate_mock_func_macro_case_n(n_args: usize, use_default: bool) -> String {
             mock_obj_arg_types.join(", "))
     };
 
+    // The closure owns a clone of the mock, which shares the mock's state, so
+    // the closure can be cloned and outlive the scope of `$mock_obj`.
     format!("
     ($mock_obj:ident, $mock_fn:ident, $retval:ty, {}{}) => (
         {}
-        let $mock_fn = |{}| -> $retval {{ $mock_obj.call({}) }};
+        let $mock_fn = {{
+            let $mock_obj = $mock_obj.clone();
+            move |{}| -> $retval {{ $mock_obj.call({}) }}
+        }};
     );",
         case_retval_default_arg,
         case_args.join(", "),
@@ -216,9 +317,10 @@ fn main() {
 
     {
         let file_contents = vec!(
-            generate_matcher_macro(MAX_ARGS),
             generate_match_impls(MAX_ARGS),
-            generate_p_macro(MAX_ARGS)).join("\n\n");
+            generate_match_impl_dispatch(MAX_ARGS),
+            generate_p_macro(MAX_ARGS, false),
+            generate_p_macro(MAX_ARGS, true)).join("\n\n");
         let dest_path = Path::new(&out_dir).join("matcher_generated.rs");
         let mut f = File::create(&dest_path).unwrap();
         f.write_all(file_contents.as_bytes()).unwrap();
@@ -232,4 +334,11 @@ fn main() {
         let mut f = File::create(&dest_path).unwrap();
         f.write_all(file_contents.as_bytes()).unwrap();
     }
+
+    {
+        let file_contents = generate_into_call_args_impls(MAX_ARGS);
+        let dest_path = Path::new(&out_dir).join("mock_generated.rs");
+        let mut f = File::create(&dest_path).unwrap();
+        f.write_all(file_contents.as_bytes()).unwrap();
+    }
 }
@@ -0,0 +1,595 @@
+//! Procedural macros for `double`.
+//!
+//! These are re-exported by the `double` crate and should be used through it,
+//! e.g. `#[double::mock]`.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Delimiter, Group, Span, TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::punctuated::Punctuated;
+use syn::{
+    braced, bracketed, Error, FnArg, GenericArgument, Ident, ItemTrait, Lifetime, Pat, Path,
+    PathArguments, ReturnType, Token, TraitItem, TraitItemFn, Type, Visibility,
+};
+
+/// Generates a mock implementation of the `trait` it's placed on.
+///
+/// The mock `struct` is named `Mock` followed by the name of the `trait`, and
+/// has the same visibility as the `trait`. It has a `double::Mock` field for
+/// each of the trait's methods, named after the method with an `m_` prefix and
+/// returned by an accessor named after the method with a `_mock` suffix. It
+/// implements the `trait` by passing each method's arguments to its `Mock`.
+/// It's equivalent to using `mock_trait!` and `mock_method!` with the trait's
+/// signatures.
+///
+/// Borrowed arguments are stored as owned values:
+///
+/// | Argument type | Stored type    |
+/// | ------------- | -------------- |
+/// | `&T`          | `T`            |
+/// | `&str`        | `String`       |
+/// | `&[T]`        | `Vec<T>`       |
+/// | `&[&str]`     | `Vec<String>`  |
+/// | `&Path`       | `PathBuf`      |
+/// | `&OsStr`      | `OsString`     |
+/// | `Cow<str>`    | `String`       |
+/// | `Cow<[T]>`    | `Vec<T>`       |
+/// | `Cow<Path>`   | `PathBuf`      |
+/// | `Cow<OsStr>`  | `OsString`     |
+///
+/// `default()` returns the `Default` value of each method's return type. If a
+/// return type doesn't implement `Default`, calling the method panics unless
+/// a return value has been configured. `new()` takes the initial return
+/// value of each method instead.
+///
+/// Only methods taking `&self` or `&mut self` without type parameters are
+/// supported.
+///
+/// # Examples
+///
+/// ```ignore
+/// #[double::mock]
+/// trait UserStore {
+///     fn get_user(&self, id: i32) -> Result<String, String>;
+///     fn rename_user(&mut self, id: i32, name: &str);
+/// }
+///
+/// let mut mock = MockUserStore::default();
+/// mock.get_user_mock().return_value(Ok("Donald".to_owned()));
+/// mock.rename_user(42, "Don");
+///
+/// assert_eq!(Ok("Donald".to_owned()), mock.get_user(42));
+/// assert!(mock.rename_user_mock().called_with((42, "Don".to_owned())));
+/// ```
+#[proc_macro_attribute]
+pub fn mock(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = TokenStream2::from(args);
+    let item = syn::parse_macro_input!(input as ItemTrait);
+
+    let generated = if !args.is_empty() {
+        Error::new(args.span(), "#[double::mock] does not take arguments")
+            .to_compile_error()
+    } else if !item.generics.params.is_empty() {
+        Error::new(
+            item.generics.span(),
+            "#[double::mock] does not support traits with generic parameters")
+            .to_compile_error()
+    } else {
+        let mock_name = format_ident!("Mock{}", item.ident);
+        let trait_path = Path::from(item.ident.clone());
+        generate_mock(&item.vis, &mock_name, &trait_path, &item.items)
+            .unwrap_or_else(|err| err.to_compile_error())
+    };
+    TokenStream::from(quote! {
+        #item
+        #generated
+    })
+}
+
+/// Generates a mock `struct` and its implementation of a `trait` from the
+/// signatures of the trait's methods.
+///
+/// This is an alternative to using `mock_trait!` and `mock_method!`, which
+/// need each method's argument and return types to be written twice. It
+/// takes the name of the mock `struct`, optionally prefixed by a visibility,
+/// followed by `as`, the `trait` to implement and the signatures of all of
+/// the trait's methods:
+///
+/// ```ignore
+/// trait TaskManager {
+///     fn max_threads(&self) -> u32;
+///     fn set_max_threads(&mut self, max_threads: u32);
+/// }
+///
+/// mock_impl_trait!(MockTaskManager as TaskManager {
+///     fn max_threads(&self) -> u32;
+///     fn set_max_threads(&mut self, max_threads: u32);
+/// });
+/// ```
+///
+/// The generated mock is the same as the one generated by `#[double::mock]`
+/// for the `trait`, and has the same limitations.
+#[proc_macro]
+pub fn mock_impl_trait(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as MockImplTrait);
+    let generated = generate_mock(
+        &input.vis,
+        &input.mock_name,
+        &input.trait_path,
+        &input.items);
+    TokenStream::from(generated.unwrap_or_else(|err| err.to_compile_error()))
+}
+
+// Generates the enum of a mock's methods and the mock's `interactions()` and
+// `interaction_summary()` methods. Used by `mock_trait!`, which can't derive
+// the enum's name from the mock's name or its variants from the method names.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __private_mock_trait_interactions_impl(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as MockInteractions);
+    TokenStream::from(generate_interactions(
+        &input.vis,
+        &input.mock_name,
+        &input.lifetimes,
+        &input.methods))
+}
+
+// Replaces each `[< ... >]` in the input with an identifier made by joining
+// the identifiers in it, e.g. `self.[<m_ $method>]` with `self.m_profit`.
+// Used by `double`'s `macro_rules` macros, which can't create identifiers,
+// to name the `m_` fields and `_mock` accessors of generated mocks.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __private_paste(input: TokenStream) -> TokenStream {
+    TokenStream::from(paste(TokenStream2::from(input)))
+}
+
+fn paste(input: TokenStream2) -> TokenStream2 {
+    input
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Group(group) => match pasted_ident(&group) {
+                Some(ident) => TokenTree::Ident(ident),
+                None => {
+                    let mut pasted = Group::new(group.delimiter(), paste(group.stream()));
+                    pasted.set_span(group.span());
+                    TokenTree::Group(pasted)
+                }
+            },
+            other => other,
+        })
+        .collect()
+}
+
+// Returns the identifier `group` is replaced with if it's a `[< ... >]`.
+fn pasted_ident(group: &Group) -> Option<Ident> {
+    if group.delimiter() != Delimiter::Bracket {
+        return None;
+    }
+    let tokens: Vec<TokenTree> = group.stream().into_iter().collect();
+    match (tokens.first(), tokens.last()) {
+        (Some(TokenTree::Punct(open)), Some(TokenTree::Punct(close)))
+            if tokens.len() > 2 && open.as_char() == '<' && close.as_char() == '>' => {}
+        _ => return None,
+    }
+
+    let mut name = String::new();
+    let mut span = None;
+    for token in tokens[1..tokens.len() - 1].iter().cloned() {
+        push_ident_parts(token, &mut name, &mut span);
+    }
+    Some(Ident::new(&name, span.unwrap_or_else(|| group.span())))
+}
+
+// Appends the identifiers in `token` to `name`. Identifiers passed to a
+// `macro_rules` macro as `$name:ident` may be wrapped in an invisible group.
+fn push_ident_parts(token: TokenTree, name: &mut String, span: &mut Option<Span>) {
+    match token {
+        TokenTree::Ident(ident) => {
+            name.push_str(&unraw(&ident));
+            span.get_or_insert(ident.span());
+        }
+        TokenTree::Group(ref group) if group.delimiter() == Delimiter::None => {
+            for inner in group.stream() {
+                push_ident_parts(inner, name, span);
+            }
+        }
+        other => panic!("`[< ... >]` can only contain identifiers, found `{}`", other),
+    }
+}
+
+// The input of `__private_mock_trait_interactions_impl!`: the mock's
+// visibility and name, its lifetime parameters in square brackets, if any,
+// and the names of its methods.
+struct MockInteractions {
+    vis: Visibility,
+    mock_name: Ident,
+    lifetimes: Vec<Lifetime>,
+    methods: Vec<Ident>,
+}
+
+impl Parse for MockInteractions {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let vis = input.parse()?;
+        let mock_name = input.parse()?;
+        let mut lifetimes = vec!();
+        if input.peek(syn::token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            let parsed = Punctuated::<Lifetime, Token![,]>::parse_terminated(&content)?;
+            lifetimes.extend(parsed);
+        }
+        let mut methods = vec!();
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            methods.push(input.parse()?);
+        }
+
+        Ok(MockInteractions { vis, mock_name, lifetimes, methods })
+    }
+}
+
+// The input of `mock_impl_trait!`.
+struct MockImplTrait {
+    vis: Visibility,
+    mock_name: Ident,
+    trait_path: Path,
+    items: Vec<TraitItem>,
+}
+
+impl Parse for MockImplTrait {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let vis = input.parse()?;
+        let mock_name = input.parse()?;
+        input.parse::<Token![as]>()?;
+        let trait_path = input.parse()?;
+
+        let content;
+        braced!(content in input);
+        let mut items = vec!();
+        while !content.is_empty() {
+            items.push(content.parse()?);
+        }
+
+        Ok(MockImplTrait { vis, mock_name, trait_path, items })
+    }
+}
+
+// A trait method, as stored by and passed to the mock.
+struct MockedMethod {
+    name: Ident,
+    // `&self` or `&mut self`
+    receiver: TokenStream2,
+    arg_names: Vec<Ident>,
+    arg_types: Vec<Type>,
+    stored_arg_types: Vec<Type>,
+    return_type: Option<Type>,
+}
+
+fn generate_mock(
+    vis: &Visibility,
+    mock_name: &Ident,
+    trait_path: &Path,
+    items: &[TraitItem],
+) -> Result<TokenStream2, Error> {
+    let methods = items
+        .iter()
+        .map(|trait_item| match trait_item {
+            TraitItem::Fn(method) => parse_method(method),
+            other => Err(Error::new(
+                other.span(),
+                "only methods can be mocked")),
+        })
+        .collect::<Result<Vec<MockedMethod>, Error>>()?;
+
+    let names: Vec<&Ident> = methods.iter().map(|m| &m.name).collect();
+    let fields: Vec<Ident> = methods.iter().map(|m| field_name(&m.name)).collect();
+    let args: Vec<TokenStream2> = methods
+        .iter()
+        .map(|m| {
+            let stored_arg_types = &m.stored_arg_types;
+            quote!((#(#stored_arg_types),*))
+        })
+        .collect();
+    let return_types: Vec<TokenStream2> = methods
+        .iter()
+        .map(|m| match m.return_type {
+            Some(ref return_type) => quote!(#return_type),
+            None => quote!(()),
+        })
+        .collect();
+    let mocked_methods = methods.iter().map(generate_mock_method);
+    let method_names: Vec<Ident> = methods.iter().map(|m| m.name.clone()).collect();
+    let interactions = generate_interactions(vis, mock_name, &[], &method_names);
+
+    Ok(quote! {
+        #[derive(Clone)]
+        #vis struct #mock_name {
+            #( #vis #fields: ::double::Mock<#args, #return_types>, )*
+        }
+
+        impl ::std::default::Default for #mock_name {
+            fn default() -> Self {
+                #[allow(unused_imports)]
+                use ::double::macros::{DefaultReturn, NoDefaultReturn};
+
+                Self {
+                    #(
+                        #fields: (&::double::macros::DefaultMock::<#args, #return_types>(
+                            ::std::marker::PhantomData)).default_mock(),
+                    )*
+                }
+            }
+        }
+
+        ::double::__private_mock_trait_accessors_impl!(#mock_name #(, #names #args: #return_types)*);
+        ::double::__private_mock_trait_debug_impl!(#mock_name #(, #names)*);
+        ::double::__private_mock_trait_new_impl!(#mock_name #(, #names: #return_types)*);
+        ::double::__private_mock_trait_deep_clone_impl!(#mock_name #(, #names)*);
+        ::double::__private_mock_trait_calls_impl!(#mock_name #(, #names)*);
+        #interactions
+        ::double::mock_method_args!(#mock_name #(, #names #args)*);
+
+        impl #trait_path for #mock_name {
+            #( #mocked_methods )*
+        }
+    })
+}
+
+fn parse_method(method: &TraitItemFn) -> Result<MockedMethod, Error> {
+    let sig = &method.sig;
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new(
+            sig.generics.span(),
+            "cannot mock methods with generic parameters"));
+    }
+    if sig.asyncness.is_some() {
+        return Err(Error::new(
+            sig.asyncness.span(),
+            "cannot mock async methods"));
+    }
+
+    let receiver = match sig.receiver() {
+        Some(receiver) if receiver.reference.is_some() && receiver.colon_token.is_none() => {
+            if receiver.mutability.is_some() {
+                quote!(&mut self)
+            } else {
+                quote!(&self)
+            }
+        }
+        _ => return Err(Error::new(
+            sig.span(),
+            "can only mock methods taking `&self` or `&mut self`")),
+    };
+
+    let mut arg_names = vec!();
+    let mut arg_types = vec!();
+    for (index, input) in sig.inputs.iter().skip(1).enumerate() {
+        if let FnArg::Typed(arg) = input {
+            let name = match *arg.pat {
+                Pat::Ident(ref pat) => pat.ident.clone(),
+                _ => format_ident!("arg{}", index),
+            };
+            arg_names.push(name);
+            arg_types.push((*arg.ty).clone());
+        }
+    }
+    let stored_arg_types = arg_types
+        .iter()
+        .map(stored_type)
+        .collect::<Result<Vec<Type>, Error>>()?;
+
+    let return_type = match sig.output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ref return_type) => {
+            if let Type::ImplTrait(_) = **return_type {
+                return Err(Error::new(
+                    return_type.span(),
+                    "cannot mock methods returning `impl Trait`"));
+            }
+            Some((**return_type).clone())
+        }
+    };
+
+    Ok(MockedMethod {
+        name: sig.ident.clone(),
+        receiver,
+        arg_names,
+        arg_types,
+        stored_arg_types,
+        return_type,
+    })
+}
+
+// Returns the owned type an argument of type `ty` is stored as, matching the
+// conversions made by `double::macros::IntoOwnedArg`.
+fn stored_type(ty: &Type) -> Result<Type, Error> {
+    if let Some(borrowed) = cow_borrowed_type(ty) {
+        return Ok(owned_type(borrowed).unwrap_or_else(|| ty.clone()));
+    }
+    let reference = match *ty {
+        Type::Reference(ref reference) => reference,
+        _ => return Ok(ty.clone()),
+    };
+    if reference.mutability.is_some() {
+        return Err(Error::new(
+            ty.span(),
+            "cannot mock methods taking `&mut` arguments"));
+    }
+
+    if let Type::Slice(ref slice) = *reference.elem {
+        if let Type::Reference(ref item) = *slice.elem {
+            if is_named(&item.elem, "str") {
+                return Ok(syn::parse_quote!(Vec<String>));
+            }
+        }
+    }
+    let elem = &*reference.elem;
+    Ok(owned_type(elem).unwrap_or_else(|| elem.clone()))
+}
+
+// Returns the owned version of the unsized type `ty`, such as `String` for
+// `str`, or `None` if `ty` isn't one of the unsized types with a conversion.
+fn owned_type(ty: &Type) -> Option<Type> {
+    if is_named(ty, "str") {
+        Some(syn::parse_quote!(String))
+    } else if is_named(ty, "Path") {
+        Some(syn::parse_quote!(::std::path::PathBuf))
+    } else if is_named(ty, "OsStr") {
+        Some(syn::parse_quote!(::std::ffi::OsString))
+    } else if let Type::Slice(ref slice) = *ty {
+        let item = &slice.elem;
+        Some(syn::parse_quote!(Vec<#item>))
+    } else {
+        None
+    }
+}
+
+// Returns `T` if `ty` is `Cow<'_, T>`.
+fn cow_borrowed_type(ty: &Type) -> Option<&Type> {
+    let path = match *ty {
+        Type::Path(ref path) if path.qself.is_none() => &path.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Cow" {
+        return None;
+    }
+    match segment.arguments {
+        PathArguments::AngleBracketed(ref arguments) => {
+            arguments.args.iter().find_map(|argument| match *argument {
+                GenericArgument::Type(ref borrowed) => Some(borrowed),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+// Returns true if `ty` is a path whose last segment is `name`, such as
+// `Path` or `std::path::Path` for `"Path"`.
+fn is_named(ty: &Type, name: &str) -> bool {
+    match *ty {
+        Type::Path(ref path) if path.qself.is_none() => match path.path.segments.last() {
+            Some(segment) => segment.ident == name && segment.arguments.is_empty(),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+fn generate_mock_method(method: &MockedMethod) -> TokenStream2 {
+    let MockedMethod { name, receiver, arg_names, arg_types, return_type, .. } = method;
+    match return_type {
+        Some(return_type) => quote! {
+            ::double::mock_method!(#name(#receiver #(, #arg_names: #arg_types)*) -> #return_type);
+        },
+        None => quote! {
+            ::double::mock_method!(#name(#receiver #(, #arg_names: #arg_types)*));
+        },
+    }
+}
+
+// Generates an enum with a variant for each of the mock's methods, named
+// `Mock...Method`, and the mock's methods for listing the calls made to all of
+// its `Mock` fields in order.
+fn generate_interactions(
+    vis: &Visibility,
+    mock_name: &Ident,
+    lifetimes: &[Lifetime],
+    methods: &[Ident],
+) -> TokenStream2 {
+    let enum_name = format_ident!("{}Method", mock_name);
+    let variants: Vec<Ident> = methods.iter().map(variant_name).collect();
+    let method_names: Vec<String> = methods.iter().map(unraw).collect();
+    let fields: Vec<Ident> = methods.iter().map(field_name).collect();
+    let enum_doc = format!("The methods of `{}`. See `{}::interactions`.", mock_name, mock_name);
+
+    quote! {
+        #[doc = #enum_doc]
+        #[allow(dead_code)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #vis enum #enum_name {
+            #( #variants, )*
+        }
+
+        impl #enum_name {
+            /// Returns the name of the method.
+            #[allow(dead_code)]
+            pub fn name(&self) -> &'static str {
+                match *self {
+                    #( #enum_name::#variants => #method_names, )*
+                }
+            }
+        }
+
+        impl<#(#lifetimes),*> #mock_name<#(#lifetimes),*> {
+            /// Returns every call made to the mock's methods, in the order
+            /// the calls were made. Each call's arguments are formatted using
+            /// `Debug`, or as `..` if they don't implement `Debug`.
+            #[allow(dead_code)]
+            pub fn interactions(&self) -> Vec<(#enum_name, String)> {
+                #[allow(unused_imports)]
+                use ::double::macros::{FullDebugCalls, SummaryDebugCalls};
+
+                let mut interactions: Vec<(usize, #enum_name, String)> = vec!();
+                #(
+                    interactions.extend(
+                        (&::double::macros::DebugCalls(&self.#fields))
+                            .debug_calls()
+                            .into_iter()
+                            .map(|(timestamp, args)| (timestamp, #enum_name::#variants, args)));
+                )*
+                interactions.sort_by_key(|interaction| interaction.0);
+                interactions.into_iter().map(|(_, method, args)| (method, args)).collect()
+            }
+
+            /// Returns a readable listing of `interactions()`, with one line
+            /// per call, for use in assertion messages.
+            #[allow(dead_code)]
+            pub fn interaction_summary(&self) -> String {
+                let interactions = self.interactions();
+                let mut summary = format!(
+                    "{} interaction{}",
+                    interactions.len(),
+                    if interactions.len() == 1 { "" } else { "s" });
+                for (i, (method, args)) in interactions.iter().enumerate() {
+                    summary.push_str(&format!("\n  {}: {}: {}", i, method.name(), args));
+                }
+                summary
+            }
+        }
+    }
+}
+
+// Returns the name of the `Mock` field of `method`, such as `m_get_user` for
+// `get_user`.
+fn field_name(method: &Ident) -> Ident {
+    format_ident!("m_{}", unraw(method), span = method.span())
+}
+
+// Returns the name of `method` without the `r#` prefix of raw identifiers.
+fn unraw(method: &Ident) -> String {
+    let name = method.to_string();
+    name.trim_start_matches("r#").to_owned()
+}
+
+// Returns the `CamelCase` name of the enum variant for `method`, such as
+// `SetMaxThreads` for `set_max_threads`.
+fn variant_name(method: &Ident) -> Ident {
+    let name: String = unraw(method)
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect();
+    Ident::new(&name, method.span())
+}
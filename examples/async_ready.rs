@@ -0,0 +1,74 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use double::mock_trait;
+use tokio::runtime::Runtime;
+
+#[async_trait]
+trait Fetcher {
+    async fn fetch(&self, id: u32) -> u32;
+}
+
+mock_trait!(
+    MockFetcher,
+    fetch(u32) -> u32);
+
+// The method is written out with the signature `#[async_trait]` generates for
+// `fetch`, so its body can do more than pass its arguments to the mock, such
+// as validate them. `call_ready` records the call and returns the mock's
+// return value as an already completed future.
+impl Fetcher for MockFetcher {
+    fn fetch<'life0, 'async_trait>(&'life0 self, id: u32)
+        -> Pin<Box<dyn Future<Output = u32> + Send + 'async_trait>>
+        where 'life0: 'async_trait, Self: 'async_trait
+    {
+        assert!(id > 0, "ids start from 1");
+        Box::pin(self.fetch_mock().call_ready(id))
+    }
+}
+
+// Code under test
+async fn fetch_total<F: Fetcher>(fetcher: &F, ids: &[u32]) -> u32 {
+    let mut total = 0;
+    for id in ids {
+        total += fetcher.fetch(*id).await;
+    }
+    total
+}
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_current_thread().build().unwrap()
+}
+
+fn test_awaiting_ready_values() {
+    // GIVEN:
+    let mock = MockFetcher::default();
+    mock.fetch_mock().return_value_for(1u32, 10u32);
+    mock.fetch_mock().return_value_for(2u32, 20u32);
+
+    // WHEN:
+    let total = runtime().block_on(fetch_total(&mock, &[1, 2, 3]));
+
+    // THEN:
+    assert_eq!(30, total);
+    assert!(mock.fetch_mock().has_calls_exactly_in_order(vec!(1u32, 2, 3)));
+}
+
+fn test_calls_are_recorded_before_futures_are_awaited() {
+    // GIVEN:
+    let mock = MockFetcher::default();
+    mock.fetch_mock().return_value(5u32);
+
+    // WHEN:
+    let future = mock.fetch(1);
+
+    // THEN:
+    assert!(mock.fetch_mock().called_with(1u32));
+    assert_eq!(5, runtime().block_on(future));
+}
+
+fn main() {
+    test_awaiting_ready_values();
+    test_calls_are_recorded_before_futures_are_awaited();
+}
@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use double::{mock_method, mock_trait};
+use tokio::runtime::Runtime;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Payload {
+    id: u64,
+    body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FetchError {
+    NotFound,
+}
+
+#[async_trait]
+trait Fetcher {
+    async fn fetch(&self, id: u64) -> Result<Payload, FetchError>;
+    async fn evict(&mut self, id: u64);
+}
+
+mock_trait!(
+    MockFetcher,
+    fetch(u64) -> Result<Payload, FetchError> = Err(FetchError::NotFound),
+    evict(u64) -> ());
+#[async_trait]
+impl Fetcher for MockFetcher {
+    mock_method!(async fetch(&self, id: u64) -> Result<Payload, FetchError>);
+    mock_method!(async evict(&mut self, id: u64));
+}
+
+// Receives keys as strings, which the custom body parses before passing them
+// to the underlying mock.
+#[async_trait]
+trait Lookup {
+    async fn lookup(&self, key: String) -> Option<u64>;
+}
+
+mock_trait!(
+    MockLookup,
+    lookup(u64) -> Option<u64>);
+#[async_trait]
+impl Lookup for MockLookup {
+    mock_method!(async lookup(&self, key: String) -> Option<u64>, self, {
+        self.lookup_mock().call(key.parse().unwrap())
+    });
+}
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_current_thread().build().unwrap()
+}
+
+fn test_awaiting_async_method() {
+    // GIVEN:
+    let mock = MockFetcher::default();
+    let payload = Payload { id: 42, body: "hello".to_owned() };
+    mock.fetch_mock().return_value_for(42u64, Ok(payload.clone()));
+
+    // WHEN:
+    let (found, missing) = runtime().block_on(async {
+        (mock.fetch(42).await, mock.fetch(7).await)
+    });
+
+    // THEN:
+    assert_eq!(Ok(payload), found);
+    assert_eq!(Err(FetchError::NotFound), missing);
+    assert!(mock.fetch_mock().called_with(42u64));
+    assert!(mock.fetch_mock().has_calls_exactly_in_order(vec!(42u64, 7)));
+}
+
+fn test_awaiting_async_method_with_mut_self() {
+    // GIVEN:
+    let mut mock = MockFetcher::default();
+
+    // WHEN:
+    runtime().block_on(async { mock.evict(3).await });
+
+    // THEN:
+    assert!(mock.evict_mock().called_with(3u64));
+    assert!(!mock.fetch_mock().called());
+}
+
+fn test_awaiting_async_method_with_custom_body() {
+    // GIVEN:
+    let mock = MockLookup::default();
+    mock.lookup_mock().return_value_for(5u64, Some(25));
+
+    // WHEN:
+    let result = runtime().block_on(async { mock.lookup("5".to_owned()).await });
+
+    // THEN:
+    assert_eq!(Some(25), result);
+    assert!(mock.lookup_mock().called_with(5u64));
+}
+
+fn main() {
+    test_awaiting_async_method();
+    test_awaiting_async_method_with_mut_self();
+    test_awaiting_async_method_with_custom_body();
+}
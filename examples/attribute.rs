@@ -0,0 +1,69 @@
+extern crate double;
+
+use std::panic::{self, AssertUnwindSafe};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Session {
+    user_id: i32,
+}
+
+// `#[double::mock]` generates `MockUserStore` from the trait definition.
+#[double::mock]
+trait UserStore {
+    fn get_user(&self, id: i32) -> Result<String, String>;
+    fn find_user(&self, name: &str) -> Option<i32>;
+    fn rename_users(&mut self, ids: &[i32], name: &str);
+    fn open_session(&self, id: &i32) -> Session;
+}
+
+// Code under test
+fn rename_user<S: UserStore>(store: &mut S, old_name: &str, new_name: &str) -> Result<String, String> {
+    let id = store.find_user(old_name).ok_or_else(|| "no such user".to_owned())?;
+    store.rename_users(&[id], new_name);
+    store.get_user(id)
+}
+
+fn test_renaming_an_existing_user() {
+    // GIVEN:
+    let mut store = MockUserStore::default();
+    store.find_user_mock().return_value(Some(42));
+    store.get_user_mock().return_value(Ok("Don".to_owned()));
+
+    // WHEN:
+    let result = rename_user(&mut store, "Donald", "Don");
+
+    // THEN:
+    assert_eq!(Ok("Don".to_owned()), result);
+    assert!(store.find_user_mock().called_with("Donald".to_owned()));
+    assert!(store.rename_users_mock().called_with((vec!(42), "Don".to_owned())));
+    assert!(store.get_user_mock().called_with(42));
+}
+
+fn test_renaming_a_missing_user() {
+    // GIVEN:
+    let mut store = MockUserStore::default();
+
+    // WHEN:
+    let result = rename_user(&mut store, "Donald", "Don");
+
+    // THEN:
+    assert_eq!(Err("no such user".to_owned()), result);
+    assert_eq!(1, store.total_calls());
+}
+
+fn test_return_type_without_default() {
+    // `Session` doesn't implement `Default`, so calling `open_session()`
+    // panics until a return value is configured.
+    let store = MockUserStore::default();
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| store.open_session(&42))).is_err());
+
+    store.open_session_mock().return_value(Session { user_id: 42 });
+    assert_eq!(Session { user_id: 42 }, store.open_session(&42));
+    assert!(store.open_session_mock().has_calls_exactly(vec!(42, 42)));
+}
+
+fn main() {
+    test_renaming_an_existing_user();
+    test_renaming_a_missing_user();
+    test_return_type_without_default();
+}
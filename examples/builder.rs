@@ -0,0 +1,162 @@
+#[macro_use]
+extern crate double;
+
+trait RequestBuilder {
+    fn header(self, name: &str, value: &str) -> Self;
+    fn timeout(self, seconds: u32) -> Self;
+    fn body(self, body: &str) -> Self;
+    fn send(self) -> u16;
+}
+
+// Methods returning `Self` are listed with `-> Self`. Their mocks record
+// calls, but don't store return values.
+mock_trait!(
+    MockRequestBuilder,
+    header(String, String) -> Self,
+    timeout(u32) -> Self,
+    body(String) -> Self,
+    send(()) -> u16);
+impl RequestBuilder for MockRequestBuilder {
+    mock_method!(header(self, name: &str, value: &str) -> Self);
+    mock_method!(timeout(self, seconds: u32) -> Self);
+    mock_method!(body(self, body: &str) -> Self);
+    mock_method!(send(self) -> u16);
+}
+
+trait Connection {
+    fn fork(&self) -> Self;
+    fn query(&self, sql: &str) -> u32;
+}
+
+mock_trait!(
+    MockConnection,
+    fork(()) -> Self,
+    query(String) -> u32);
+impl Connection for MockConnection {
+    mock_method!(fork(&self) -> Self);
+    mock_method!(query(&self, sql: &str) -> u32);
+}
+
+trait Shape {
+    fn area(&self) -> u32;
+    fn boxed_clone(&self) -> Box<dyn Shape>;
+}
+
+// `Box<dyn Shape>` can't be cloned, so the mock stores the `MockShape` to box
+// instead. It's wrapped in an `Option`, since the `Default` value of a
+// `MockShape` can't contain another `MockShape`.
+mock_trait!(
+    MockShape,
+    area(()) -> u32,
+    boxed_clone(()) -> Option<MockShape>);
+impl Shape for MockShape {
+    mock_method!(area(&self) -> u32);
+    mock_method!(boxed_clone(&self) -> Box<dyn Shape>, self, {
+        Box::new(self.boxed_clone_mock().call(()).expect("no clone configured"))
+    });
+}
+
+// Code under test
+fn post<B: RequestBuilder>(builder: B, payload: &str) -> u16 {
+    builder
+        .header("Content-Type", "application/json")
+        .timeout(30)
+        .body(payload)
+        .send()
+}
+
+fn count_in_parallel<C: Connection>(connection: &C, tables: &[&str]) -> u32 {
+    tables
+        .iter()
+        .map(|table| connection.fork().query(&format!("SELECT COUNT(*) FROM {}", table)))
+        .sum()
+}
+
+fn duplicate(shape: &dyn Shape, copies: usize) -> Vec<Box<dyn Shape>> {
+    (0..copies).map(|_| shape.boxed_clone()).collect()
+}
+
+fn test_chaining_builder_calls() {
+    // GIVEN:
+    let builder = MockRequestBuilder::default();
+    builder.send_mock().return_value(201u16);
+
+    // WHEN:
+    // The mock is consumed by the chain, but clones share its calls.
+    let status = post(builder.clone(), "{}");
+
+    // THEN:
+    assert_eq!(201, status);
+    assert!(builder.header_mock().called_with(
+        ("Content-Type".to_owned(), "application/json".to_owned())));
+    assert!(builder.timeout_mock().called_with(30u32));
+    assert!(builder.body_mock().called_with("{}".to_owned()));
+    assert_eq!(
+        builder.interactions(),
+        vec!(
+            (MockRequestBuilderMethod::Header,
+                "(\"Content-Type\", \"application/json\")".to_owned()),
+            (MockRequestBuilderMethod::Timeout, "30".to_owned()),
+            (MockRequestBuilderMethod::Body, "\"{}\"".to_owned()),
+            (MockRequestBuilderMethod::Send, "()".to_owned())));
+}
+
+fn test_returning_self_prototype() {
+    // GIVEN:
+    let connection = MockConnection::default();
+    let forked = MockConnection::default();
+    forked.query_mock().return_value(10u32);
+    connection.fork_mock().return_self_prototype(forked.clone());
+
+    // WHEN:
+    let count = count_in_parallel(&connection, &["users", "posts"]);
+
+    // THEN:
+    assert_eq!(20, count);
+    assert_eq!(2, connection.fork_mock().num_calls());
+    assert!(!connection.query_mock().called());
+    assert!(forked.query_mock().has_calls_exactly_in_order(vec!(
+        "SELECT COUNT(*) FROM users".to_owned(),
+        "SELECT COUNT(*) FROM posts".to_owned())));
+}
+
+fn test_returning_self_default() {
+    // GIVEN:
+    let connection = MockConnection::default();
+
+    // WHEN:
+    let forked = connection.fork();
+    forked.query("SELECT 1");
+
+    // THEN:
+    // Without a prototype, a new mock is returned.
+    assert!(connection.fork_mock().called());
+    assert!(!connection.query_mock().called());
+    assert!(forked.query_mock().called());
+}
+
+fn test_returning_fresh_boxed_mocks() {
+    // GIVEN:
+    let shape = MockShape::default();
+    shape.boxed_clone_mock().use_closure(Box::new(|()| {
+        let clone = MockShape::default();
+        clone.area_mock().return_value(12u32);
+        Some(clone)
+    }));
+
+    // WHEN:
+    let copies = duplicate(&shape, 3);
+
+    // THEN:
+    assert_eq!(3, copies.len());
+    assert_eq!(36, copies.iter().map(|copy| copy.area()).sum::<u32>());
+    assert_eq!(3, shape.boxed_clone_mock().num_calls());
+    assert!(!shape.area_mock().called());
+}
+
+fn main() {
+    test_chaining_builder_calls();
+    test_returning_self_prototype();
+    test_returning_self_default();
+    test_returning_fresh_boxed_mocks();
+}
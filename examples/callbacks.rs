@@ -0,0 +1,61 @@
+#[macro_use]
+extern crate double;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Message {
+    id: u32,
+    body: String,
+}
+
+trait Broker {
+    fn subscribe(&self, topic: &str, on_msg: Box<dyn Fn(Message)>) -> bool;
+}
+
+// `on_msg` can't be recorded, so it's listed after the `;` and passed to the
+// live handler instead.
+mock_trait!(
+    MockBroker,
+    subscribe(String) -> bool);
+impl Broker for MockBroker {
+    mock_method!(subscribe(&self, topic: &str; on_msg: Box<dyn Fn(Message)>) -> bool);
+}
+
+// Code under test
+struct Subscriber {
+    processed: Rc<RefCell<Vec<String>>>,
+}
+
+impl Subscriber {
+    fn listen<B: Broker>(&self, broker: &B, topic: &str) -> bool {
+        let processed = self.processed.clone();
+        broker.subscribe(topic, Box::new(move |msg| {
+            processed.borrow_mut().push(format!("{}: {}", msg.id, msg.body));
+        }))
+    }
+}
+
+fn test_subscriber_processes_delivered_messages() {
+    // GIVEN:
+    let broker = MockBroker::default();
+    broker.subscribe_mock().return_value(true);
+    broker.subscribe_mock().use_live_handler(Box::new(|_topic: String, on_msg: Box<dyn Fn(Message)>| {
+        on_msg(Message { id: 1, body: "hello".to_owned() });
+        on_msg(Message { id: 2, body: "world".to_owned() });
+    }));
+    let subscriber = Subscriber { processed: Rc::new(RefCell::new(vec!())) };
+
+    // WHEN:
+    let subscribed = subscriber.listen(&broker, "greetings");
+
+    // THEN:
+    assert!(subscribed);
+    assert_eq!(*subscriber.processed.borrow(), vec!("1: hello", "2: world"));
+    assert!(broker.subscribe_mock().called_with("greetings".to_owned()));
+}
+
+fn main() {
+    test_subscriber_processes_delivered_messages();
+}
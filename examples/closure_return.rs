@@ -0,0 +1,60 @@
+#[macro_use]
+extern crate double;
+
+use std::rc::Rc;
+
+// Closures don't implement `Debug` or `Default`, but the mock's return type
+// has to implement `Clone`, so they're returned behind an `Rc`.
+type Handler = Rc<dyn Fn(&str) -> String>;
+
+trait Router {
+    fn route(&self, path: &str) -> Handler;
+    fn fallback(&self) -> Option<Handler>;
+}
+
+// Generated mocks implement `Debug` without requiring their return types to,
+// so no escape hatch is needed for closure types.
+mock_trait_no_default!(
+    MockRouter,
+    route(String) -> Handler,
+    fallback(()) -> Option<Handler>);
+impl Router for MockRouter {
+    mock_method!(route(&self, path: &str) -> Handler);
+    mock_method!(fallback(&self) -> Option<Handler>);
+}
+
+// Code under test
+fn serve<R: Router>(router: &R, path: &str, body: &str) -> String {
+    match router.fallback() {
+        Some(fallback) if path.is_empty() => fallback(body),
+        _ => router.route(path)(body),
+    }
+}
+
+fn test_serving_with_configured_handlers() {
+    // GIVEN:
+    let router = MockRouter::builder()
+        .route(Rc::new(|_: &str| "404".to_owned()) as Handler)
+        .fallback(None)
+        .build();
+    router.route_mock().return_value_for(
+        "/echo".to_owned(),
+        Rc::new(|body: &str| body.to_owned()) as Handler);
+
+    // WHEN:
+    let echoed = serve(&router, "/echo", "hello");
+    let missing = serve(&router, "/missing", "hello");
+
+    // THEN:
+    assert_eq!("hello", echoed);
+    assert_eq!("404", missing);
+    assert!(router.route_mock().has_calls_exactly_in_order(vec!(
+        "/echo".to_owned(),
+        "/missing".to_owned())));
+    // The mock can still be formatted, e.g. by `assert_eq!` failures.
+    assert!(format!("{:?}", router).starts_with("MockRouter { route: Mock {"));
+}
+
+fn main() {
+    test_serving_with_configured_handlers();
+}
@@ -0,0 +1,79 @@
+#[macro_use]
+extern crate double;
+
+// Mocks shared by several test modules. The `pub` mock structs expose their
+// per-method `Mock` fields, so they can be configured and verified by the
+// modules that use them.
+mod test_utils {
+    pub trait TaskManager {
+        fn max_threads(&self) -> u32;
+        fn set_max_threads(&mut self, max_threads: u32);
+    }
+
+    pub trait UserStore {
+        fn get_user(&self, id: i32) -> Result<String, String>;
+    }
+
+    mock_trait!(
+        pub MockTaskManager,
+        max_threads(()) -> u32,
+        set_max_threads(u32) -> ());
+    impl TaskManager for MockTaskManager {
+        mock_method!(max_threads(&self) -> u32);
+        mock_method!(set_max_threads(&mut self, max_threads: u32));
+    }
+
+    mock_trait_no_default!(
+        pub(crate) MockUserStore,
+        get_user(i32) -> Result<String, String>);
+    impl UserStore for MockUserStore {
+        mock_method!(get_user(&self, id: i32) -> Result<String, String>);
+    }
+}
+
+mod tests {
+    use crate::test_utils::{MockTaskManager, MockUserStore, TaskManager, UserStore};
+
+    // Code under test
+    fn double_max_threads(manager: &mut dyn TaskManager) {
+        let max_threads = manager.max_threads();
+        manager.set_max_threads(max_threads * 2);
+    }
+
+    fn username(store: &dyn UserStore, id: i32) -> String {
+        store.get_user(id).unwrap_or_else(|_| "unknown".to_owned())
+    }
+
+    pub fn test_pub_mock_configured_from_sibling_module() {
+        // GIVEN:
+        let mut mock = MockTaskManager::default();
+        mock.max_threads_mock().return_value(4u32);
+
+        // WHEN:
+        double_max_threads(&mut mock);
+
+        // THEN:
+        assert!(mock.max_threads_mock().called());
+        assert!(mock.set_max_threads_mock().called_with(8u32));
+    }
+
+    pub fn test_pub_crate_mock_configured_from_sibling_module() {
+        // GIVEN:
+        let mock = MockUserStore::new(Err("no such user".to_owned()));
+        mock.get_user_mock().return_value_for(42, Ok("donald".to_owned()));
+
+        // WHEN:
+        let found = username(&mock, 42);
+        let missing = username(&mock, 7);
+
+        // THEN:
+        assert_eq!("donald", found);
+        assert_eq!("unknown", missing);
+        assert!(mock.get_user_mock().has_calls_exactly_in_order(vec!(42, 7)));
+    }
+}
+
+fn main() {
+    tests::test_pub_mock_configured_from_sibling_module();
+    tests::test_pub_crate_mock_configured_from_sibling_module();
+}
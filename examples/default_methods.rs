@@ -0,0 +1,99 @@
+#[macro_use]
+extern crate double;
+
+// The body of `Greeter::greeting`'s default implementation. It's a free
+// function so mocks can call through to it.
+fn default_greeting(name: &str) -> String {
+    format!("Hello, {}!", name)
+}
+
+trait Greeter {
+    fn name(&self, id: u32) -> String;
+
+    // default methods
+    fn greeting(&self, name: &str) -> String {
+        default_greeting(name)
+    }
+
+    fn greet(&self, id: u32) -> String {
+        self.greeting(&self.name(id))
+    }
+}
+
+// Code under test
+fn greet_all(greeter: &dyn Greeter, ids: &[u32]) -> Vec<String> {
+    ids.iter().map(|id| greeter.greet(*id)).collect()
+}
+
+// Only the required method is mocked. The real default implementations of
+// `greeting` and `greet` run, and their calls are not recorded.
+mock_trait!(
+    MockNameGreeter,
+    name(u32) -> String);
+impl Greeter for MockNameGreeter {
+    mock_method!(name(&self, id: u32) -> String);
+}
+
+// Default methods are mocked like any other method. The default
+// implementations are replaced, so they no longer run.
+mock_trait!(
+    MockGreeter,
+    name(u32) -> String,
+    greeting(String) -> String,
+    greet(u32) -> String);
+impl Greeter for MockGreeter {
+    mock_method!(name(&self, id: u32) -> String);
+    mock_method!(greeting(&self, name: &str) -> String);
+    mock_method!(greet(&self, id: u32) -> String);
+}
+
+fn test_unmocked_default_methods_run() {
+    // GIVEN:
+    let greeter = MockNameGreeter::default();
+    greeter.name_mock().return_value_for(1u32, "Donald");
+    greeter.name_mock().return_value_for(2u32, "Mary");
+
+    // WHEN:
+    let greetings = greet_all(&greeter, &[1, 2]);
+
+    // THEN:
+    assert_eq!(vec!("Hello, Donald!", "Hello, Mary!"), greetings);
+    assert!(greeter.name_mock().has_calls_exactly_in_order(vec!(1u32, 2)));
+}
+
+fn test_mocked_default_methods_are_recorded() {
+    // GIVEN:
+    let greeter = MockGreeter::default();
+    greeter.greet_mock().return_value("Hi!");
+
+    // WHEN:
+    let greetings = greet_all(&greeter, &[1, 2]);
+
+    // THEN:
+    assert_eq!(vec!("Hi!", "Hi!"), greetings);
+    assert!(greeter.greet_mock().has_calls_exactly_in_order(vec!(1u32, 2)));
+    assert!(!greeter.name_mock().called());
+    assert!(!greeter.greeting_mock().called());
+}
+
+fn test_mocked_default_method_calls_through() {
+    // GIVEN:
+    let greeter = MockGreeter::default();
+    greeter.greeting_mock().use_fn(|name| default_greeting(&name));
+    greeter.greeting_mock().return_value_for("Mary", "Hi, Mary!");
+
+    // WHEN:
+    let donald = greeter.greeting("Donald");
+    let mary = greeter.greeting("Mary");
+
+    // THEN:
+    assert_eq!("Hello, Donald!", donald);
+    assert_eq!("Hi, Mary!", mary);
+    assert!(greeter.greeting_mock().has_calls_exactly_in_order(vec!("Donald", "Mary")));
+}
+
+fn main() {
+    test_unmocked_default_methods_run();
+    test_mocked_default_methods_are_recorded();
+    test_mocked_default_method_calls_through();
+}
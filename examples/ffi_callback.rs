@@ -0,0 +1,71 @@
+#[macro_use]
+extern crate double;
+
+use std::os::raw::c_void;
+
+use double::ffi;
+
+type Callback = extern "C" fn(*mut c_void, u32);
+
+// C-style event source, which notifies registered callbacks of new events.
+trait EventSource {
+    fn register(&self, cb: Callback, user_data: *mut c_void);
+}
+
+// Code under test. Registers itself as the user data of a callback that sums
+// the values of all received events.
+struct EventTotaller {
+    total: u32,
+}
+
+impl EventTotaller {
+    fn new() -> Self {
+        EventTotaller { total: 0 }
+    }
+
+    fn subscribe(&mut self, source: &dyn EventSource) {
+        source.register(
+            EventTotaller::on_event,
+            self as *mut EventTotaller as *mut c_void);
+    }
+
+    extern "C" fn on_event(user_data: *mut c_void, value: u32) {
+        let totaller = unsafe { &mut *(user_data as *mut EventTotaller) };
+        totaller.total += value;
+    }
+}
+
+mock_trait!(
+    MockEventSource,
+    // store the user data pointer as an address
+    register(Callback, usize) -> ());
+impl EventSource for MockEventSource {
+    mock_method!(register(&self, cb: Callback, user_data: *mut c_void), self, {
+        self.register_mock().call((cb, ffi::addr(user_data)))
+    });
+}
+
+fn test_callback_registered_and_driven() {
+    // GIVEN:
+    let source = MockEventSource::default();
+    let mut totaller = EventTotaller::new();
+
+    // WHEN:
+    totaller.subscribe(&source);
+
+    // THEN:
+    // the totaller registered itself as the callback's user data
+    let (callback, user_data) = ffi::last_captured_callback(source.register_mock())
+        .expect("no callback registered");
+    assert_eq!(source.register_mock().num_calls(), 1);
+    assert_eq!(user_data, ffi::addr(&totaller as *const EventTotaller));
+
+    // driving the captured callback updates the totaller
+    callback(ffi::ptr(user_data), 10);
+    callback(ffi::ptr(user_data), 32);
+    assert_eq!(totaller.total, 42);
+}
+
+fn main() {
+    test_callback_registered_and_driven();
+}
@@ -0,0 +1,116 @@
+#[macro_use]
+extern crate double;
+
+use double::mock::KeyedMock;
+
+trait Thermostat {
+    fn set_target(&self, celsius: f64) -> bool;
+    fn move_to(&self, x: f32, y: f32);
+}
+
+// `f64` and `f32` aren't `Eq` or `Hash`, so `mock_trait!` can't be used. The
+// mock is written by hand, using `KeyedMock`s that key each call on a
+// rounded value or on the arguments' bit patterns.
+#[derive(Clone)]
+struct MockThermostat {
+    m_set_target: KeyedMock<f64, i64, bool>,
+    m_move_to: KeyedMock<(f32, f32), (u32, u32), ()>,
+}
+
+impl Default for MockThermostat {
+    fn default() -> Self {
+        MockThermostat {
+            // Targets are compared to a tenth of a degree.
+            m_set_target: KeyedMock::new(
+                false,
+                Box::new(|celsius: &f64| (celsius * 10.0).round() as i64)),
+            m_move_to: KeyedMock::new(
+                (),
+                Box::new(|&(x, y): &(f32, f32)| (x.to_bits(), y.to_bits()))),
+        }
+    }
+}
+
+// Lets `mock_method!` generate the bodies of the hand-written mock's methods,
+// which pass their arguments to the field named after the method with an `m_`
+// prefix.
+mock_method_args!(
+    MockThermostat,
+    set_target(f64),
+    move_to(f32, f32));
+
+impl Thermostat for MockThermostat {
+    mock_method!(set_target(&self, celsius: f64) -> bool);
+    mock_method!(move_to(&self, x: f32, y: f32));
+}
+
+// Code under test
+fn warm_up<T: Thermostat>(thermostat: &T, from: f64, to: f64, step: f64) -> usize {
+    let mut target = from;
+    let mut accepted = 0;
+    while target <= to {
+        if thermostat.set_target(target) {
+            accepted += 1;
+        }
+        target += step;
+    }
+    accepted
+}
+
+fn test_keying_calls_on_rounded_values() {
+    // GIVEN:
+    let thermostat = MockThermostat::default();
+    thermostat.m_set_target.return_value(true);
+    thermostat.m_set_target.return_value_for(20.3, false);
+
+    // WHEN:
+    // Repeatedly adding 0.1 doesn't give exactly 20.3.
+    let accepted = warm_up(&thermostat, 20.0, 20.45, 0.1);
+
+    // THEN:
+    assert_eq!(4, accepted);
+    assert_eq!(5, thermostat.m_set_target.num_calls());
+    assert!(thermostat.m_set_target.called_with(20.3));
+    assert!(!thermostat.m_set_target.called_with(20.5));
+    assert!(thermostat.m_set_target.has_calls_exactly_in_order(
+        vec!(20.0, 20.1, 20.2, 20.3, 20.4)));
+    assert!(thermostat.m_set_target.calls()[3] != 20.3);
+    assert!(thermostat.m_set_target.key_mock().has_calls_exactly_in_order(
+        vec!(200, 201, 202, 203, 204)));
+}
+
+fn test_keying_calls_on_bit_patterns() {
+    // GIVEN:
+    let thermostat = MockThermostat::default();
+
+    // WHEN:
+    thermostat.move_to(1.5, -0.0);
+    thermostat.move_to(f32::NAN, 2.0);
+
+    // THEN:
+    // Unlike `==`, bit patterns tell zeroes apart and match NaNs.
+    assert!(thermostat.m_move_to.called_with((1.5, -0.0)));
+    assert!(!thermostat.m_move_to.called_with((1.5, 0.0)));
+    assert!(thermostat.m_move_to.called_with((f32::NAN, 2.0)));
+    assert_eq!(thermostat.m_move_to.calls()[0], (1.5, 0.0));
+}
+
+fn test_resetting_calls() {
+    // GIVEN:
+    let thermostat = MockThermostat::default();
+    thermostat.set_target(18.0);
+
+    // WHEN:
+    thermostat.m_set_target.reset_calls();
+
+    // THEN:
+    assert!(!thermostat.m_set_target.called());
+    assert!(thermostat.m_set_target.calls().is_empty());
+    assert!(!thermostat.m_set_target.key_mock().called());
+}
+
+fn main() {
+    test_keying_calls_on_rounded_values();
+    test_keying_calls_on_bit_patterns();
+    test_resetting_calls();
+}
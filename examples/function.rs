@@ -6,6 +6,14 @@ fn generate_sequence(func: &dyn Fn(i32) -> i32, min: i32, max: i32) -> Vec<i32>
     (min..max).map(func).collect()
 }
 
+fn sum_sequence<F: Fn(i32) -> i32>(func: F, min: i32, max: i32) -> i32 {
+    (min..max).map(func).sum()
+}
+
+fn defer_transform<F: Fn(i32) -> i32 + 'static>(func: F) -> Box<dyn Fn(i32) -> i32> {
+    Box::new(func)
+}
+
 fn test_function_used_correctly() {
     // GIVEN:
     mock_func!(
@@ -45,7 +53,32 @@ fn test_function_with_custom_defaults() {
     )));
 }
 
+fn test_function_passed_to_multiple_consumers() {
+    // GIVEN:
+    mock_func!(
+        mock,
+        mock_fn,
+        i32,   // return value type
+        i32);  // argument1 type
+    mock.use_closure(Box::new(|x| x + 1));
+
+    // WHEN:
+    let sequence = generate_sequence(&mock_fn, 1, 3);
+    let sum = sum_sequence(mock_fn.clone(), 1, 3);
+    let deferred = defer_transform(mock_fn);
+    let deferred_result = deferred(10);
+
+    // THEN:
+    assert_eq!(vec!(2, 3), sequence);
+    assert_eq!(5, sum);
+    assert_eq!(11, deferred_result);
+    assert!(mock.has_calls_exactly_in_order(vec!(
+      1, 2, 1, 2, 10
+    )));
+}
+
 fn main() {
     test_function_used_correctly();
     test_function_with_custom_defaults();
+    test_function_passed_to_multiple_consumers();
 }
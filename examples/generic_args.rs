@@ -0,0 +1,78 @@
+#[macro_use]
+extern crate double;
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+trait FileSystem {
+    fn copy<P: AsRef<Path>, Q: AsRef<Path>>(&self, from: P, to: Q) -> Result<(), String>;
+    fn write<B: AsRef<[u8]>>(&self, path: &Path, contents: B) -> usize;
+    fn set_var<K: AsRef<OsStr>, V: Into<String>>(&mut self, key: K, value: V);
+}
+
+// Generic arguments are annotated with the type the mock stores them as, so
+// no custom bodies are needed to convert them. Other arguments, such as
+// `path`, are converted as usual.
+mock_trait_no_default!(
+    MockFileSystem,
+    copy(PathBuf, PathBuf) -> Result<(), String> = Ok(()),
+    write(PathBuf, Vec<u8>) -> usize = 0,
+    set_var(OsString, String) -> () = ());
+impl FileSystem for MockFileSystem {
+    mock_method!(copy<(P: AsRef<Path>, Q: AsRef<Path>)>(
+        &self, from: P as PathBuf, to: Q as PathBuf) -> Result<(), String>);
+    mock_method!(write<(B: AsRef<[u8]>)>(&self, path: &Path, contents: B as Vec<u8>) -> usize);
+    mock_method!(set_var<(K: AsRef<OsStr>, V: Into<String>)>(
+        &mut self, key: K as OsString, value: V as String));
+}
+
+// Code under test
+fn copy_to_all<FS: FileSystem, P: AsRef<Path>>(fs: &FS, from: P, to: &[P]) -> Vec<Result<(), String>> {
+    to.iter()
+        .map(|path| fs.copy(&from, path))
+        .collect()
+}
+
+fn install<FS: FileSystem>(fs: &mut FS, prefix: &str) -> usize {
+    fs.set_var("PREFIX", prefix);
+    fs.write(&Path::new(prefix).join("VERSION"), "1.0.0") +
+        fs.write(&Path::new(prefix).join("LICENSE"), vec!(0u8; 4))
+}
+
+fn test_copying_to_all_paths() {
+    // GIVEN:
+    let fs = MockFileSystem::default();
+    fs.copy_mock().return_value_for(
+        (PathBuf::from("/tmp/a"), PathBuf::from("/readonly/a")),
+        Err("permission denied".to_owned()));
+
+    // WHEN:
+    let results = copy_to_all(&fs, "/tmp/a", &["/home/a", "/readonly/a"]);
+
+    // THEN:
+    assert_eq!(results, vec!(Ok(()), Err("permission denied".to_owned())));
+    assert!(fs.copy_mock().has_calls_exactly_in_order(vec!(
+        (PathBuf::from("/tmp/a"), PathBuf::from("/home/a")),
+        (PathBuf::from("/tmp/a"), PathBuf::from("/readonly/a")))));
+}
+
+fn test_storing_each_generic_argument() {
+    // GIVEN:
+    let mut fs = MockFileSystem::default();
+    fs.write_mock().use_closure(Box::new(|(_, contents)| contents.len()));
+
+    // WHEN:
+    let written = install(&mut fs, "/opt/app");
+
+    // THEN:
+    assert_eq!(9, written);
+    assert!(fs.set_var_mock().called_with((OsString::from("PREFIX"), "/opt/app".to_owned())));
+    assert!(fs.write_mock().has_calls_exactly_in_order(vec!(
+        (PathBuf::from("/opt/app/VERSION"), b"1.0.0".to_vec()),
+        (PathBuf::from("/opt/app/LICENSE"), vec!(0u8; 4)))));
+}
+
+fn main() {
+    test_copying_to_all_paths();
+    test_storing_each_generic_argument();
+}
@@ -0,0 +1,118 @@
+#[macro_use]
+extern crate double;
+
+use std::any::Any;
+use std::rc::Rc;
+use std::str::FromStr;
+
+// The return type of both methods is chosen by the caller.
+trait Config {
+    fn get<T: FromStr>(&self, key: &str) -> Result<T, T::Err>;
+}
+
+trait Decoder {
+    fn decode<T: Any + Clone>(&self, bytes: &[u8]) -> Option<T>;
+}
+
+// Code under test
+fn worker_settings<C: Config>(config: &C) -> (u32, bool) {
+    let threads = config.get("threads").unwrap_or(1);
+    let verbose = config.get("verbose").unwrap_or(false);
+    (threads, verbose)
+}
+
+fn decode_header<D: Decoder>(decoder: &D, bytes: &[u8]) -> (Option<u32>, Option<String>) {
+    (decoder.decode(&bytes[..4]), decoder.decode(&bytes[4..]))
+}
+
+// Pattern 1: store the value in a form every `T` can be built from.
+//
+// The underlying mock returns the raw text of the config value, which the
+// custom body parses into whichever `T` the caller asked for.
+mock_trait!(
+    MockConfig,
+    get(String) -> String);
+impl Config for MockConfig {
+    mock_method!(get<(T: FromStr)>(&self, key: &str) -> Result<T, T::Err>, self, {
+        self.get_mock().call(key.to_owned()).parse()
+    });
+}
+
+// Pattern 2: store type-erased values and downcast them to `T`.
+//
+// A closure configured using `use_closure` constructs the value to return for
+// each call. The custom body downcasts it to the `T` the caller asked for,
+// returning `None` if the closure constructed a value of a different type.
+mock_trait!(
+    MockDecoder,
+    decode(Vec<u8>) -> Rc<dyn Any> = Rc::new(()));
+impl Decoder for MockDecoder {
+    mock_method!(decode<(T: Any + Clone)>(&self, bytes: &[u8]) -> Option<T>, self, {
+        self.decode_mock().call(bytes.to_vec()).downcast_ref::<T>().cloned()
+    });
+}
+
+fn test_generic_return_parsed_from_stored_text() {
+    // GIVEN:
+    let config = MockConfig::default();
+    config.get_mock().return_value_for("threads".to_owned(), "8");
+    config.get_mock().return_value_for("verbose".to_owned(), "true");
+
+    // WHEN:
+    let settings = worker_settings(&config);
+
+    // THEN:
+    assert_eq!((8, true), settings);
+    assert!(config.get_mock().has_calls_exactly_in_order(vec!(
+        "threads".to_owned(),
+        "verbose".to_owned())));
+}
+
+fn test_generic_return_falls_back_when_text_is_invalid() {
+    // GIVEN:
+    let config = MockConfig::default();
+    config.get_mock().return_value_for("threads".to_owned(), "many");
+
+    // WHEN:
+    let settings = worker_settings(&config);
+
+    // THEN:
+    assert_eq!((1, false), settings);
+}
+
+fn test_generic_return_downcast_from_closure() {
+    // GIVEN:
+    let decoder = MockDecoder::default();
+    decoder.decode_mock().use_closure(Box::new(|bytes| {
+        if bytes.len() == 4 {
+            Rc::new(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        } else {
+            Rc::new(String::from_utf8(bytes).unwrap())
+        }
+    }));
+
+    // WHEN:
+    let header = decode_header(&decoder, &[0, 0, 1, 0, b'o', b'k']);
+
+    // THEN:
+    assert_eq!((Some(256), Some("ok".to_owned())), header);
+    assert!(decoder.decode_mock().called_with(vec!(b'o', b'k')));
+}
+
+fn test_generic_return_is_none_for_other_types() {
+    // GIVEN:
+    let decoder = MockDecoder::default();
+
+    // WHEN:
+    let header = decode_header(&decoder, &[0, 0, 1, 0, b'o', b'k']);
+
+    // THEN:
+    assert_eq!((None, None), header);
+}
+
+fn main() {
+    test_generic_return_parsed_from_stored_text();
+    test_generic_return_falls_back_when_text_is_invalid();
+    test_generic_return_downcast_from_closure();
+    test_generic_return_is_none_for_other_types();
+}
@@ -0,0 +1,74 @@
+#[macro_use]
+extern crate double;
+
+use double::Mock;
+
+// Represents a source of IDs which may be too large to hold in memory.
+trait Source {
+    fn ids(&self) -> Box<dyn Iterator<Item = u64>>;
+}
+
+// The `mock_trait!` macros require return types to implement `Clone`, so the
+// mock `struct` is written by hand. The method body is still generated, once
+// the method's argument types are declared with `mock_method_args!`, and
+// passes the method's arguments to the `m_ids` field.
+struct MockSource {
+    pub m_ids: Mock<(), Box<dyn Iterator<Item = u64>>>,
+}
+
+impl MockSource {
+    fn new() -> Self {
+        MockSource {
+            m_ids: Mock::with_producer(Box::new(|| Box::new(std::iter::empty()))),
+        }
+    }
+}
+
+mock_method_args!(
+    MockSource,
+    ids(()));
+
+impl Source for MockSource {
+    mock_method!(ids(&self) -> Box<dyn Iterator<Item = u64>>);
+}
+
+// Code under test
+fn total_and_count<S: Source>(source: &S) -> (u64, usize) {
+    let total = source.ids().sum();
+    let count = source.ids().count();
+    (total, count)
+}
+
+fn test_source_is_drained_twice() {
+    // GIVEN:
+    // `Box<dyn Iterator>` doesn't implement `Clone`, so the mock constructs a
+    // fresh iterator over the items every time `ids()` is called.
+    let source = MockSource::new();
+    source.m_ids.return_iter(vec!(3, 5, 8));
+
+    // WHEN:
+    let (total, count) = total_and_count(&source);
+
+    // THEN:
+    assert_eq!(16, total);
+    assert_eq!(3, count);
+    assert_eq!(2, source.m_ids.num_calls());
+}
+
+fn test_empty_source() {
+    // GIVEN:
+    let source = MockSource::new();
+    source.m_ids.return_iter(Vec::new());
+
+    // WHEN:
+    let (total, count) = total_and_count(&source);
+
+    // THEN:
+    assert_eq!((0, 0), (total, count));
+    assert_eq!(2, source.m_ids.num_calls());
+}
+
+fn main() {
+    test_source_is_drained_twice();
+    test_empty_source();
+}
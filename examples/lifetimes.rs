@@ -0,0 +1,75 @@
+#[macro_use]
+extern crate double;
+
+trait Store<'a> {
+    fn iter(&'a self) -> Box<dyn Iterator<Item = u32> + 'a>;
+}
+
+trait Index<'a> {
+    fn contains(&self, key: &'a str) -> bool;
+}
+
+// Code under test
+fn total<'a, S: Store<'a>>(store: &'a S) -> u32 {
+    store.iter().sum()
+}
+
+fn count_present<'a, I: Index<'a>>(index: &I, keys: &[&'a str]) -> usize {
+    keys.iter().filter(|key| index.contains(key)).count()
+}
+
+// Iterators can't be stored in a `Mock`, so `iter` stores the items to
+// iterate over instead. The custom body turns them into a new boxed iterator
+// on each call.
+mock_trait!(
+    MockStore['a],
+    iter(()) -> Vec<u32>);
+impl<'a> Store<'a> for MockStore<'a> {
+    mock_method!(iter(&self) -> Box<dyn Iterator<Item = u32> + 'a>, self, {
+        Box::new(self.iter_mock().call(()).into_iter())
+    });
+}
+
+// The mock is parameterised by the trait's lifetime, so it can store the
+// borrowed keys it receives.
+mock_trait!(
+    MockIndex['a],
+    contains(&'a str) -> bool);
+impl<'a> Index<'a> for MockIndex<'a> {
+    mock_method!(contains(&self, key: &'a str) -> bool);
+}
+
+fn test_boxed_iterator() {
+    // GIVEN:
+    let store = MockStore::default();
+    store.iter_mock().return_value(vec!(1, 2, 3));
+
+    // WHEN:
+    let first = total(&store);
+    let second = total(&store);
+
+    // THEN:
+    assert_eq!(6, first);
+    assert_eq!(6, second);
+    assert_eq!(2, store.iter_mock().num_calls());
+}
+
+fn test_borrowed_args() {
+    // GIVEN:
+    let keys = ["apple".to_owned(), "banana".to_owned()];
+    let index = MockIndex::default();
+    index.contains_mock().return_value_for("banana", true);
+
+    // WHEN:
+    let key_refs: Vec<&str> = keys.iter().map(|key| key.as_str()).collect();
+    let num_present = count_present(&index, &key_refs);
+
+    // THEN:
+    assert_eq!(1, num_present);
+    assert!(index.contains_mock().has_calls_exactly_in_order(vec!("apple", "banana")));
+}
+
+fn main() {
+    test_boxed_iterator();
+    test_borrowed_args();
+}
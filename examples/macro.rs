@@ -15,6 +15,11 @@ trait Greeter: Clone {
     fn greet<S: AsRef<str>>(&mut self, name: S);
 }
 
+trait TaskManager: Clone {
+    fn max_threads(&self) -> u32;
+    fn set_max_threads(&mut self, max_threads: u32);
+}
+
 mock_trait!(EmptyMock);
 
 mock_trait!(
@@ -38,10 +43,19 @@ mock_trait!(
     greet(String) -> ());
 impl Greeter for MockGreeter {
     mock_method!(greet<(S: AsRef<str>)>(&mut self, name: S), self, {
-        self.greet.call(name.as_ref().to_string());
+        self.greet_mock().call(name.as_ref().to_string());
     });
 }
 
+mock_trait!(
+    MockTaskManager,
+    max_threads(()) -> u32 = 8,
+    set_max_threads(u32) -> ());
+impl TaskManager for MockTaskManager {
+    mock_method!(max_threads(&self) -> u32);
+    mock_method!(set_max_threads(&mut self, max_threads: u32));
+}
+
 // Traits which return types that do not implement `Default`.
 #[derive(Debug, Clone, PartialEq)]
 pub struct User {
@@ -63,26 +77,75 @@ impl UserStore for MockUserStore {
     mock_method!(delete_user(&self, id: i32) -> Result<(), String>);
 }
 
+mock_trait_no_default!(
+    MockUserStoreWithDefaults,
+    get_user(i32) -> Result<User, String> = Err("no user".to_owned()),
+    delete_user(i32) -> Result<(), String> = Ok(()));
+
+impl UserStore for MockUserStoreWithDefaults {
+    mock_method!(get_user(&self, id: i32) -> Result<User, String>);
+    mock_method!(delete_user(&self, id: i32) -> Result<(), String>);
+}
+
+// Only `get_user`'s return type doesn't implement `Default`.
+mock_trait_no_default!(
+    MockUserDirectory,
+    get_user(i32) -> Result<User, String>,
+    num_users(()) -> usize,
+    usernames(()) -> Vec<String>);
+
+// Only some methods have inline return values.
+mock_trait_no_default!(
+    MockUserStoreWithSomeDefaults,
+    get_user(i32) -> Result<User, String>,
+    delete_user(i32) -> Result<(), String> = Err("read only".to_owned()));
+
+impl UserStore for MockUserStoreWithSomeDefaults {
+    mock_method!(get_user(&self, id: i32) -> Result<User, String>);
+    mock_method!(delete_user(&self, id: i32) -> Result<(), String>);
+}
+
+// Traits whose argument types do not implement `Debug`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Credentials {
+    password: String
+}
+
+pub trait Authenticator {
+    fn login(&self, credentials: Credentials) -> bool;
+    fn logout(&self, user: String);
+}
+
+mock_trait!(
+    MockAuthenticator,
+    login(Credentials) -> bool,
+    logout(String) -> ());
+
+impl Authenticator for MockAuthenticator {
+    mock_method!(login(&self, credentials: Credentials) -> bool);
+    mock_method!(logout(&self, user: String));
+}
+
 fn main() {
     // Test individual return values
     let mock = MockBalanceSheet::default();
-    mock.profit.return_value(42);
-    mock.profit.return_value_for((0, 0), 9001);
+    mock.profit_mock().return_value(42);
+    mock.profit_mock().return_value_for((0, 0), 9001);
 
     let value = mock.profit(10, 20);
     assert_eq!(42, value);
-    mock.profit.has_calls_exactly_in_order(vec!((10, 20)));
+    mock.profit_mock().has_calls_exactly_in_order(vec!((10, 20)));
 
     let value = mock.profit(0, 0);
     assert_eq!(9001, value);
-    mock.profit.has_calls_exactly_in_order(vec!((10, 20), (0, 0)));
+    mock.profit_mock().has_calls_exactly_in_order(vec!((10, 20), (0, 0)));
 
     // Test sequence of return values
-    mock.profit.return_values(vec!(1, 2, 3));
-    assert_eq!(1, mock.profit.call((1, 2)));
-    assert_eq!(2, mock.profit.call((2, 4)));
-    assert_eq!(3, mock.profit.call((3, 6)));
-    assert_eq!(42, mock.profit.call((4, 8)));
+    mock.profit_mock().return_values(vec!(1, 2, 3));
+    assert_eq!(1, mock.profit_mock().call((1, 2)));
+    assert_eq!(2, mock.profit_mock().call((2, 4)));
+    assert_eq!(3, mock.profit_mock().call((3, 6)));
+    assert_eq!(42, mock.profit_mock().call((4, 8)));
 
     // Test using mocks that do not implement the `Default` trait.
     // One must manually specify the default values for all methods in the
@@ -91,7 +154,7 @@ fn main() {
         Err("cannot get, no user with given ID".to_owned()),
         Err("cannot delete, no user with given ID".to_owned()));
 
-    store.get_user.return_value_for(
+    store.get_user_mock().return_value_for(
         42,
         Ok(User{ name: "Donald".to_owned() }));
     assert_eq!(
@@ -101,9 +164,169 @@ fn main() {
         Ok(User{ name: "Donald".to_owned() }),
         store.get_user(42));
 
-    store.delete_user.return_value_for(42, Ok(()));
+    store.delete_user_mock().return_value_for(42, Ok(()));
     assert_eq!(
         Err("cannot delete, no user with given ID".to_owned()),
         store.delete_user(10));
     assert_eq!(Ok(()), store.delete_user(42));
+
+    // Test default return values specified inline.
+    let mut task_manager = MockTaskManager::default();
+    assert_eq!(8, task_manager.max_threads());
+    task_manager.set_max_threads(16);
+    assert!(task_manager.set_max_threads_mock().called_with(16u32));
+
+    let store = MockUserStoreWithDefaults::default();
+    assert_eq!(Err("no user".to_owned()), store.get_user(42));
+    assert_eq!(Ok(()), store.delete_user(42));
+
+    // `new()` is still generated, and overrides the inline values.
+    let store = MockUserStoreWithDefaults::new(
+        Ok(User{ name: "Donald".to_owned() }),
+        Err("cannot delete".to_owned()));
+    assert_eq!(Ok(User{ name: "Donald".to_owned() }), store.get_user(42));
+    assert_eq!(Err("cannot delete".to_owned()), store.delete_user(42));
+
+    // Test constructing mocks using a builder, which names each default
+    // return value explicitly.
+    let store = MockUserStore::builder()
+        .delete_user(Err("cannot delete".to_owned()))
+        .get_user(Err("cannot get".to_owned()))
+        .build();
+    assert_eq!(Err("cannot get".to_owned()), store.get_user(42));
+    assert_eq!(Err("cannot delete".to_owned()), store.delete_user(42));
+
+    // Builders fall back to inline return values.
+    let store = MockUserStoreWithDefaults::builder()
+        .delete_user(Err("cannot delete".to_owned()))
+        .build();
+    assert_eq!(Err("no user".to_owned()), store.get_user(42));
+    assert_eq!(Err("cannot delete".to_owned()), store.delete_user(42));
+
+    // Builders fall back to `Default` values, so only methods whose return
+    // types don't implement `Default` have to be set.
+    let directory = MockUserDirectory::builder()
+        .get_user(Ok(User { name: "Donald".to_owned() }))
+        .build();
+    assert_eq!(Ok(User { name: "Donald".to_owned() }), directory.get_user_mock().call(1));
+    assert_eq!(0, directory.num_users_mock().call(()));
+    assert!(directory.usernames_mock().call(()).is_empty());
+    let missing_default = std::panic::catch_unwind(|| MockUserDirectory::builder().build());
+    assert!(missing_default.is_err());
+
+    // Building fails if a default return value is missing.
+    let missing_default = std::panic::catch_unwind(|| {
+        MockUserStore::builder()
+            .get_user(Err("cannot get".to_owned()))
+            .build()
+    });
+    assert!(missing_default.is_err());
+
+    // Methods with inline return values don't have to be set on the builder,
+    // even if other methods have no inline return value.
+    let store = MockUserStoreWithSomeDefaults::builder()
+        .get_user(Err("cannot get".to_owned()))
+        .build();
+    assert_eq!(Err("cannot get".to_owned()), store.get_user(42));
+    assert_eq!(Err("read only".to_owned()), store.delete_user(42));
+    let store = MockUserStoreWithSomeDefaults::new(
+        Err("cannot get".to_owned()),
+        Ok(()));
+    assert_eq!(Ok(()), store.delete_user(42));
+
+    // Deep clones of a configured "template" mock record their calls
+    // independently of the template and of each other.
+    let template = MockBalanceSheet::default();
+    template.profit_mock().return_value_for((100, 50), 50);
+    let first = template.deep_clone();
+    let second = template.deep_clone();
+    assert_eq!(50, first.profit(100, 50));
+    assert_eq!(50, second.profit(100, 50));
+    second.profit(1, 1);
+    assert!(!template.profit_mock().called());
+    assert_eq!(1, first.profit_mock().num_calls());
+    assert_eq!(2, second.profit_mock().num_calls());
+
+    // Regular clones share the template's recorded calls.
+    let alias = template.clone();
+    alias.profit(1, 1);
+    assert!(template.profit_mock().called_with((1, 1)));
+
+    // Mocks can be formatted even if some of their types don't implement
+    // `Debug`. Those methods are summarised by their number of calls.
+    let authenticator = MockAuthenticator::default();
+    authenticator.login(Credentials { password: "hunter2".to_owned() });
+    authenticator.logout("Donald".to_owned());
+    let formatted = format!("{:?}", authenticator);
+    assert!(formatted.starts_with("MockAuthenticator { login: Mock { num_calls: 1 }, logout: Mock {"));
+    assert!(formatted.contains("\"Donald\""));
+
+    // Calls can be counted and reset across all of a mock's methods.
+    let sheet = MockBalanceSheet::default();
+    assert!(sheet.verify_no_interactions());
+    sheet.profit(10, 5);
+    sheet.profit(20, 5);
+    assert_eq!(2, sheet.total_calls());
+    assert!(!sheet.loss_mock().called());
+    assert!(!sheet.verify_no_interactions());
+    assert_eq!(
+        sheet.check_no_interactions().unwrap_err().to_string(),
+        "Mock::no_interactions failed\n  \
+         expected: no calls to MockBalanceSheet\n  \
+         actual calls: [profit ×2]");
+    sheet.reset_all_calls();
+    assert_eq!(0, sheet.total_calls());
+    assert!(sheet.verify_no_interactions());
+
+    // Checkpointing a mock verifies the calls expected in the current test
+    // phase, then starts a new phase, keeping the behaviour configured for
+    // specific arguments.
+    sheet.profit_mock().return_value_for((10, 5), 5);
+    sheet.loss_mock().return_value_once(-3);
+    sheet.profit_mock().expect_call((10, 5));
+    sheet.profit(10, 5);
+    sheet.checkpoint();
+    assert_eq!(0, sheet.total_calls());
+    assert_eq!(0, sheet.loss(10, 5));
+    assert_eq!(5, sheet.profit(10, 5));
+    assert!(sheet.profit_mock().has_calls_exactly(vec!((10, 5))));
+
+    // The checkpoint fails if an expected call wasn't made.
+    sheet.loss_mock().expect_call((1, 2));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| sheet.checkpoint()));
+    let message = result.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.starts_with("loss: Mock::expect_call failed"));
+    assert_eq!(2, sheet.total_calls());
+
+    let store = MockUserStore::new(Ok(User { name: "Donald".to_owned() }), Ok(()));
+    store.delete_user(42).unwrap();
+    assert_eq!(1, store.total_calls());
+    store.reset_all_calls();
+    assert!(store.verify_no_interactions());
+
+    // The calls made to all of a mock's methods can be listed in order.
+    let sheet = MockBalanceSheet::default();
+    sheet.profit(10, 5);
+    sheet.loss(3, 1);
+    sheet.profit(20, 5);
+    sheet.loss(4, 2);
+    assert_eq!(
+        sheet.interactions(),
+        vec!(
+            (MockBalanceSheetMethod::Profit, "(10, 5)".to_owned()),
+            (MockBalanceSheetMethod::Loss, "(3, 1)".to_owned()),
+            (MockBalanceSheetMethod::Profit, "(20, 5)".to_owned()),
+            (MockBalanceSheetMethod::Loss, "(4, 2)".to_owned())));
+    assert_eq!(
+        sheet.interaction_summary(),
+        "4 interactions\n  0: profit: (10, 5)\n  1: loss: (3, 1)\n  2: profit: (20, 5)\n  3: loss: (4, 2)");
+
+    // Arguments that don't implement `Debug` are listed as `..`.
+    authenticator.logout("Donald".to_owned());
+    assert_eq!(
+        authenticator.interactions(),
+        vec!(
+            (MockAuthenticatorMethod::Login, "..".to_owned()),
+            (MockAuthenticatorMethod::Logout, "\"Donald\"".to_owned()),
+            (MockAuthenticatorMethod::Logout, "\"Donald\"".to_owned())));
 }
@@ -46,7 +46,7 @@ mock_trait!(
     greet(String) -> ());
 impl Greeter for MockGreeter {
     mock_method!(greet<(S: AsRef<str>)>(&mut self, name: S), self, {
-        self.greet.call(name.as_ref().to_string());
+        self.greet_mock().call(name.as_ref().to_string());
     });
 }
 
@@ -0,0 +1,98 @@
+#[macro_use]
+extern crate double;
+
+// A trait whose method names match methods `double` generates for mocks, or
+// methods from traits the generated mocks implement.
+#[allow(unknown_lints, clippy::new_ret_no_self, clippy::wrong_self_convention)]
+trait Factory {
+    fn new(&self, name: String) -> u32;
+    fn default(&self) -> u32;
+    fn clone(&self) -> bool;
+    fn call(&self, input: i32) -> i32;
+}
+
+mock_trait!(
+    MockFactory,
+    new(String) -> u32,
+    default(()) -> u32,
+    clone(()) -> bool,
+    call(i32) -> i32);
+
+impl Factory for MockFactory {
+    mock_method!(new(&self, name: String) -> u32);
+    mock_method!(default(&self) -> u32);
+    mock_method!(clone(&self) -> bool);
+    mock_method!(call(&self, input: i32) -> i32);
+}
+
+// The same methods, mocked by `#[double::mock]`.
+#[allow(unknown_lints, clippy::new_ret_no_self, clippy::wrong_self_convention)]
+#[double::mock]
+trait Registry {
+    fn new(&self, name: &str) -> u32;
+    fn default(&self) -> u32;
+}
+
+fn test_accessors_of_colliding_methods() {
+    // GIVEN:
+    // `MockFactory::default()` is ambiguous here, since both `Default` and
+    // `Factory` provide `default`, so the trait has to be named explicitly.
+    let mock = <MockFactory as Default>::default();
+    mock.new_mock().return_value(1u32);
+    mock.default_mock().return_value(2u32);
+    mock.clone_mock().return_value(true);
+    mock.call_mock().use_closure(Box::new(|input| input * 2));
+
+    // WHEN:
+    let new_result = mock.new("widget".to_owned());
+    let default_result = Factory::default(&mock);
+    let clone_result = Factory::clone(&mock);
+    let call_result = mock.call(21);
+
+    // THEN:
+    assert_eq!(1, new_result);
+    assert_eq!(2, default_result);
+    assert!(clone_result);
+    assert_eq!(42, call_result);
+    assert!(mock.new_mock().called_with("widget".to_owned()));
+    assert!(mock.default_mock().called());
+    assert!(mock.clone_mock().called());
+    assert!(mock.call_mock().called_with(21));
+}
+
+fn test_generated_constructor_with_colliding_methods() {
+    // GIVEN:
+    let mock = MockFactory::new(1, 2, true, 3);
+
+    // WHEN:
+    let copy = Clone::clone(&mock);
+    let default_result = Factory::default(&copy);
+
+    // THEN:
+    assert_eq!(2, default_result);
+    assert_eq!(1, mock.default_mock().num_calls());
+    // the fields behind the accessors don't collide with `new()` either
+    assert_eq!(1, mock.m_default.num_calls());
+    assert!(!mock.m_new.called());
+}
+
+fn test_attribute_mock_with_colliding_methods() {
+    // GIVEN:
+    let mock = MockRegistry::new(7, 8);
+
+    // WHEN:
+    let new_result = Registry::new(&mock, "widget");
+    let default_result = Registry::default(&mock);
+
+    // THEN:
+    assert_eq!(7, new_result);
+    assert_eq!(8, default_result);
+    assert!(mock.new_mock().called_with("widget"));
+    assert_eq!(1, mock.default_mock().num_calls());
+}
+
+fn main() {
+    test_accessors_of_colliding_methods();
+    test_generated_constructor_with_colliding_methods();
+    test_attribute_mock_with_colliding_methods();
+}
@@ -0,0 +1,106 @@
+#[macro_use]
+extern crate double;
+
+trait BalanceSheet: Clone {
+    fn profit(&self, revenue: u32, costs: u32) -> i32;
+    fn loss(&self, revenue: u32, costs: u32) -> i32;
+}
+
+trait Ledger {
+    fn record(&mut self, account: &str, amounts: &[i64]) -> Result<(), String>;
+    fn balance(&self, account: &str) -> Option<i64>;
+}
+
+// The method signatures are only written once, so the mock's argument and
+// return types can't drift apart from the ones used by the `impl`.
+mock_impl_trait!(MockBalanceSheet as BalanceSheet {
+    fn profit(&self, revenue: u32, costs: u32) -> i32;
+    fn loss(&self, revenue: u32, costs: u32) -> i32;
+});
+
+// Borrowed arguments are stored as owned values.
+mock_impl_trait!(MockLedger as Ledger {
+    fn record(&mut self, account: &str, amounts: &[i64]) -> Result<(), String>;
+    fn balance(&self, account: &str) -> Option<i64>;
+});
+
+fn main() {
+    // Test individual return values
+    let mock = MockBalanceSheet::default();
+    mock.profit_mock().return_value(42);
+    mock.profit_mock().return_value_for((0, 0), 9001);
+
+    let value = mock.profit(10, 20);
+    assert_eq!(42, value);
+    mock.profit_mock().has_calls_exactly_in_order(vec!((10, 20)));
+
+    let value = mock.profit(0, 0);
+    assert_eq!(9001, value);
+    mock.profit_mock().has_calls_exactly_in_order(vec!((10, 20), (0, 0)));
+
+    // Test sequence of return values
+    mock.profit_mock().return_values(vec!(1, 2, 3));
+    assert_eq!(1, mock.profit_mock().call((1, 2)));
+    assert_eq!(2, mock.profit_mock().call((2, 4)));
+    assert_eq!(3, mock.profit_mock().call((3, 6)));
+    assert_eq!(42, mock.profit_mock().call((4, 8)));
+
+    // Deep clones of a configured "template" mock record their calls
+    // independently of the template and of each other.
+    let template = MockBalanceSheet::default();
+    template.profit_mock().return_value_for((100, 50), 50);
+    let first = template.deep_clone();
+    let second = template.deep_clone();
+    assert_eq!(50, first.profit(100, 50));
+    assert_eq!(50, second.profit(100, 50));
+    second.profit(1, 1);
+    assert!(!template.profit_mock().called());
+    assert_eq!(1, first.profit_mock().num_calls());
+    assert_eq!(2, second.profit_mock().num_calls());
+
+    // Regular clones share the template's recorded calls.
+    let alias = template.clone();
+    alias.profit(1, 1);
+    assert!(template.profit_mock().called_with((1, 1)));
+
+    // Calls can be counted and reset across all of a mock's methods.
+    let sheet = MockBalanceSheet::default();
+    assert!(sheet.verify_no_interactions());
+    sheet.profit(10, 5);
+    sheet.profit(20, 5);
+    assert_eq!(2, sheet.total_calls());
+    assert!(!sheet.loss_mock().called());
+    assert!(!sheet.verify_no_interactions());
+    sheet.reset_all_calls();
+    assert_eq!(0, sheet.total_calls());
+    assert!(sheet.verify_no_interactions());
+
+    // Checkpointing a mock starts a new test phase, keeping the behaviour
+    // configured for specific arguments.
+    sheet.profit_mock().return_value_for((10, 5), 5);
+    sheet.loss_mock().return_value_once(-3);
+    sheet.profit(10, 5);
+    sheet.checkpoint();
+    assert_eq!(0, sheet.total_calls());
+    assert_eq!(0, sheet.loss(10, 5));
+    assert_eq!(5, sheet.profit(10, 5));
+    assert!(sheet.profit_mock().has_calls_exactly(vec!((10, 5))));
+
+    // Test methods with borrowed arguments. `Result` doesn't implement
+    // `Default`, so `record()` needs a return value before it's called.
+    let mut ledger = MockLedger::default();
+    ledger.record_mock().return_value(Ok(()));
+    ledger.balance_mock().return_value_for("savings".to_owned(), Some(100));
+    assert_eq!(Ok(()), ledger.record("savings", &[50, -20]));
+    assert_eq!(Some(100), ledger.balance("savings"));
+    assert_eq!(None, ledger.balance("current"));
+    assert!(ledger.record_mock().called_with(("savings".to_owned(), vec!(50, -20))));
+
+    // The calls made to all of a mock's methods can be listed in order.
+    assert_eq!(
+        ledger.interactions(),
+        vec!(
+            (MockLedgerMethod::Record, "(\"savings\", [50, -20])".to_owned()),
+            (MockLedgerMethod::Balance, "\"savings\"".to_owned()),
+            (MockLedgerMethod::Balance, "\"current\"".to_owned())));
+}
@@ -0,0 +1,71 @@
+#[macro_use]
+extern crate double;
+
+trait Reader {
+    // Appends the next chunk of data to `buf` and returns its length.
+    fn read_into(&self, buf: &mut Vec<u8>) -> usize;
+    // Pads `buf` with `byte` and returns its new length.
+    fn fill(&mut self, byte: u8, buf: &mut Vec<u8>) -> usize;
+}
+
+mock_trait!(
+    MockReader,
+    // Returns the chunk to append, rather than its length, so each test can
+    // configure the data that's read.
+    read_into(Vec<u8>) -> Vec<u8>,
+    fill(u8, Vec<u8>) -> usize);
+impl Reader for MockReader {
+    mock_method!(read_into(&self, buf: &mut Vec<u8>) -> usize, self, {
+        self.read_into_mock().call_with_effect(
+            buf.clone(),
+            buf,
+            &|buf: &mut Vec<u8>, chunk: &Vec<u8>| buf.extend_from_slice(chunk)
+        ).len()
+    });
+    mock_method!(fill(&mut self, byte: u8, buf: &mut Vec<u8>) -> usize,
+        effect(buf, |buf: &mut Vec<u8>, len: &usize| buf.resize(*len, 0xFF)));
+}
+
+// Code under test
+fn read_all<R: Reader>(reader: &R) -> Vec<u8> {
+    let mut buf = vec!();
+    while reader.read_into(&mut buf) > 0 {}
+    buf
+}
+
+fn test_reading_configured_bytes() {
+    // GIVEN:
+    let reader = MockReader::default();
+    reader.read_into_mock().return_values(vec!(vec!(1, 2, 3), vec!(4)));
+
+    // WHEN:
+    let data = read_all(&reader);
+
+    // THEN:
+    assert_eq!(vec!(1, 2, 3, 4), data);
+    assert!(reader.read_into_mock().has_calls_exactly_in_order(vec!(
+        vec!(),
+        vec!(1, 2, 3),
+        vec!(1, 2, 3, 4)
+    )));
+}
+
+fn test_generated_effect_body() {
+    // GIVEN:
+    let mut reader = MockReader::default();
+    reader.fill_mock().return_value(2usize);
+
+    // WHEN:
+    let mut buf = vec!(7);
+    let len = reader.fill(0xFF, &mut buf);
+
+    // THEN:
+    assert_eq!(2, len);
+    assert_eq!(vec!(7, 0xFF), buf);
+    assert!(reader.fill_mock().called_with((0xFF, vec!(7))));
+}
+
+fn main() {
+    test_reading_configured_bytes();
+    test_generated_effect_body();
+}
@@ -0,0 +1,67 @@
+#[macro_use]
+extern crate double;
+
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+trait FileSystem {
+    fn copy(&self, from: &Path, to: &Path) -> bool;
+    fn set_env(&mut self, key: &OsStr, value: Cow<str>);
+}
+
+// `&Path`, `&OsStr` and `Cow` arguments are stored as their owned types, so
+// no custom bodies are needed to convert them.
+mock_trait!(
+    MockFileSystem,
+    copy(PathBuf, PathBuf) -> bool,
+    set_env(OsString, String) -> ());
+impl FileSystem for MockFileSystem {
+    mock_method!(copy(&self, from: &Path, to: &Path) -> bool);
+    mock_method!(set_env(&mut self, key: &OsStr, value: Cow<str>));
+}
+
+// The same conversions are made by `#[double::mock]`.
+#[double::mock]
+trait Archiver {
+    fn archive(&self, dir: &Path, name: Cow<str>) -> usize;
+}
+
+// Code under test
+fn backup<FS: FileSystem>(fs: &mut FS, files: &[&str], dest: &Path) -> usize {
+    fs.set_env(OsStr::new("BACKUP_DIR"), dest.to_string_lossy());
+    files
+        .iter()
+        .filter(|file| fs.copy(Path::new(file), &dest.join(file)))
+        .count()
+}
+
+fn test_backing_up_files() {
+    // GIVEN:
+    let mut fs = MockFileSystem::default();
+    fs.copy_mock().return_value(true);
+    fs.copy_mock().return_value_for(
+        (PathBuf::from("locked.txt"), PathBuf::from("/backup/locked.txt")),
+        false);
+
+    // WHEN:
+    let num_copied = backup(&mut fs, &["a.txt", "locked.txt"], Path::new("/backup"));
+
+    // THEN:
+    assert_eq!(1, num_copied);
+    assert!(fs.copy_mock().called_with((PathBuf::from("a.txt"), PathBuf::from("/backup/a.txt"))));
+    assert!(fs.set_env_mock().called_with((OsString::from("BACKUP_DIR"), "/backup".to_owned())));
+}
+
+fn test_generated_mock() {
+    let archiver = MockArchiver::default();
+    archiver.archive_mock().return_value(3usize);
+
+    assert_eq!(3, archiver.archive(Path::new("/home"), Cow::Borrowed("home")));
+    assert!(archiver.archive_mock().called_with((PathBuf::from("/home"), "home".to_owned())));
+}
+
+fn main() {
+    test_backing_up_files();
+    test_generated_mock();
+}
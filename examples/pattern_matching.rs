@@ -27,61 +27,68 @@ fn main() {
     forecaster.write_report_for(84, true);
     forecaster.write_report_for(42, false);
 
-    assert!(forecaster.profit_at.called_with_pattern(
+    assert!(forecaster.profit_at_mock().called_with_pattern(
         matcher!( p!(eq, 42) )
     ));
-    assert!(!forecaster.profit_at.called_with_pattern(
+    assert!(!forecaster.profit_at_mock().called_with_pattern(
         matcher!( p!(gt, 84) )
     ));
-    assert!(forecaster.profit_at.called_with_pattern(
+    assert!(forecaster.profit_at_mock().called_with_pattern(
         matcher!( p!(between_inc, 42, 84) )
     ));
-    assert!(!forecaster.profit_at.called_with_pattern(
+    assert!(!forecaster.profit_at_mock().called_with_pattern(
         matcher!( p!(between_exc, 42, 84) )
     ));
 
-    assert!(forecaster.profit_at.called_with_pattern(
+    assert!(forecaster.profit_at_mock().called_with_pattern(
         matcher!( p!(not, p!(gt, 84)) )
     ));
-    assert!(!forecaster.profit_at.called_with_pattern(
+    assert!(!forecaster.profit_at_mock().called_with_pattern(
         matcher!( p!(not, p!(gt, 0)) )
     ));
 
-    assert!(forecaster.profit_at.called_with_pattern(
+    assert!(forecaster.profit_at_mock().called_with_pattern(
         matcher!( p!(all_of, vec!(p!(gt, 40), p!(lt, 90))) )
     ));
-    assert!(!forecaster.profit_at.called_with_pattern(
+    assert!(!forecaster.profit_at_mock().called_with_pattern(
         matcher!( p!(all_of, vec!(p!(gt, 40), p!(lt, 42))) )
     ));
 
-    assert!(forecaster.profit_at.called_with_pattern(
+    assert!(forecaster.profit_at_mock().called_with_pattern(
         matcher!( p!(any_of, vec!(p!(lt, 100), p!(gt, 200))) )
     ));
-    assert!(!forecaster.profit_at.called_with_pattern(
+    assert!(!forecaster.profit_at_mock().called_with_pattern(
         matcher!( p!(any_of, vec!(p!(lt, 5), p!(gt, 200))) )
     ));
 
-    assert!(forecaster.write_report_for.called_with_pattern(
+    assert!(forecaster.write_report_for_mock().called_with_pattern(
         matcher!( p!(eq, 42), p!(eq, false) )
     ));
 
-    assert!(forecaster.write_report_for.has_patterns(vec!(
+    assert!(forecaster.write_report_for_mock().called_with_pattern(
+        matcher!( _, false )
+    ));
+    assert!(!forecaster.write_report_for_mock().called_with_pattern(
+        matcher!( 21, _ )
+    ));
+
+    assert!(forecaster.write_report_for_mock().has_patterns(vec!(
         matcher!( p!(eq, 42), p!(eq, true) ),
         matcher!( p!(eq, 42), p!(eq, false) )
     )));
 
     forecaster.store_forecast_result(Ok(51));
     forecaster.store_forecast_result(Err("sad_face :(".to_owned()));
-    assert!(forecaster.store_forecast_result.called_with_pattern(
+    assert!(forecaster.store_forecast_result_mock().called_with_pattern(
         matcher!( p!(is_ok, p!(ge, 50)) )
     ));
-    assert!(forecaster.store_forecast_result.called_with_pattern(
+    assert!(forecaster.store_forecast_result_mock().called_with_pattern(
         matcher!( p!(is_err, p!(contains, "sad")) )
     ));
-    assert!(!forecaster.store_forecast_result.called_with_pattern(
+    assert!(!forecaster.store_forecast_result_mock().called_with_pattern(
         matcher!( p!(is_err, p!(contains, "happy")) )
     ));
-    assert!(forecaster.store_forecast_result.called_with_pattern(
+    assert!(forecaster.store_forecast_result_mock().called_with_pattern(
         matcher!(
             p!(is_ok,
                 p!(all_of, vec!(
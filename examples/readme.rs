@@ -21,14 +21,14 @@ impl BalanceSheet for MockBalanceSheet {
 fn test_doubling_a_sheets_profit() {
     // GIVEN:
     let sheet = MockBalanceSheet::default();
-    sheet.profit.return_value(250);
+    sheet.profit_mock().return_value(250);
     // WHEN:
     let profit = double_profit(500, 250, &sheet);
     // THEN:
     // mock return 250, which was double
     assert_eq!(500, profit);
     // assert that the revenue and costs were correctly passed to the mock
-    sheet.profit.has_calls_exactly_in_order(vec!((500, 250)));
+    sheet.profit_mock().has_calls_exactly_in_order(vec!((500, 250)));
 }
 
 // `Result` does not implement the `Default` trait. Trying to mock `UserStore`
@@ -51,7 +51,7 @@ fn test_manually_setting_default_retval() {
     // Construct instance of the mock, manually specifying the default
     // return value for `get_username()`.
     let mock = MockUserStore::new(
-        Ok("default_user_name".to_owned()));
+        Ok("default_username".to_owned()));
     // WHEN:
     let result = mock.get_username(10001);
     // THEN:
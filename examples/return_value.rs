@@ -43,4 +43,20 @@ fn main() {
     assert_eq!(2, mock.profit.call((2, 4)));
     assert_eq!(3, mock.profit.call((3, 6)));
     assert_eq!(42, mock.profit.call((4, 8)));
+
+    // Test return values for specific call indices, which take precedence
+    // over sequences and per-argument return values.
+    let mock = MockDependency::default();
+    mock.profit.return_value(42);
+    mock.profit.return_values(vec!(1, 2, 3));
+    mock.profit.return_value_for((0, 0), 9001);
+    mock.profit.return_value_for_call(1, -1);
+    mock.profit.return_value_for_call(3, -3);
+    assert_eq!(1, mock.profit(1, 2));
+    assert_eq!(-1, mock.profit(0, 0));
+    assert_eq!(2, mock.profit(1, 2));
+    assert_eq!(-3, mock.profit(1, 2));
+    assert_eq!(3, mock.profit(1, 2));
+    assert_eq!(9001, mock.profit(0, 0));
+    assert_eq!(42, mock.profit(1, 2));
 }
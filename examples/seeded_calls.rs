@@ -0,0 +1,108 @@
+#[macro_use]
+extern crate double;
+
+use double::Mock;
+use double::matcher::*;
+
+// Assertion helper built on top of double, which we want to test against
+// synthetic call histories.
+fn called_with_increasing_args(mock: &Mock<i32, ()>) -> bool {
+    let calls = mock.calls();
+    calls.windows(2).all(|pair| pair[0] < pair[1])
+}
+
+fn check_all<F>(driven: &Mock<i32, ()>, seeded: &Mock<i32, ()>, check: F)
+    where F: Fn(&Mock<i32, ()>) -> bool
+{
+    assert_eq!(check(driven), check(seeded));
+}
+
+fn test_seeded_history_matches_driven_history() {
+    // GIVEN:
+    let history = vec!(3, 1, 4, 1, 5);
+    let driven = Mock::<i32, ()>::default();
+    for args in history.clone() {
+        driven.call(args);
+    }
+    let seeded = Mock::<i32, ()>::with_recorded_calls((), history);
+
+    // THEN:
+    assert_eq!(driven.called(), seeded.called());
+    assert_eq!(driven.num_calls(), seeded.num_calls());
+    assert_eq!(driven.calls(), seeded.calls());
+    // Seeded calls were never invoked, so they have no return values.
+    assert_eq!(driven.call_log().len(), 5);
+    assert!(seeded.call_log().is_empty());
+
+    let expectations = vec!(
+        vec!(),
+        vec!(1),
+        vec!(9),
+        vec!(1, 1),
+        vec!(4, 3),
+        vec!(3, 4, 5),
+        vec!(3, 1, 4, 1, 5),
+        vec!(5, 1, 4, 1, 3));
+    for expected in expectations {
+        for &args in expected.iter() {
+            check_all(&driven, &seeded, |mock| mock.called_with(args));
+        }
+        check_all(&driven, &seeded, |mock| mock.has_calls(expected.clone()));
+        check_all(&driven, &seeded, |mock| mock.has_calls_in_order(expected.clone()));
+        check_all(&driven, &seeded, |mock| mock.has_calls_exactly(expected.clone()));
+        check_all(&driven, &seeded, |mock| {
+            mock.has_calls_exactly_in_order(expected.clone())
+        });
+    }
+
+    check_all(&driven, &seeded, |mock| mock.called_with_pattern(p!(gt, 4)));
+    check_all(&driven, &seeded, |mock| mock.called_with_pattern(p!(gt, 5)));
+    check_all(&driven, &seeded, |mock| {
+        mock.has_patterns(vec!(p!(eq, 1), p!(ge, 5)))
+    });
+    check_all(&driven, &seeded, |mock| {
+        mock.has_patterns_in_order(vec!(p!(ge, 5), p!(eq, 1)))
+    });
+    check_all(&driven, &seeded, |mock| {
+        mock.has_patterns_exactly(vec!(p!(lt, 3), p!(ge, 3)))
+    });
+    check_all(&driven, &seeded, |mock| {
+        mock.has_patterns_exactly_in_order(vec!(p!(eq, 3), p!(any)))
+    });
+}
+
+fn test_assertion_helper_against_seeded_history() {
+    // GIVEN:
+    let increasing = Mock::<i32, ()>::with_recorded_calls((), vec!(1, 2, 3));
+    let decreasing = Mock::<i32, ()>::with_recorded_calls((), vec!(3, 2, 1));
+    let appended = Mock::<i32, ()>::default();
+    appended.record_call_without_invoking(1);
+    appended.record_call_without_invoking(1);
+
+    // THEN:
+    assert!(called_with_increasing_args(&increasing));
+    assert!(!called_with_increasing_args(&decreasing));
+    assert!(!called_with_increasing_args(&appended));
+}
+
+fn test_seeding_calls_after_driven_calls() {
+    // GIVEN:
+    let mock = Mock::<i32, i32>::new(7);
+    assert_eq!(7, mock.call(1));
+    mock.seed_calls(vec!(2, 2));
+    let copy = Mock::<i32, i32>::default();
+    copy.seed_calls(mock.calls());
+
+    // THEN:
+    assert_eq!(mock.num_calls(), 3);
+    assert!(mock.has_calls_in_order(vec!(1, 2, 2)));
+    assert!(!mock.has_calls_in_order(vec!(2, 1)));
+    assert_eq!(mock.call_log(), vec!((1, 7)));
+    assert_eq!(copy.calls(), vec!(1, 2, 2));
+}
+
+fn main() {
+    test_seeded_history_matches_driven_history();
+    test_assertion_helper_against_seeded_history();
+    test_seeding_calls_after_driven_calls();
+}
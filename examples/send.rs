@@ -0,0 +1,163 @@
+#[macro_use]
+extern crate double;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+trait Queue {
+    fn push(&self, item: i32) -> bool;
+    fn len(&self) -> usize;
+}
+
+mock_trait!(
+    MockQueue,
+    push(i32) -> bool,
+    len(()) -> usize);
+impl Queue for MockQueue {
+    mock_method!(push(&self, item: i32) -> bool);
+    mock_method!(len(&self) -> usize);
+}
+
+trait Dependency: Clone + Send + 'static {
+    fn greet(&self, name: String);
+}
+
+// Listing the bounds checks the mock satisfies them where it's defined.
+mock_trait!(
+    MockDependency: Clone + Send + 'static,
+    greet(String) -> ());
+impl Dependency for MockDependency {
+    mock_method!(greet(&self, name: String));
+}
+
+// Code under test, which runs the producer on its own thread.
+fn spawn_producer(queue: Box<dyn Queue + Send + Sync>, items: Vec<i32>) -> thread::JoinHandle<usize> {
+    thread::spawn(move || {
+        let num_pushed = items.into_iter().filter(|item| queue.push(*item)).count();
+        assert_eq!(num_pushed, queue.len());
+        num_pushed
+    })
+}
+
+fn test_mock_owned_by_another_thread() {
+    // GIVEN:
+    // Clones of a mock share its configuration and calls, so the mock can be
+    // checked from this thread after the clone is moved to the producer's.
+    let mock = MockQueue::default();
+    mock.push_mock().return_value(true);
+    mock.push_mock().return_value_for(13, false);
+    mock.len_mock().use_closure(Box::new(|_| 2));
+
+    // WHEN:
+    let handle = spawn_producer(Box::new(mock.clone()), vec!(1, 13, 2));
+
+    // THEN:
+    assert_eq!(2, handle.join().unwrap());
+    assert!(mock.push_mock().has_calls_exactly_in_order(vec!(1, 13, 2)));
+    assert_eq!(1, mock.len_mock().num_calls());
+}
+
+// Code under test, generic over a dependency that's moved to other threads.
+struct Consumer<T: Dependency> {
+    dependency: T,
+}
+
+impl<T: Dependency> Consumer<T> {
+    fn greet_everyone_in_background(&self, names: Vec<&'static str>) {
+        let handles: Vec<_> = names.into_iter().map(|name| {
+            let dependency = self.dependency.clone();
+            thread::spawn(move || dependency.greet(name.to_owned()))
+        }).collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+}
+
+fn test_generic_consumer_of_send_dependency() {
+    // GIVEN:
+    let mock = MockDependency::default();
+    let consumer: Consumer<MockDependency> = Consumer { dependency: mock.clone() };
+
+    // WHEN:
+    consumer.greet_everyone_in_background(vec!("Fido", "Spot", "Princess"));
+
+    // THEN:
+    assert_eq!(3, mock.greet_mock().num_calls());
+    assert!(mock.greet_mock().has_calls_exactly(vec!(
+        "Fido".to_owned(),
+        "Spot".to_owned(),
+        "Princess".to_owned())));
+}
+
+// Code under test, which pushes items from a background thread after a delay.
+fn push_later(queue: MockQueue, items: Vec<i32>, delay: Duration) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        thread::sleep(delay);
+        for item in items {
+            queue.push(item);
+        }
+    })
+}
+
+fn test_waiting_for_calls_from_background_thread() {
+    // GIVEN:
+    let mock = MockQueue::default();
+
+    // WHEN:
+    let handle = push_later(mock.clone(), vec!(1, 2, 3), Duration::from_millis(50));
+
+    // THEN:
+    mock.push_mock().try_wait_for_calls(3, Duration::from_secs(5)).unwrap();
+    mock.push_mock().try_wait_for_call_matching(&|item| *item == 2, Duration::from_secs(5)).unwrap();
+    // The calls have already been made, so these return immediately.
+    assert!(mock.push_mock().wait_for_calls(3, Duration::from_millis(0)));
+    assert!(mock.push_mock().wait_for_call_matching(&|item| *item == 3, Duration::from_millis(0)));
+    handle.join().unwrap();
+}
+
+fn test_waiting_for_calls_times_out() {
+    // GIVEN:
+    let mock = MockQueue::default();
+    let handle = push_later(mock.clone(), vec!(1), Duration::from_millis(0));
+    handle.join().unwrap();
+
+    // WHEN:
+    let start = Instant::now();
+    let received_two = mock.push_mock().wait_for_calls(2, Duration::from_millis(20));
+    let received_five = mock.push_mock().try_wait_for_call_matching(
+        &|item| *item == 5, Duration::from_millis(20));
+
+    // THEN:
+    assert!(!received_two);
+    let timeout = received_five.unwrap_err();
+    assert_eq!(&[1], timeout.calls());
+    assert!(timeout.elapsed() >= Duration::from_millis(20));
+    assert!(timeout.to_string().starts_with("timed out waiting for a call matching the pattern"));
+    assert!(start.elapsed() >= Duration::from_millis(40));
+}
+
+fn test_waiting_for_calls_reports_calls_made_before_timeout() {
+    // GIVEN:
+    let mock = MockQueue::default();
+    let handle = push_later(mock.clone(), vec!(1), Duration::from_millis(0));
+
+    // WHEN:
+    // Only one of the three expected items is ever pushed.
+    let timeout = mock.push_mock().try_wait_for_calls(3, Duration::from_millis(50)).unwrap_err();
+    handle.join().unwrap();
+
+    // THEN:
+    assert_eq!(1, timeout.observed());
+    assert_eq!(&[1], timeout.calls());
+    assert!(timeout.elapsed() >= Duration::from_millis(50));
+    assert!(timeout.to_string().starts_with("timed out waiting for 3 calls after "));
+}
+
+fn main() {
+    test_mock_owned_by_another_thread();
+    test_generic_consumer_of_send_dependency();
+    test_waiting_for_calls_from_background_thread();
+    test_waiting_for_calls_times_out();
+    test_waiting_for_calls_reports_calls_made_before_timeout();
+}
@@ -0,0 +1,58 @@
+#[macro_use]
+extern crate double;
+
+trait Logger {
+    fn log(&self, parts: &[&str]);
+    fn log_samples(&self, label: &str, samples: &[u32]) -> bool;
+}
+
+// Code under test
+fn report(logger: &dyn Logger, name: &str, samples: &[u32]) {
+    if !logger.log_samples(name, samples) {
+        logger.log(&["failed to log samples for", name]);
+    }
+}
+
+// Slices and string slices are stored as owned `Vec`s and `String`s. The
+// generated method bodies convert the borrowed arguments automatically.
+mock_trait!(
+    MockLogger,
+    log(Vec<String>) -> (),
+    log_samples(String, Vec<u32>) -> bool);
+impl Logger for MockLogger {
+    mock_method!(log(&self, parts: &[&str]));
+    mock_method!(log_samples(&self, label: &str, samples: &[u32]) -> bool);
+}
+
+fn test_slice_args_are_recorded() {
+    // GIVEN:
+    let logger = MockLogger::default();
+    logger.log_samples_mock().return_value(true);
+
+    // WHEN:
+    report(&logger, "latency", &[3, 1, 4]);
+
+    // THEN:
+    assert!(logger.log_samples_mock().called_with(("latency".to_owned(), vec!(3, 1, 4))));
+    assert!(!logger.log_mock().called());
+}
+
+fn test_string_slice_args_are_recorded() {
+    // GIVEN:
+    let logger = MockLogger::default();
+    logger.log_samples_mock().return_value(false);
+
+    // WHEN:
+    report(&logger, "latency", &[]);
+
+    // THEN:
+    assert!(logger.log_samples_mock().called_with(("latency".to_owned(), vec!())));
+    assert!(logger.log_mock().called_with(vec!(
+        "failed to log samples for".to_string(),
+        "latency".to_string())));
+}
+
+fn main() {
+    test_slice_args_are_recorded();
+    test_string_slice_args_are_recorded();
+}
@@ -0,0 +1,83 @@
+#[macro_use]
+extern crate double;
+
+use std::rc::Rc;
+use std::sync::Arc;
+
+trait Service {
+    fn start(self: Arc<Self>) -> bool;
+    fn stop(self: Arc<Self>, reason: &str);
+}
+
+trait Task {
+    fn run(self: Rc<Self>, attempt: u32) -> Result<(), String>;
+}
+
+mock_trait!(
+    MockService,
+    start(()) -> bool,
+    stop(String) -> ());
+impl Service for MockService {
+    mock_method!(start(self: Arc<Self>) -> bool);
+    mock_method!(stop(self: Arc<Self>, reason: &str));
+}
+
+mock_trait!(
+    MockTask,
+    run(u32) -> Result<(), String> = Ok(()));
+impl Task for MockTask {
+    mock_method!(run(self: Rc<Self>, attempt: u32) -> Result<(), String>, self, {
+        self.run_mock().call(attempt)
+    });
+}
+
+// Code under test, which consumes the service.
+fn launch<S: Service>(service: Arc<S>) -> bool {
+    if Arc::clone(&service).start() {
+        true
+    } else {
+        service.stop("failed to start");
+        false
+    }
+}
+
+fn retry<T: Task>(task: Rc<T>, max_attempts: u32) -> bool {
+    (1..=max_attempts).any(|attempt| Rc::clone(&task).run(attempt).is_ok())
+}
+
+// Mocks are only `Send` and `Sync` with the `sync-mocks` feature, but
+// `Service` needs an `Arc` regardless.
+#[allow(clippy::arc_with_non_send_sync)]
+fn test_launching_service() {
+    // GIVEN:
+    // Clones of a mock share its configuration and calls, so a clone kept by
+    // the test can check the mock after `launch` has consumed the `Arc`.
+    let mock = MockService::default();
+    mock.start_mock().return_value(false);
+
+    // WHEN:
+    let launched = launch(Arc::new(mock.clone()));
+
+    // THEN:
+    assert!(!launched);
+    assert!(mock.start_mock().called());
+    assert!(mock.stop_mock().called_with("failed to start".to_owned()));
+}
+
+fn test_retrying_task() {
+    // GIVEN:
+    let mock = MockTask::default();
+    mock.run_mock().return_values(vec!(Err("busy".to_owned()), Ok(())));
+
+    // WHEN:
+    let succeeded = retry(Rc::new(mock.clone()), 3);
+
+    // THEN:
+    assert!(succeeded);
+    assert!(mock.run_mock().has_calls_exactly_in_order(vec!(1u32, 2u32)));
+}
+
+fn main() {
+    test_launching_service();
+    test_retrying_task();
+}
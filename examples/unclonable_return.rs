@@ -0,0 +1,79 @@
+extern crate double;
+
+use double::Mock;
+
+// Represents a resource that can't be copied, such as a socket or a file
+// handle.
+#[derive(Debug, PartialEq)]
+struct Connection {
+    address: String,
+}
+
+impl Connection {
+    fn open(address: &str) -> Self {
+        Connection { address: address.to_owned() }
+    }
+}
+
+trait ConnectionPool {
+    fn take_connection(&self, priority: u32) -> Connection;
+}
+
+// Code under test
+fn addresses_of_connections(pool: &dyn ConnectionPool, n: u32) -> Vec<String> {
+    (0..n)
+        .map(|priority| pool.take_connection(priority).address)
+        .collect()
+}
+
+// `Connection` does not implement `Clone`, so the mock is constructed with a
+// producer that opens a fresh connection each time the mock is called.
+struct MockConnectionPool {
+    pub take_connection: Mock<u32, Connection>,
+}
+
+impl ConnectionPool for MockConnectionPool {
+    fn take_connection(&self, priority: u32) -> Connection {
+        self.take_connection.call(priority)
+    }
+}
+
+impl Default for MockConnectionPool {
+    fn default() -> Self {
+        MockConnectionPool {
+            take_connection: Mock::with_producer(
+                Box::new(|| Connection::open("default"))),
+        }
+    }
+}
+
+fn test_non_clone_return_values() {
+    // GIVEN:
+    let pool = MockConnectionPool::default();
+    pool.take_connection.return_value_once(Connection::open("primary"));
+
+    // WHEN:
+    let addresses = addresses_of_connections(&pool, 3);
+
+    // THEN:
+    assert_eq!(addresses, vec!("primary", "default", "default"));
+    assert!(pool.take_connection.has_calls_exactly_in_order(vec!(0u32, 1, 2)));
+}
+
+fn test_replacing_producer() {
+    // GIVEN:
+    let pool = MockConnectionPool::default();
+    pool.take_connection.use_producer(Box::new(|| Connection::open("backup")));
+
+    // WHEN:
+    let addresses = addresses_of_connections(&pool, 2);
+
+    // THEN:
+    assert_eq!(addresses, vec!("backup", "backup"));
+    assert_eq!(pool.take_connection.num_calls(), 2);
+}
+
+fn main() {
+    test_non_clone_return_values();
+    test_replacing_producer();
+}
@@ -0,0 +1,118 @@
+#[macro_use]
+extern crate double;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Represents a large buffer that can't (or shouldn't) be copied.
+struct Frame {
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    fn blank(num_pixels: usize) -> Self {
+        Frame { pixels: vec![0; num_pixels] }
+    }
+}
+
+// Large argument that can be cloned, but where cloning is expensive. Counts
+// how many times it has been cloned.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct Snapshot {
+    id: u32,
+    data: Vec<u8>,
+}
+
+static NUM_SNAPSHOT_CLONES: AtomicUsize = AtomicUsize::new(0);
+
+impl Clone for Snapshot {
+    fn clone(&self) -> Self {
+        NUM_SNAPSHOT_CLONES.fetch_add(1, Ordering::SeqCst);
+        Snapshot { id: self.id, data: self.data.clone() }
+    }
+}
+
+fn num_snapshot_clones() -> usize {
+    NUM_SNAPSHOT_CLONES.load(Ordering::SeqCst)
+}
+
+trait Display {
+    fn draw(&self, frame: Frame) -> bool;
+}
+
+// Code under test
+fn draw_all(display: &dyn Display, frames: Vec<Frame>) -> usize {
+    frames
+        .into_iter()
+        .map(|frame| display.draw(frame))
+        .filter(|drawn| *drawn)
+        .count()
+}
+
+// `Frame` does not implement `Clone`, so it can't be used as a mock's args.
+// Instead, the mock only receives the size of each frame, which is enough to
+// select a return value. The size is not recorded, since the test only cares
+// about how many frames were drawn.
+mock_trait!(
+    MockDisplay,
+    draw(usize) -> bool);
+impl Display for MockDisplay {
+    mock_method!(draw(&self, frame: Frame) -> bool, self, {
+        self.draw_mock().call_without_recording(frame.pixels.len())
+    });
+}
+
+fn test_non_clone_args_are_counted() {
+    // GIVEN:
+    let display = MockDisplay::default();
+    display.draw_mock().return_value(true);
+    display.draw_mock().return_value_for(0usize, false);
+
+    // WHEN:
+    let frames = vec!(Frame::blank(4), Frame::blank(0), Frame::blank(16));
+    let num_drawn = draw_all(&display, frames);
+
+    // THEN:
+    assert_eq!(num_drawn, 2);
+    assert_eq!(display.draw_mock().num_calls(), 3);
+    assert!(display.draw_mock().calls().is_empty());
+}
+
+fn test_disabling_call_recording() {
+    // GIVEN:
+    let draw = double::Mock::<usize, bool>::new(true);
+    draw.call(4);
+    draw.disable_call_recording();
+
+    // WHEN:
+    draw.call(8);
+    draw.call(16);
+
+    // THEN:
+    assert_eq!(draw.num_calls(), 3);
+    assert_eq!(draw.calls(), vec!(4));
+}
+
+fn test_disabling_call_recording_skips_clones() {
+    // GIVEN:
+    let save = double::Mock::<Snapshot, bool>::new(false);
+    save.use_closure(Box::new(|snapshot| snapshot.id % 2 == 0));
+    save.disable_call_recording();
+    let clones_before = num_snapshot_clones();
+
+    // WHEN:
+    let results: Vec<bool> = (0..4)
+        .map(|id| save.call(Snapshot { id, data: vec![0; 1024 * 1024] }))
+        .collect();
+
+    // THEN:
+    assert_eq!(vec!(true, false, true, false), results);
+    assert_eq!(clones_before, num_snapshot_clones());
+    assert_eq!(4, save.num_calls());
+    assert!(save.calls().is_empty());
+}
+
+fn main() {
+    test_non_clone_args_are_counted();
+    test_disabling_call_recording();
+    test_disabling_call_recording_skips_clones();
+}
@@ -0,0 +1,76 @@
+#[macro_use]
+extern crate double;
+
+use std::fmt::Display;
+
+trait Reporter {
+    fn report<T>(&self, label: &str, value: T) -> bool
+        where T: Display;
+    fn history(&self) -> impl Iterator<Item = u32>;
+}
+
+// Code under test
+fn report_history<R: Reporter>(reporter: &R) -> u32 {
+    let total = reporter.history().sum::<u32>();
+    if !reporter.report("total", total) {
+        reporter.report("error", "failed to report total");
+    }
+    total
+}
+
+mock_trait!(
+    MockReporter,
+    report((String, String)) -> bool,
+    history(()) -> Vec<u32>);
+impl Reporter for MockReporter {
+    // The where clause is passed in parentheses, after the return type.
+    mock_method!(report<(T)>(&self, label: &str, value: T) -> bool where (T: Display), self, {
+        self.report_mock().call((label.to_owned(), value.to_string()))
+    });
+
+    // `impl Trait` values can't be stored in a `Mock`, so the underlying mock
+    // stores the items and the custom body builds a new iterator over them.
+    mock_method!(history(&self) -> impl Iterator<Item = u32>, self, {
+        self.history_mock().call(()).into_iter()
+    });
+}
+
+fn test_where_clause_method_is_recorded() {
+    // GIVEN:
+    let reporter = MockReporter::default();
+    reporter.history_mock().return_value(vec!(1, 2, 3));
+    reporter.report_mock().return_value(true);
+
+    // WHEN:
+    let total = report_history(&reporter);
+
+    // THEN:
+    assert_eq!(6, total);
+    assert!(reporter.history_mock().called());
+    assert!(reporter.report_mock().has_calls_exactly(vec!(
+        ("total".to_owned(), "6".to_owned()))));
+}
+
+fn test_impl_trait_method_is_recorded() {
+    // GIVEN:
+    let reporter = MockReporter::default();
+    reporter.history_mock().return_values(vec!(vec!(5), vec!(7, 8)));
+
+    // WHEN:
+    let first = report_history(&reporter);
+    let second = report_history(&reporter);
+
+    // THEN:
+    assert_eq!(5, first);
+    assert_eq!(15, second);
+    assert_eq!(2, reporter.history_mock().num_calls());
+    assert!(reporter.report_mock().has_calls_in_order(vec!(
+        ("total".to_owned(), "5".to_owned()),
+        ("error".to_owned(), "failed to report total".to_owned()),
+        ("total".to_owned(), "15".to_owned()))));
+}
+
+fn main() {
+    test_where_clause_method_is_recorded();
+    test_impl_trait_method_is_recorded();
+}
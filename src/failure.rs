@@ -0,0 +1,290 @@
+//! Reporting of failed `Mock` verification checks.
+//!
+//! Each verification check on a `Mock` is available in three shapes:
+//!
+//! | Shape     | Example                    | On failure                          |
+//! | --------- | -------------------------- | ----------------------------------- |
+//! | `bool`    | `called_with(args)`        | returns `false`                     |
+//! | `check_`  | `check_called_with(args)`  | returns `Err(CallMismatch)`         |
+//! | `assert_` | `assert_called_with(args)` | panics with the `CallMismatch` text |
+//!
+//! The `check_` and `assert_` shapes describe the failure using
+//! `format_mismatch`, so a failed check reads the same regardless of whether
+//! it was returned as an error or raised as a panic.
+//!
+//! # Examples
+//!
+//! ```
+//! use double::Mock;
+//!
+//! let mock = Mock::<i32, ()>::default();
+//! mock.call(1);
+//! mock.call(2);
+//!
+//! assert!(!mock.called_with(3));
+//!
+//! let mismatch = mock.check_called_with(3).unwrap_err();
+//! assert_eq!(
+//!     mismatch.to_string(),
+//!     "Mock::called_with failed\n  expected: 3\n  actual calls: [1, 2]");
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+#[cfg(feature = "sync-mocks")]
+use std::time::Duration;
+
+/// Describes a failed verification check on a `Mock`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallMismatch {
+    check: String,
+    expected: String,
+    actual_calls: Vec<String>,
+}
+
+impl CallMismatch {
+    /// Creates a `CallMismatch` for a failed `check` (the name of the bool
+    /// shaped `Mock` method), given descriptions of what was `expected` and
+    /// of the calls that were actually recorded.
+    pub fn new<S: Into<String>, T: Into<String>>(
+        check: S,
+        expected: T,
+        actual_calls: Vec<String>) -> Self
+    {
+        CallMismatch {
+            check: check.into(),
+            expected: expected.into(),
+            actual_calls,
+        }
+    }
+
+    /// Name of the check that failed, e.g. `called_with`.
+    pub fn check(&self) -> &str {
+        &self.check
+    }
+
+    /// Description of what the check expected.
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    /// Descriptions of the calls the mock actually recorded, in order from
+    /// first to last.
+    pub fn actual_calls(&self) -> &[String] {
+        &self.actual_calls
+    }
+}
+
+impl Display for CallMismatch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", format_mismatch(&self.check, &self.expected, &self.actual_calls))
+    }
+}
+
+impl Error for CallMismatch {}
+
+/// Describes what a `Mock` had observed when `Mock::try_wait_for_calls` or
+/// `Mock::try_wait_for_call_matching` timed out. Requires the `sync-mocks`
+/// feature.
+///
+/// # Examples
+///
+/// ```
+/// use double::Mock;
+/// use std::time::Duration;
+///
+/// let mock = Mock::<i32, ()>::default();
+/// mock.call(1);
+///
+/// let timeout = mock.try_wait_for_calls(3, Duration::from_millis(10)).unwrap_err();
+/// assert_eq!(timeout.observed(), 1);
+/// assert_eq!(timeout.calls(), [1]);
+/// assert!(timeout.elapsed() >= Duration::from_millis(10));
+/// assert!(timeout.to_string().starts_with("timed out waiting for 3 calls after "));
+/// ```
+#[cfg(feature = "sync-mocks")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WaitTimeout<C> {
+    expected: String,
+    observed: usize,
+    calls: Vec<C>,
+    elapsed: Duration,
+}
+
+#[cfg(feature = "sync-mocks")]
+impl<C> WaitTimeout<C> {
+    pub(crate) fn new(expected: String, observed: usize, calls: Vec<C>, elapsed: Duration) -> Self {
+        WaitTimeout { expected, observed, calls, elapsed }
+    }
+
+    /// Number of calls made before the wait timed out, including calls
+    /// whose arguments were not recorded.
+    pub fn observed(&self) -> usize {
+        self.observed
+    }
+
+    /// Arguments of the calls recorded before the wait timed out, in order
+    /// from first to last.
+    pub fn calls(&self) -> &[C] {
+        &self.calls
+    }
+
+    /// How long the wait lasted before it timed out.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+#[cfg(feature = "sync-mocks")]
+impl<C: fmt::Debug> Display for WaitTimeout<C> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "timed out waiting for {} after {:?}, {} call{} observed: {:?}",
+            self.expected,
+            self.elapsed,
+            self.observed,
+            if self.observed == 1 { "" } else { "s" },
+            self.calls)
+    }
+}
+
+#[cfg(feature = "sync-mocks")]
+impl<C: fmt::Debug> Error for WaitTimeout<C> {}
+
+/// Formats the message used by every failed verification check.
+///
+/// # Examples
+///
+/// ```
+/// use double::failure::format_mismatch;
+///
+/// assert_eq!(
+///     format_mismatch("called", "at least one call", &[]),
+///     "Mock::called failed\n  expected: at least one call\n  actual calls: []");
+/// ```
+pub fn format_mismatch(check: &str, expected: &str, actual_calls: &[String]) -> String {
+    format!(
+        "Mock::{} failed\n  expected: {}\n  actual calls: [{}]",
+        check,
+        expected,
+        actual_calls.join(", "))
+}
+
+// Panics if `result` is a failed check. Used by the `assert_` shaped checks.
+pub(crate) fn assert_check(result: Result<(), CallMismatch>) {
+    if let Err(mismatch) = result {
+        panic!("{}", mismatch)
+    }
+}
+
+
+// ============================================================================
+// * Unit Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use super::*;
+    use crate::mock::Mock;
+
+    fn panic_message<F: FnOnce() + panic::UnwindSafe>(f: F) -> String {
+        let err = panic::catch_unwind(f).unwrap_err();
+        match err.downcast::<String>() {
+            Ok(message) => *message,
+            Err(err) => err.downcast_ref::<&str>().unwrap().to_string(),
+        }
+    }
+
+    #[test]
+    fn mismatch_display_uses_format_mismatch() {
+        let mismatch = CallMismatch::new("called_with", "3", vec!("1".to_owned()));
+        assert_eq!(
+            mismatch.to_string(),
+            format_mismatch("called_with", "3", &["1".to_owned()]));
+        assert_eq!(mismatch.check(), "called_with");
+        assert_eq!(mismatch.expected(), "3");
+        assert_eq!(mismatch.actual_calls(), ["1"]);
+    }
+
+    #[test]
+    fn every_failure_shape_reports_the_same_message() {
+        let expected_message = format_mismatch(
+            "has_calls_in_order",
+            "[2, 1]",
+            &["1".to_owned(), "2".to_owned()]);
+
+        let mock = Mock::<i32, ()>::default();
+        mock.call(1);
+        mock.call(2);
+
+        assert!(!mock.has_calls_in_order(vec!(2, 1)));
+
+        let mismatch = mock.check_has_calls_in_order(vec!(2, 1)).unwrap_err();
+        assert_eq!(mismatch.to_string(), expected_message);
+
+        let message = panic_message(panic::AssertUnwindSafe(|| {
+            mock.assert_has_calls_in_order(vec!(2, 1))
+        }));
+        assert_eq!(message, expected_message);
+    }
+
+    #[test]
+    fn passing_checks_return_ok() {
+        let mock = Mock::<i32, ()>::default();
+        mock.call(1);
+
+        assert_eq!(mock.check_called(), Ok(()));
+        assert_eq!(mock.check_called_with(1), Ok(()));
+        assert_eq!(mock.check_has_patterns_exactly(vec!(&|x: &i32| *x == 1)), Ok(()));
+        mock.assert_called_with_pattern(&|x: &i32| *x > 0);
+    }
+
+    struct BalanceSheet {
+        profit: Mock<(i32, i32), i32>,
+    }
+
+    #[test]
+    fn expect_macros_pass_silently() {
+        let sheet = BalanceSheet { profit: Mock::default() };
+        sheet.profit.call((500, 250));
+        sheet.profit.call((0, 0));
+
+        crate::expect_called!(sheet.profit, (500, 250));
+        crate::expect_called_pattern!(sheet.profit, &|args: &(i32, i32)| args.0 == 500);
+        crate::expect_has_calls_exactly_in_order!(sheet.profit, vec!((500, 250), (0, 0)));
+    }
+
+    #[test]
+    fn expect_macros_panic_with_mock_expression_and_calls() {
+        let sheet = BalanceSheet { profit: Mock::default() };
+        sheet.profit.call((1, 2));
+        sheet.profit.call((3, 4));
+
+        let message = panic_message(panic::AssertUnwindSafe(|| {
+            crate::expect_called!(sheet.profit, (500, 250))
+        }));
+        assert!(message.starts_with("expect_called!(sheet.profit) failed at "));
+        assert!(message.contains("src/failure.rs:"));
+        assert!(message.ends_with(&format_mismatch(
+            "called_with",
+            "(500, 250)",
+            &["(1, 2)".to_owned(), "(3, 4)".to_owned()])));
+
+        let message = panic_message(panic::AssertUnwindSafe(|| {
+            crate::expect_called_pattern!(sheet.profit, &|args: &(i32, i32)| args.0 == 500)
+        }));
+        assert!(message.starts_with("expect_called_pattern!(sheet.profit) failed at "));
+        assert!(message.contains("actual calls: [(1, 2), (3, 4)]"));
+
+        let message = panic_message(panic::AssertUnwindSafe(|| {
+            crate::expect_has_calls_exactly_in_order!(sheet.profit, vec!((3, 4), (1, 2)))
+        }));
+        assert!(message.starts_with("expect_has_calls_exactly_in_order!(sheet.profit) failed at "));
+        assert!(message.contains("expected: [(3, 4), (1, 2)]"));
+        assert!(message.contains("actual calls: [(1, 2), (3, 4)]"));
+    }
+}
@@ -0,0 +1,143 @@
+//! Helpers for mocking C-style callback registration APIs.
+//!
+//! FFI-facing traits often register a callback using a function pointer plus
+//! an opaque "user data" pointer, like so:
+//!
+//! ```
+//! use std::os::raw::c_void;
+//!
+//! trait EventSource {
+//!     fn register(&self, cb: extern "C" fn(*mut c_void, u32), user_data: *mut c_void);
+//! }
+//! ```
+//!
+//! Function pointers can be stored in a `Mock` directly, since they implement
+//! `Clone`, `Eq` and `Hash`. Raw pointers can also be stored directly, but
+//! recording them as plain addresses (`usize`) makes expectations much easier
+//! to write and keeps the pointer's identity without implying it is still
+//! valid. Use `addr()` to decay a pointer to an address inside a custom
+//! `mock_method!` body and `ptr()` to turn a recorded address back into a
+//! pointer when driving a captured callback from a test. `captured_callbacks()`
+//! and `last_captured_callback()` return the registered pairs from a `Mock`
+//! that records them.
+//!
+//! Calling a captured `extern "C" fn` pointer is safe in Rust. Any `unsafe`
+//! code lives inside the callback itself, where the user data pointer is
+//! dereferenced. Tests must therefore ensure whatever the user data pointer
+//! refers to is still alive when the captured callback is invoked.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[macro_use] extern crate double;
+//! use std::os::raw::c_void;
+//! use double::ffi;
+//!
+//! type Callback = extern "C" fn(*mut c_void, u32);
+//!
+//! trait EventSource {
+//!     fn register(&self, cb: Callback, user_data: *mut c_void);
+//! }
+//!
+//! mock_trait!(
+//!     MockEventSource,
+//!     register(Callback, usize) -> ());
+//! impl EventSource for MockEventSource {
+//!     mock_method!(register(&self, cb: Callback, user_data: *mut c_void), self, {
+//!         self.register_mock().call((cb, ffi::addr(user_data)))
+//!     });
+//! }
+//!
+//! extern "C" fn add_to_total(user_data: *mut c_void, value: u32) {
+//!     let total = unsafe { &mut *(user_data as *mut u32) };
+//!     *total += value;
+//! }
+//!
+//! # fn main() {
+//! let mut total = 0u32;
+//! let total_ptr = &mut total as *mut u32 as *mut c_void;
+//!
+//! let source = MockEventSource::default();
+//! source.register(add_to_total, total_ptr);
+//!
+//! // Verify the registration args and drive the captured callback.
+//! let callbacks = ffi::captured_callbacks(source.register_mock());
+//! assert_eq!(callbacks.len(), 1);
+//! let (callback, user_data) = callbacks[0];
+//! assert_eq!(user_data, ffi::addr(total_ptr));
+//! callback(ffi::ptr(user_data), 42);
+//!
+//! assert_eq!(total, 42);
+//! # }
+//! ```
+
+use std::hash::Hash;
+
+use crate::mock::Mock;
+
+/// Returns the address of `ptr`, so it can be recorded by a `Mock`.
+///
+/// Both `*const T` and `*mut T` pointers are accepted.
+pub fn addr<T>(ptr: *const T) -> usize {
+    ptr as usize
+}
+
+/// Converts an address recorded using `addr()` back into a raw pointer.
+///
+/// This is safe, since the returned pointer is not dereferenced. Whoever
+/// dereferences it must make sure the original pointee is still alive.
+pub fn ptr<T>(addr: usize) -> *mut T {
+    addr as *mut T
+}
+
+/// Returns all `(callback, user_data_address)` pairs passed to a mocked
+/// callback registration method, in order from first to last.
+///
+/// # Examples
+///
+/// ```
+/// use std::os::raw::c_void;
+/// use double::{ffi, Mock};
+///
+/// extern "C" fn on_event(_: *mut c_void, _: u32) {}
+///
+/// let mut user_data = 0u32;
+/// let mock = Mock::<(extern "C" fn(*mut c_void, u32), usize), ()>::default();
+/// mock.call((on_event, ffi::addr(&mut user_data as *mut u32)));
+///
+/// let callbacks = ffi::captured_callbacks(&mock);
+/// assert_eq!(callbacks.len(), 1);
+/// assert_eq!(callbacks[0].1, ffi::addr(&user_data as *const u32));
+/// ```
+pub fn captured_callbacks<F, R>(mock: &Mock<(F, usize), R>) -> Vec<(F, usize)>
+    where F: Copy + Eq + Hash
+{
+    mock.calls()
+}
+
+/// Returns the most recently registered `(callback, user_data_address)` pair,
+/// or `None` if no callback has been registered.
+///
+/// # Examples
+///
+/// ```
+/// use std::os::raw::c_void;
+/// use double::{ffi, Mock};
+///
+/// extern "C" fn first(_: *mut c_void, _: u32) {}
+/// extern "C" fn second(_: *mut c_void, _: u32) {}
+///
+/// let mock = Mock::<(extern "C" fn(*mut c_void, u32), usize), ()>::default();
+/// assert!(ffi::last_captured_callback(&mock).is_none());
+///
+/// mock.call((first, 1));
+/// mock.call((second, 2));
+///
+/// let (_, user_data) = ffi::last_captured_callback(&mock).unwrap();
+/// assert_eq!(user_data, 2);
+/// ```
+pub fn last_captured_callback<F, R>(mock: &Mock<(F, usize), R>) -> Option<(F, usize)>
+    where F: Copy + Eq + Hash
+{
+    mock.calls().pop()
+}
@@ -30,14 +30,14 @@
 //! fn test_doubling_a_sheets_profit() {
 //!     // GIVEN:
 //!     let sheet = MockBalanceSheet::default();
-//!     sheet.profit.return_value(250);
+//!     sheet.profit_mock().return_value(250);
 //!     // WHEN:
 //!     let profit = double_profit(500, 250, &sheet);
 //!     // THEN:
 //!     // mock return 250, which was double
 //!     assert_eq!(500, profit);
 //!     // assert that the revenue and costs were correctly passed to the mock
-//!     sheet.profit.has_calls_exactly_in_order(vec!((500, 250)));
+//!     sheet.profit_mock().has_calls_exactly_in_order(vec!((500, 250)));
 //! }
 //!
 //! // Executing test
@@ -47,7 +47,14 @@
 //! ```
 
 pub use crate::mock::Mock;
+pub use double_macros::{mock, mock_impl_trait};
+#[doc(hidden)]
+pub use double_macros::__private_mock_trait_interactions_impl;
+#[doc(hidden)]
+pub use double_macros::__private_paste;
 
+pub mod failure;
+pub mod ffi;
 pub mod macros;
 pub mod matcher;
 pub mod mock;
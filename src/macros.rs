@@ -1,35 +1,767 @@
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use crate::mock::Mock;
+
+/// Converts an argument passed to a mocked method into the type the method's
+/// underlying `Mock` stores.
+///
+/// Bodies generated by `mock_method!` use this to pass each argument to the
+/// `Mock`, so arguments can be borrowed versions of the stored types:
+///
+/// | Argument type | Stored type    |
+/// | ------------- | -------------- |
+/// | `T`           | `T`            |
+/// | `&T`          | `T`            |
+/// | `&str`        | `String`       |
+/// | `&[T]`        | `Vec<T>`       |
+/// | `&[&str]`     | `Vec<String>`  |
+/// | `&Path`       | `PathBuf`      |
+/// | `&OsStr`      | `OsString`     |
+/// | `Cow<str>`    | `String`       |
+/// | `Cow<[T]>`    | `Vec<T>`       |
+/// | `Cow<Path>`   | `PathBuf`      |
+/// | `Cow<OsStr>`  | `OsString`     |
+///
+/// # Examples
+///
+/// ```
+/// use double::macros::IntoOwnedArg;
+///
+/// let parts: &[&str] = &["a", "b"];
+/// let owned: Vec<String> = parts.into_owned_arg();
+/// assert_eq!(owned, vec!("a".to_owned(), "b".to_owned()));
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "`mock_method!` can't pass a `{Self}` argument to a mock that stores `{T}`",
+    label = "the argument types listed for this method in `mock_trait!` don't match its signature",
+    note = "the arguments listed in `mock_trait!` must be the owned versions of the method's \
+            arguments, e.g. `String` for `&str`"
+)]
+pub trait IntoOwnedArg<T> {
+    /// Returns an owned copy of the argument.
+    fn into_owned_arg(self) -> T;
+}
+
+impl<T: Clone> IntoOwnedArg<T> for T {
+    fn into_owned_arg(self) -> T {
+        self
+    }
+}
+
+impl<T: Clone> IntoOwnedArg<T> for &T {
+    fn into_owned_arg(self) -> T {
+        self.clone()
+    }
+}
+
+impl IntoOwnedArg<String> for &str {
+    fn into_owned_arg(self) -> String {
+        self.to_owned()
+    }
+}
+
+impl<T: Clone> IntoOwnedArg<Vec<T>> for &[T] {
+    fn into_owned_arg(self) -> Vec<T> {
+        self.to_vec()
+    }
+}
+
+impl IntoOwnedArg<Vec<String>> for &[&str] {
+    fn into_owned_arg(self) -> Vec<String> {
+        self.iter().map(|part| (*part).to_owned()).collect()
+    }
+}
+
+impl IntoOwnedArg<PathBuf> for &Path {
+    fn into_owned_arg(self) -> PathBuf {
+        self.to_path_buf()
+    }
+}
+
+impl IntoOwnedArg<OsString> for &OsStr {
+    fn into_owned_arg(self) -> OsString {
+        self.to_os_string()
+    }
+}
+
+impl IntoOwnedArg<String> for Cow<'_, str> {
+    fn into_owned_arg(self) -> String {
+        self.into_owned()
+    }
+}
+
+impl<T: Clone> IntoOwnedArg<Vec<T>> for Cow<'_, [T]> {
+    fn into_owned_arg(self) -> Vec<T> {
+        self.into_owned()
+    }
+}
+
+impl IntoOwnedArg<PathBuf> for Cow<'_, Path> {
+    fn into_owned_arg(self) -> PathBuf {
+        self.into_owned()
+    }
+}
+
+impl IntoOwnedArg<OsString> for Cow<'_, OsStr> {
+    fn into_owned_arg(self) -> OsString {
+        self.into_owned()
+    }
+}
+
+/// Converts an argument of a generic type, passed to a mocked method, into
+/// the type the method's underlying `Mock` stores.
+///
+/// Bodies generated by `mock_method!` use this for arguments annotated with
+/// `as` followed by the stored type, e.g. `from: P as PathBuf`. The argument
+/// can be of any type with the bound listed below:
+///
+/// | Stored type | Argument bound  |
+/// | ----------- | --------------- |
+/// | `PathBuf`   | `AsRef<Path>`   |
+/// | `OsString`  | `AsRef<OsStr>`  |
+/// | `String`    | `Into<String>`  |
+/// | `Vec<T>`    | `AsRef<[T]>`    |
+///
+/// # Examples
+///
+/// ```
+/// use std::path::PathBuf;
+/// use double::macros::FromGenericArg;
+///
+/// fn store<P: AsRef<std::path::Path>>(path: P) -> PathBuf {
+///     PathBuf::from_generic_arg(path)
+/// }
+///
+/// assert_eq!(store("a.txt"), PathBuf::from("a.txt"));
+/// assert_eq!(store(String::from("b.txt")), PathBuf::from("b.txt"));
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "`mock_method!` can't store a `{A}` argument as `{Self}`",
+    label = "the type after `as` doesn't match the argument's bounds",
+    note = "arguments stored as `PathBuf`, `OsString`, `String` or `Vec<T>` must be bound by \
+            `AsRef<Path>`, `AsRef<OsStr>`, `Into<String>` or `AsRef<[T]>` respectively"
+)]
+pub trait FromGenericArg<A>: Sized {
+    /// Returns the owned value the argument is stored as.
+    fn from_generic_arg(arg: A) -> Self;
+}
+
+impl<A: AsRef<Path>> FromGenericArg<A> for PathBuf {
+    fn from_generic_arg(arg: A) -> Self {
+        arg.as_ref().to_path_buf()
+    }
+}
+
+impl<A: AsRef<OsStr>> FromGenericArg<A> for OsString {
+    fn from_generic_arg(arg: A) -> Self {
+        arg.as_ref().to_os_string()
+    }
+}
+
+impl<A: Into<String>> FromGenericArg<A> for String {
+    fn from_generic_arg(arg: A) -> Self {
+        arg.into()
+    }
+}
+
+impl<T: Clone, A: AsRef<[T]>> FromGenericArg<A> for Vec<T> {
+    fn from_generic_arg(arg: A) -> Self {
+        arg.as_ref().to_vec()
+    }
+}
+
+/// Converts a borrowed argument passed to a mocked method into the type the
+/// method's underlying `Mock` stores, without consuming the argument.
+///
+/// This performs the same conversions as `IntoOwnedArg`, and also converts
+/// `&mut T` arguments to `T`. Bodies generated by `mock_method!` for methods
+/// with out-parameters use this to record each argument, so the arguments
+/// can still be passed to the method's effect afterwards.
+///
+/// # Examples
+///
+/// ```
+/// use double::macros::ToOwnedArg;
+///
+/// let mut buf = vec!(1, 2);
+/// let out = &mut buf;
+/// let owned: Vec<u8> = (&out).to_owned_arg();
+/// out.push(3);
+/// assert_eq!(owned, vec!(1, 2));
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "`mock_method!` can't record a `{Self}` argument in a mock that stores `{T}`",
+    label = "the argument types listed for this method in `mock_trait!` don't match its signature",
+    note = "the arguments listed in `mock_trait!` must be the owned versions of the method's \
+            arguments, e.g. `String` for `&str`"
+)]
+pub trait ToOwnedArg<T> {
+    /// Returns an owned copy of the argument.
+    fn to_owned_arg(&self) -> T;
+}
+
+impl<T: Clone> ToOwnedArg<T> for T {
+    fn to_owned_arg(&self) -> T {
+        self.clone()
+    }
+}
+
+impl<T: Clone> ToOwnedArg<T> for &T {
+    fn to_owned_arg(&self) -> T {
+        (*self).clone()
+    }
+}
+
+impl<T: Clone> ToOwnedArg<T> for &mut T {
+    fn to_owned_arg(&self) -> T {
+        (**self).clone()
+    }
+}
+
+impl ToOwnedArg<String> for &str {
+    fn to_owned_arg(&self) -> String {
+        (*self).to_owned()
+    }
+}
+
+impl<T: Clone> ToOwnedArg<Vec<T>> for &[T] {
+    fn to_owned_arg(&self) -> Vec<T> {
+        self.to_vec()
+    }
+}
+
+impl ToOwnedArg<Vec<String>> for &[&str] {
+    fn to_owned_arg(&self) -> Vec<String> {
+        self.iter().map(|part| (*part).to_owned()).collect()
+    }
+}
+
+impl ToOwnedArg<PathBuf> for &Path {
+    fn to_owned_arg(&self) -> PathBuf {
+        self.to_path_buf()
+    }
+}
+
+impl ToOwnedArg<OsString> for &OsStr {
+    fn to_owned_arg(&self) -> OsString {
+        self.to_os_string()
+    }
+}
+
+impl ToOwnedArg<String> for Cow<'_, str> {
+    fn to_owned_arg(&self) -> String {
+        (**self).to_owned()
+    }
+}
+
+impl<T: Clone> ToOwnedArg<Vec<T>> for Cow<'_, [T]> {
+    fn to_owned_arg(&self) -> Vec<T> {
+        self.to_vec()
+    }
+}
+
+impl ToOwnedArg<PathBuf> for Cow<'_, Path> {
+    fn to_owned_arg(&self) -> PathBuf {
+        self.to_path_buf()
+    }
+}
+
+impl ToOwnedArg<OsString> for Cow<'_, OsStr> {
+    fn to_owned_arg(&self) -> OsString {
+        self.to_os_string()
+    }
+}
+
+/// The argument types of a mocked method, as listed in `mock_trait!`.
+///
+/// Mocks implement this once per method, keyed by `method_id` of the method's
+/// name. Bodies generated by `mock_method!` store the arguments they pass to
+/// the method's `Mock` in a variable of type `Args`, so arguments that don't
+/// match the listed types fail to compile where `mock_method!` is used,
+/// rather than inside the macro. Use `mock_method_args!` to implement it.
+#[diagnostic::on_unimplemented(
+    message = "`mock_method!` can't find the argument types of a method of `{Self}`",
+    label = "the method isn't declared in `mock_trait!` or `mock_method_args!`",
+    note = "mocks that aren't generated by `mock_trait!` must declare the argument types of \
+            their methods with `mock_method_args!`"
+)]
+pub trait MockMethodArgs<const METHOD: u64> {
+    /// The tuple of arguments the method's `Mock` stores.
+    type Args;
+}
+
+/// Returns the key `MockMethodArgs` is implemented with for the method called
+/// `name`. This is the FNV-1a hash of the name.
+#[doc(hidden)]
+pub const fn method_id(name: &str) -> u64 {
+    let bytes = name.as_bytes();
+    let mut id: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut i = 0;
+    while i < bytes.len() {
+        id ^= bytes[i] as u64;
+        id = id.wrapping_mul(0x0100_0000_01b3);
+        i += 1;
+    }
+    id
+}
+
+// Used by the `Debug` impls generated for mock structs, which can't require
+// the mocked methods' argument and return types to implement `Debug`. The
+// generated code calls `debug_mock()` on a `&DebugMock`. Method resolution
+// picks `FullDebug` if the `Mock` implements `Debug`, since it doesn't need
+// the receiver to be auto-referenced, and falls back to `SummaryDebug`
+// otherwise. This works because the mocked types are concrete.
+#[doc(hidden)]
+pub struct DebugMock<'a, C: 'a + Clone + Eq + Hash, R: 'a>(pub &'a Mock<C, R>);
+
+#[doc(hidden)]
+pub trait FullDebug<'a> {
+    fn debug_mock(&self) -> Box<dyn Debug + 'a>;
+}
+
+impl<'a, C, R> FullDebug<'a> for DebugMock<'a, C, R>
+    where C: Clone + Debug + Eq + Hash,
+          R: Debug
+{
+    fn debug_mock(&self) -> Box<dyn Debug + 'a> {
+        Box::new(self.0)
+    }
+}
+
+#[doc(hidden)]
+pub trait SummaryDebug<'a> {
+    fn debug_mock(&self) -> Box<dyn Debug + 'a>;
+}
+
+impl<'a, C: Clone + Eq + Hash, R> SummaryDebug<'a> for &DebugMock<'a, C, R> {
+    fn debug_mock(&self) -> Box<dyn Debug + 'a> {
+        Box::new(MockSummary { num_calls: self.0.num_calls() })
+    }
+}
+
+// Used by the `interactions()` methods generated for mock structs, which
+// can't require the mocked methods' argument types to implement `Debug`.
+// Works like `DebugMock`: `FullDebugCalls` formats the arguments of each call
+// if they implement `Debug`, and `SummaryDebugCalls` formats them as `..`
+// otherwise.
+#[doc(hidden)]
+pub struct DebugCalls<'a, C: 'a + Clone + Eq + Hash, R: 'a>(pub &'a Mock<C, R>);
+
+#[doc(hidden)]
+pub trait FullDebugCalls {
+    fn debug_calls(&self) -> Vec<(usize, String)>;
+}
+
+impl<'a, C: Clone + Debug + Eq + Hash, R> FullDebugCalls for DebugCalls<'a, C, R> {
+    fn debug_calls(&self) -> Vec<(usize, String)> {
+        self.0
+            .timestamped_calls()
+            .into_iter()
+            .map(|(timestamp, args)| (timestamp, format!("{:?}", args)))
+            .collect()
+    }
+}
+
+#[doc(hidden)]
+pub trait SummaryDebugCalls {
+    fn debug_calls(&self) -> Vec<(usize, String)>;
+}
+
+impl<'a, C: Clone + Eq + Hash, R> SummaryDebugCalls for &DebugCalls<'a, C, R> {
+    fn debug_calls(&self) -> Vec<(usize, String)> {
+        self.0
+            .call_timestamps()
+            .into_iter()
+            .map(|timestamp| (timestamp, "..".to_owned()))
+            .collect()
+    }
+}
+
+// Used by the `Default` impls generated by `#[double::mock]`, which can't
+// require the mocked methods' return types to implement `Default`. Works like
+// `DebugMock`: `DefaultReturn` is picked if the return type implements
+// `Default`. Otherwise, `NoDefaultReturn` creates a `Mock` which panics if it's
+// called before a return value is configured.
+#[doc(hidden)]
+pub struct DefaultMock<C, R>(pub PhantomData<(C, R)>);
+
+#[doc(hidden)]
+pub trait DefaultReturn<C: Clone + Eq + Hash, R> {
+    fn default_mock(&self) -> Mock<C, R>;
+}
+
+impl<C, R> DefaultReturn<C, R> for DefaultMock<C, R>
+    where C: Clone + Eq + Hash,
+          R: Clone + Default
+{
+    fn default_mock(&self) -> Mock<C, R> {
+        Mock::default()
+    }
+}
+
+#[doc(hidden)]
+pub trait NoDefaultReturn<C: Clone + Eq + Hash, R> {
+    fn default_mock(&self) -> Mock<C, R>;
+}
+
+impl<C: Clone + Eq + Hash, R> NoDefaultReturn<C, R> for &DefaultMock<C, R> {
+    fn default_mock(&self) -> Mock<C, R> {
+        Mock::unconfigured()
+    }
+}
+
+// Used by the builders generated by `mock_trait_no_default!` for methods which
+// were not set on the builder and have no inline return value. Works like
+// `DebugMock`: `BuilderDefaultReturn` is picked if the return type implements
+// `Default`. Otherwise, `BuilderNoDefaultReturn` panics when the mock is built.
+#[doc(hidden)]
+pub struct BuilderDefault<R>(pub PhantomData<R>);
+
+#[doc(hidden)]
+pub trait BuilderDefaultReturn<R> {
+    fn builder_default(&self, mock_name: &str, method: &str) -> R;
+}
+
+impl<R: Default> BuilderDefaultReturn<R> for BuilderDefault<R> {
+    fn builder_default(&self, _: &str, _: &str) -> R {
+        R::default()
+    }
+}
+
+#[doc(hidden)]
+pub trait BuilderNoDefaultReturn<R> {
+    fn builder_default(&self, mock_name: &str, method: &str) -> R;
+}
+
+impl<R> BuilderNoDefaultReturn<R> for &BuilderDefault<R> {
+    fn builder_default(&self, mock_name: &str, method: &str) -> R {
+        panic!(
+            "{}::builder(): no default return value provided for `{}`, whose return type \
+             doesn't implement `Default`",
+            mock_name,
+            method)
+    }
+}
+
+struct MockSummary {
+    num_calls: usize,
+}
+
+impl Debug for MockSummary {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Mock")
+            .field("num_calls", &self.num_calls)
+            .finish()
+    }
+}
+
 // Private macros. They need to be exported and made public so they can be used
 // in the actual public facing macros. Ideally these would be inaccessible to
 // clients, but since that's not possible, we at least make it explicit that
 // these are intended to be private by prepending the macro names with
 // "__private".
+#[macro_export]
+macro_rules! __private_mock_trait_bounds_impl {
+    ($mock_name:ident $([$($lt:lifetime),+])? $(: $bound:tt $(+ $more_bounds:tt)*)?) => (
+        // Fails to compile if the mock doesn't satisfy the bounds it was
+        // declared with, rather than where the mock is used.
+        const _: () = {
+            #[allow(dead_code)]
+            fn assert_bounds<T: $($bound $(+ $more_bounds)*)?>() {}
+
+            #[allow(dead_code)]
+            fn assert_mock_bounds<$($($lt),+)?>() {
+                assert_bounds::<$mock_name<$($($lt),+)?>>();
+            }
+        };
+    );
+}
+
 #[macro_export]
 macro_rules! __private_mock_trait_default_impl {
-    ($mock_name:ident $(, $method:ident)*) => (
-         impl Default for $mock_name {
-            fn default() -> Self {
-                Self {
-                    $( $method: double::Mock::default() ),*
+    ($mock_name:ident $([$($lt:lifetime),+])? $(, $method:ident: $retval:ty $(= $default:expr)?)*) => (
+        $crate::__private_paste! {
+            impl<$($($lt),+)?> Default for $mock_name<$($($lt),+)?> {
+                fn default() -> Self {
+                    Self {
+                        $( [<m_ $method>]: $crate::__private_mock_default!($retval $(, $default)?), )*
+                        $( __lifetimes: ::std::marker::PhantomData::<($(&$lt ()),+)>, )?
+                    }
+                }
+            }
+        }
+    );
+}
+
+#[macro_export]
+macro_rules! __private_mock_trait_accessors_impl {
+    ($mock_name:ident $([$($lt:lifetime),+])? $(, $method:ident($($arg_type:ty),*): $retval:ty)*) => (
+        $crate::__private_paste! {
+            impl<$($($lt),+)?> $mock_name<$($($lt),+)?> {
+                $(
+                    #[doc = concat!("Returns the `Mock` of the `", stringify!($method), "` method.")]
+                    #[allow(dead_code)]
+                    pub fn [<$method _mock>](&self) -> &double::Mock<(($($arg_type),*)), $retval> {
+                        &self.[<m_ $method>]
+                    }
+                )*
+            }
+        }
+    );
+}
+
+#[macro_export]
+macro_rules! __private_mock_trait_debug_impl {
+    ($mock_name:ident $([$($lt:lifetime),+])? $(, $method:ident)*) => (
+        $crate::__private_paste! {
+            impl<$($($lt),+)?> ::std::fmt::Debug for $mock_name<$($($lt),+)?> {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    #[allow(unused_imports)]
+                    use $crate::macros::{FullDebug, SummaryDebug};
+
+                    f.debug_struct(stringify!($mock_name))
+                        $(
+                            .field(
+                                stringify!($method),
+                                &(&$crate::macros::DebugMock(&self.[<m_ $method>])).debug_mock())
+                        )*
+                        .finish()
                 }
             }
         }
     );
 }
 
+#[macro_export]
+macro_rules! __private_mock_default {
+    ($retval:ty) => ( double::Mock::default() );
+    // The return type is specified so literals in `$default` are inferred to
+    // have the method's return type.
+    ($retval:ty, $default:expr) => ( double::Mock::new::<$retval>($default) );
+}
+
 #[macro_export]
 macro_rules! __private_mock_trait_new_impl {
-    ($mock_name:ident $(, $method:ident: $retval: ty)*) => (
-        impl $mock_name {
+    ($mock_name:ident $([$($lt:lifetime),+])? $(, $method:ident: $retval: ty)*) => (
+        $crate::__private_paste! {
+            impl<$($($lt),+)?> $mock_name<$($($lt),+)?> {
+                #[allow(dead_code)]
+                pub fn new( $($method: $retval),* ) -> Self {
+                    Self {
+                        $( [<m_ $method>]: double::Mock::new($method), )*
+                        $( __lifetimes: ::std::marker::PhantomData::<($(&$lt ()),+)>, )?
+                    }
+                }
+            }
+        }
+    );
+}
+
+#[macro_export]
+macro_rules! __private_mock_trait_deep_clone_impl {
+    ($mock_name:ident $([$($lt:lifetime),+])? $(, $method:ident)*) => (
+        $crate::__private_paste! {
+            impl<$($($lt),+)?> $mock_name<$($($lt),+)?> {
+                #[allow(dead_code)]
+                pub fn deep_clone(&self) -> Self {
+                    Self {
+                        $( [<m_ $method>]: self.[<m_ $method>].deep_clone(), )*
+                        $( __lifetimes: ::std::marker::PhantomData::<($(&$lt ()),+)>, )?
+                    }
+                }
+            }
+        }
+    );
+}
+
+#[macro_export]
+macro_rules! __private_mock_trait_calls_impl {
+    ($mock_name:ident $([$($lt:lifetime),+])? $(, $method:ident)*) => (
+        $crate::__private_paste! {
+            impl<$($($lt),+)?> $mock_name<$($($lt),+)?> {
+                /// Returns the number of calls made to all of the mock's methods.
+                #[allow(dead_code)]
+                pub fn total_calls(&self) -> usize {
+                    0 $( + self.[<m_ $method>].num_calls() )*
+                }
+
+                /// Clears the recorded calls of all of the mock's methods.
+                #[allow(dead_code)]
+                pub fn reset_all_calls(&self) {
+                    $( self.[<m_ $method>].reset_calls(); )*
+                }
+
+                /// Starts a new phase of a test by checkpointing the mocks of all
+                /// of the mock's methods. See `double::Mock::checkpoint`.
+                ///
+                /// The expected calls of every method are verified before any
+                /// method is checkpointed. Panics with the name of the first
+                /// method whose expected calls weren't made.
+                #[allow(dead_code)]
+                pub fn checkpoint(&self) {
+                    $(
+                        if let Err(mismatch) = self.[<m_ $method>].check_expectations() {
+                            panic!("{}: {}", stringify!($method), mismatch);
+                        }
+                    )*
+                    $( self.[<m_ $method>].checkpoint(); )*
+                }
+
+                /// Returns true if none of the mock's methods were called.
+                /// Otherwise, prints the name and number of calls of each method
+                /// that was called, as listed by `check_no_interactions`, and
+                /// returns false.
+                #[allow(dead_code)]
+                pub fn verify_no_interactions(&self) -> bool {
+                    match self.check_no_interactions() {
+                        Ok(()) => true,
+                        Err(mismatch) => {
+                            println!("{}", mismatch);
+                            false
+                        }
+                    }
+                }
+
+                /// Checks that none of the mock's methods were called. Otherwise,
+                /// the returned `CallMismatch` lists the name and number of calls
+                /// of each method that was called.
+                #[allow(dead_code)]
+                pub fn check_no_interactions(&self)
+                    -> ::std::result::Result<(), $crate::failure::CallMismatch>
+                {
+                    #[allow(unused_mut)]
+                    let mut called_methods: Vec<String> = Vec::new();
+                    $(
+                        if self.[<m_ $method>].called() {
+                            called_methods.push(format!(
+                                "{} ×{}", stringify!($method), self.[<m_ $method>].num_calls()));
+                        }
+                    )*
+                    if called_methods.is_empty() {
+                        Ok(())
+                    } else {
+                        Err($crate::failure::CallMismatch::new(
+                            "no_interactions",
+                            format!("no calls to {}", stringify!($mock_name)),
+                            called_methods))
+                    }
+                }
+            }
+        }
+    );
+}
+
+#[macro_export]
+macro_rules! __private_mock_trait_builder_impl {
+    ($mock_name:ident $(, $method:ident($($arg_type:ty),*): $retval:ty $(= $default:expr)?)*) => (
+        // The builder is defined in an anonymous scope, since `macro_rules`
+        // can't derive a unique name for it from `$mock_name`. The impls
+        // still apply outside the scope.
+        const _: () = {
+            pub struct Builder {
+                $( $method: Option<double::Mock<(($($arg_type),*)), $retval>> ),*
+            }
+
+            impl $mock_name {
+                #[allow(dead_code)]
+                pub fn builder() -> Builder {
+                    Builder {
+                        $( $method: None ),*
+                    }
+                }
+            }
+
             #[allow(dead_code)]
-            pub fn new( $($method: $retval),* ) -> Self {
-                Self {
-                    $( $method: double::Mock::new($method) ),*
+            impl Builder {
+                $(
+                    pub fn $method(mut self, return_value: $retval) -> Self {
+                        self.$method = Some(double::Mock::new::<$retval>(return_value));
+                        self
+                    }
+                )*
+
+                pub fn build(self) -> $mock_name {
+                    $crate::__private_paste! {
+                        $mock_name {
+                            $(
+                                [<m_ $method>]: self.$method.unwrap_or_else(|| {
+                                    double::Mock::new::<$retval>($crate::__private_mock_builder_default!(
+                                        $mock_name, $method, $retval $(, $default)?))
+                                })
+                            ),*
+                        }
+                    }
                 }
             }
+        };
+    );
+}
+
+#[macro_export]
+macro_rules! __private_mock_builder_default {
+    ($mock_name:ident, $method:ident, $retval:ty) => ({
+        #[allow(unused_imports)]
+        use $crate::macros::{BuilderDefaultReturn, BuilderNoDefaultReturn};
+
+        (&$crate::macros::BuilderDefault::<$retval>(::std::marker::PhantomData))
+            .builder_default(stringify!($mock_name), stringify!($method))
+    });
+    ($mock_name:ident, $method:ident, $retval:ty, $default:expr) => ( $default );
+}
+
+// Panics with the location of the `expect_` macro and the stringified mock if
+// `$check` returned a `CallMismatch`.
+#[macro_export]
+macro_rules! __private_expect {
+    ($macro_name:ident, $mock:expr, $check:expr) => (
+        if let Err(mismatch) = $check {
+            panic!(
+                "{}!({}) failed at {}:{}\n{}",
+                stringify!($macro_name),
+                stringify!($mock),
+                file!(),
+                line!(),
+                mismatch);
         }
     );
 }
 
+// Converts an argument of a method with type parameters into the type its
+// mock stores. Arguments annotated with `as $store_type` are converted using
+// `FromGenericArg`, and other arguments like those of other methods.
+#[macro_export]
+macro_rules! __private_store_arg {
+    ($arg_name:ident) => (
+        $crate::macros::IntoOwnedArg::into_owned_arg($arg_name)
+    );
+    ($arg_name:ident as $store_type:ty) => (
+        <$store_type as $crate::macros::FromGenericArg<_>>::from_generic_arg($arg_name)
+    );
+}
+
+// The `Mock` field that `mock_method!` passes the arguments of `$method` to.
+#[macro_export]
+macro_rules! __private_mock_field {
+    ($self:ident, $method:ident) => (
+        $crate::__private_paste!($self.[<m_ $method>])
+    );
+}
+
+// The type of the arguments `mock_method!` passes to the mock of `$method`.
+#[macro_export]
+macro_rules! __private_mock_args {
+    ($method:ident) => (
+        <Self as $crate::macros::MockMethodArgs<
+            { $crate::macros::method_id(stringify!($method)) }>>::Args
+    );
+}
+
 
 include!(concat!(env!("OUT_DIR"), "/macros_generated.rs"));
 
@@ -47,8 +779,10 @@ include!(concat!(env!("OUT_DIR"), "/macros_generated.rs"));
 /// each mocked method using `new()`.
 ///
 /// The `struct` has a field for each method of the `trait`, which manages
-/// their respective method's behaviour and call expectations. For example, if
-/// one defines a mock like so:
+/// their respective method's behaviour and call expectations. Each field is
+/// named after its method with an `m_` prefix, and is returned by an accessor
+/// named after the method with a `_mock` suffix. For example, if one defines
+/// a mock like so:
 ///
 /// ```
 /// # #[macro_use] extern crate double;
@@ -67,27 +801,90 @@ include!(concat!(env!("OUT_DIR"), "/macros_generated.rs"));
 /// Then the following code is generated:
 ///
 /// ```
-/// #[derive(Debug, Clone)]
+/// #[derive(Clone)]
 /// struct MockTaskManager {
-///     max_threads: double::Mock<(), u32>,
-///     set_max_threads: double::Mock<(u32), ()>,
+///     m_max_threads: double::Mock<(), u32>,
+///     m_set_max_threads: double::Mock<(u32), ()>,
+/// }
+///
+/// impl MockTaskManager {
+///     pub fn max_threads_mock(&self) -> &double::Mock<(), u32> {
+///         &self.m_max_threads
+///     }
+///
+///     pub fn set_max_threads_mock(&self) -> &double::Mock<(u32), ()> {
+///         &self.m_set_max_threads
+///     }
 /// }
 ///
 /// impl Default for MockTaskManager {
 ///     fn default() -> Self {
 ///         MockTaskManager {
-///             max_threads: double::Mock::default(),
-///             set_max_threads: double::Mock::default(),
+///             m_max_threads: double::Mock::default(),
+///             m_set_max_threads: double::Mock::default(),
 ///         }
 ///     }
 /// }
 /// ```
 ///
+/// Like `Mock`, cloning the generated `struct` produces a mock that shares
+/// its configured behaviour and recorded calls with the original. Use the
+/// generated `deep_clone()` method to create an independent copy instead,
+/// which calls `Mock::deep_clone` on every field.
+///
+/// `Debug` is implemented manually, so the mocked methods' argument and
+/// return types don't need to implement `Debug`. Methods whose types do are
+/// formatted using `Mock`'s `Debug` impl. Other methods are summarised by
+/// their number of calls, e.g. `login: Mock { num_calls: 1 }`.
+///
+/// Methods returning `Self` are declared with `-> Self`. Their fields are
+/// `double::Mock`s with a `()` return type. See `mock_method!`.
+///
+/// An `enum` with a variant for each mocked method is generated too, named
+/// after the mock with a `Method` suffix. The generated `interactions()`
+/// method returns every call made to the mock's methods, in the order they
+/// were made, as the method's variant and its arguments formatted using
+/// `Debug`. Arguments that don't implement `Debug` are formatted as `..`.
+/// `interaction_summary()` lists the same calls with one call per line,
+/// which is useful in assertion messages.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+///
+/// mock_trait!(
+///     MockTaskManager,
+///     max_threads(()) -> u32,
+///     set_max_threads(u32) -> ()
+/// );
+///
+/// # fn main() {
+/// let mock = MockTaskManager::default();
+/// mock.set_max_threads_mock().call(4);
+/// mock.max_threads_mock().call(());
+///
+/// assert_eq!(
+///     mock.interactions(),
+///     vec!(
+///         (MockTaskManagerMethod::SetMaxThreads, "4".to_owned()),
+///         (MockTaskManagerMethod::MaxThreads, "()".to_owned())));
+/// assert_eq!(
+///     mock.interaction_summary(),
+///     "2 interactions\n  0: set_max_threads: 4\n  1: max_threads: ()");
+/// # }
+/// ```
+///
 /// Note that just defining this macro is not enough. This macro is used to
 /// generate the necessary boilerplate, but the generated struct *does not*
 /// implement the desired `trait`. To do that, use `double`'s `mock_method`
 /// macro.
 ///
+/// The `m_` prefix and `_mock` suffix keep the generated fields and accessors
+/// from colliding with the mock's other methods, such as `new()` and
+/// `default()`, if a mocked method has the same name. Calling such a method
+/// on the mock can still be ambiguous, in which case the trait must be named
+/// explicitly (e.g. `<MockFactory as Default>::default()` or
+/// `Factory::clone(&mock)`).
+///
 /// # Examples
 ///
 /// ```
@@ -106,36 +903,173 @@ include!(concat!(env!("OUT_DIR"), "/macros_generated.rs"));
 ///
 /// # fn main() {
 /// let mock = MockTaskManager::default();
-/// mock.max_threads.return_value(42u32);
-/// assert_eq!(42, mock.max_threads.call(()));
-/// mock.set_max_threads.call(9001u32);
-/// assert!(mock.set_max_threads.called_with(9001u32));
+/// mock.max_threads_mock().return_value(42u32);
+/// assert_eq!(42, mock.max_threads_mock().call(()));
+/// mock.set_max_threads_mock().call(9001u32);
+/// assert!(mock.set_max_threads_mock().called_with(9001u32));
+/// # }
+/// ```
+///
+/// The return value used by `default()` can be specified inline for any
+/// method by appending `= value` to the method's declaration. Methods without
+/// an inline value return `R::default()`.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+///
+/// mock_trait!(
+///     MockTaskManager,
+///     max_threads(()) -> u32 = 8,
+///     set_max_threads(u32) -> ()
+/// );
+///
+/// # fn main() {
+/// let mock = MockTaskManager::default();
+/// assert_eq!(8, mock.max_threads_mock().call(()));
+/// # }
+/// ```
+///
+/// The generated `struct` and its fields are private by default. Prefix the
+/// mock's name with a visibility, such as `pub` or `pub(crate)`, to configure
+/// and verify the mock from other modules.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+///
+/// mod test_utils {
+///     mock_trait!(
+///         pub(crate) MockTaskManager,
+///         max_threads(()) -> u32
+///     );
+/// }
+///
+/// # fn main() {
+/// let mock = test_utils::MockTaskManager::default();
+/// mock.max_threads_mock().return_value(4u32);
+/// assert_eq!(4, mock.max_threads_mock().call(()));
+/// # }
+/// ```
+///
+/// Lifetime parameters for the generated `struct` can be listed in square
+/// brackets after its name. This allows mocking traits which have lifetime
+/// parameters, including ones whose methods take or return references with
+/// those lifetimes.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+///
+/// trait Store<'a> {
+///     fn put(&self, key: &'a str);
+///     fn keys(&self) -> Box<dyn Iterator<Item = &'a str> + 'a>;
+/// }
+///
+/// mock_trait!(
+///     MockStore['a],
+///     put(&'a str) -> (),
+///     keys(()) -> Vec<&'a str>
+/// );
+///
+/// impl<'a> Store<'a> for MockStore<'a> {
+///     mock_method!(put(&self, key: &'a str));
+///     // iterators can't be stored, so a new one is created on each call
+///     mock_method!(keys(&self) -> Box<dyn Iterator<Item = &'a str> + 'a>, self, {
+///         Box::new(self.keys_mock().call(()).into_iter())
+///     });
+/// }
+///
+/// # fn main() {
+/// let mock = MockStore::default();
+/// mock.keys_mock().return_value(vec!("a", "b"));
+/// mock.put("c");
+/// assert_eq!(vec!("a", "b"), mock.keys().collect::<Vec<&str>>());
+/// assert!(mock.put_mock().called_with("c"));
+/// # }
+/// ```
+///
+/// Traits with supertraits are often used as bounds on generic code. The
+/// bounds the mock has to satisfy can be listed after its name, so the mock
+/// fails to compile where it's defined, rather than where it's used, if it
+/// doesn't satisfy them. Each bound must be a single name or lifetime, such
+/// as `Clone` or `'static`.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+/// use std::fmt::Debug;
+///
+/// trait Dependency: Clone + Debug + 'static {
+///     fn greet(&self, name: String);
+/// }
+///
+/// mock_trait!(
+///     MockDependency: Clone + Debug + 'static,
+///     greet(String) -> ()
+/// );
+///
+/// impl Dependency for MockDependency {
+///     mock_method!(greet(&self, name: String));
+/// }
+///
+/// fn greet_everyone<T: Dependency>(dependency: T, names: &[&str]) {
+///     for name in names {
+///         dependency.greet(name.to_string());
+///     }
+/// }
+///
+/// # fn main() {
+/// let mock = MockDependency::default();
+/// greet_everyone(mock.clone(), &["Fido", "Spot"]);
+/// assert_eq!(2, mock.greet_mock().num_calls());
 /// # }
 /// ```
+///
+/// Mocks are only `Send` and `Sync` if the `sync-mocks` feature is enabled,
+/// so listing either bound requires it. See the `send` example.
 #[macro_export]
 macro_rules! mock_trait {
-    ($mock_name:ident $(, $method:ident($($arg_type:ty),* ) -> $retval:ty )* ) => (
-        #[derive(Debug, Clone)]
-        struct $mock_name {
-            $(
-                $method: double::Mock<(($($arg_type),*)), $retval>
-            ),*
-        }
-
-        $crate::__private_mock_trait_new_impl!($mock_name $(, $method: $retval)*);
-        $crate::__private_mock_trait_default_impl!($mock_name $(, $method)*);
+    // Methods returning `Self` are rewritten to return `()`, since the mock
+    // can't store values of its own type. The rewritten methods are collected
+    // in the second pair of brackets.
+    (@methods [$($header:tt)*] [$($done:tt)*]) => (
+        $crate::mock_trait!(@impl $($header)* $($done)*);
+    );
+    (@methods [$($header:tt)*] [$($done:tt)*]
+        , $method:ident($($arg_type:ty),* ) -> Self $(, $($rest:tt)*)?) => (
+        $crate::mock_trait!(@methods [$($header)*] [$($done)* , $method($($arg_type),*) -> ()]
+            $(, $($rest)*)?);
+    );
+    (@methods [$($header:tt)*] [$($done:tt)*]
+        , $method:ident($($arg_type:ty),* ) -> $retval:ty $(= $default:expr)? $(, $($rest:tt)*)?) => (
+        $crate::mock_trait!(@methods [$($header)*] [$($done)* , $method($($arg_type),*) -> $retval $(= $default)?]
+            $(, $($rest)*)?);
     );
 
-    (pub $mock_name:ident $(, $method:ident($($arg_type:ty),* ) -> $retval:ty )* ) => (
-        #[derive(Debug, Clone)]
-        pub struct $mock_name {
-            $(
-                $method: double::Mock<(($($arg_type),*)), $retval>
-            ),*
+    (@impl $vis:vis $mock_name:ident $([$($lt:lifetime),+])? $(: $bound:tt $(+ $more_bounds:tt)*)? $(, $method:ident($($arg_type:ty),* ) -> $retval:ty $(= $default:expr)? )* ) => (
+        $crate::__private_paste! {
+            #[derive(Clone)]
+            $vis struct $mock_name<$($($lt),+)?> {
+                $(
+                    $vis [<m_ $method>]: double::Mock<(($($arg_type),*)), $retval>,
+                )*
+                $( __lifetimes: ::std::marker::PhantomData<($(&$lt ()),+)>, )?
+            }
         }
 
-        $crate::__private_mock_trait_new_impl!($mock_name $(, $method: $retval)*);
-        $crate::__private_mock_trait_default_impl!($mock_name $(, $method)*);
+        $crate::__private_mock_trait_accessors_impl!(
+            $mock_name $([$($lt),+])? $(, $method($($arg_type),*): $retval)*);
+        $crate::__private_mock_trait_debug_impl!($mock_name $([$($lt),+])? $(, $method)*);
+        $crate::__private_mock_trait_new_impl!($mock_name $([$($lt),+])? $(, $method: $retval)*);
+        $crate::__private_mock_trait_deep_clone_impl!($mock_name $([$($lt),+])? $(, $method)*);
+        $crate::__private_mock_trait_calls_impl!($mock_name $([$($lt),+])? $(, $method)*);
+        $crate::__private_mock_trait_interactions_impl!($vis $mock_name $([$($lt),+])? $(, $method)*);
+        $crate::mock_method_args!($mock_name $([$($lt),+])? $(, $method($($arg_type),*))*);
+        $crate::__private_mock_trait_default_impl!(
+            $mock_name $([$($lt),+])? $(, $method: $retval $(= $default)?)*);
+        $crate::__private_mock_trait_bounds_impl!($mock_name $([$($lt),+])? $(: $bound $(+ $more_bounds)*)?);
+    );
+
+    ($vis:vis $mock_name:ident $([$($lt:lifetime),+])? $(: $bound:tt $(+ $more_bounds:tt)*)? $(, $($methods:tt)*)?) => (
+        $crate::mock_trait!(@methods [$vis $mock_name $([$($lt),+])? $(: $bound $(+ $more_bounds)*)?] []
+            $(, $($methods)*)?);
     );
 }
 
@@ -148,11 +1082,15 @@ macro_rules! mock_trait {
 ///
 /// This macro generates a `struct` that implements the traits `Clone` and
 /// and `Debug`. Create instances of the mock object by calling `new()`,
-/// passing in the return values for each mocked method using `new()`.
+/// passing in the return values for each mocked method using `new()`. As with
+/// `mock_trait!`, `Debug` is implemented even if the mocked methods' types
+/// don't implement `Debug`.
 ///
 /// The `struct` has a field for each method of the `trait`, which manages
-/// their respective method's behaviour and call expectations. For example, if
-/// one defines a mock like so:
+/// their respective method's behaviour and call expectations. Each field is
+/// named after its method with an `m_` prefix, and is returned by an accessor
+/// named after the method with a `_mock` suffix. For example, if one defines
+/// a mock like so:
 //
 /// ```
 /// # #[macro_use] extern crate double;
@@ -172,19 +1110,27 @@ macro_rules! mock_trait {
 /// Then the following code is generated:
 ///
 /// ```
-/// #[derive(Debug, Clone)]
+/// #[derive(Clone)]
 /// struct MockTaskManager {
-///     max_threads: double::Mock<(), Result<u32, String>>,
-///     set_max_threads: double::Mock<(u32), ()>,
+///     m_max_threads: double::Mock<(), Result<u32, String>>,
+///     m_set_max_threads: double::Mock<(u32), ()>,
 /// }
 ///
 /// impl MockTaskManager {
 ///     pub fn new(max_threads: Result<u32, String>, set_max_threads: ()) -> Self {
 ///         MockTaskManager {
-///             max_threads: double::Mock::new(max_threads),
-///             set_max_threads: double::Mock::new(set_max_threads),
+///             m_max_threads: double::Mock::new(max_threads),
+///             m_set_max_threads: double::Mock::new(set_max_threads),
 ///         }
 ///     }
+///
+///     pub fn max_threads_mock(&self) -> &double::Mock<(), Result<u32, String>> {
+///         &self.m_max_threads
+///     }
+///
+///     pub fn set_max_threads_mock(&self) -> &double::Mock<(u32), ()> {
+///         &self.m_set_max_threads
+///     }
 /// }
 /// ```
 ///
@@ -193,6 +1139,9 @@ macro_rules! mock_trait {
 /// implement the desired `trait`. To do that, use `double`'s `mock_method`
 /// macro.
 ///
+/// As with `mock_trait!`, bounds the mock has to satisfy, such as
+/// `Clone + 'static`, can be listed after its name.
+///
 /// # Examples
 ///
 /// ```
@@ -211,33 +1160,158 @@ macro_rules! mock_trait {
 ///
 /// # fn main() {
 /// let mock = MockTaskManager::new(Ok(42), ());
-/// assert_eq!(Ok(42), mock.max_threads.call(()));
-/// mock.set_max_threads.call(9001u32);
-/// assert!(mock.set_max_threads.called_with(9001u32));
+/// assert_eq!(Ok(42), mock.max_threads_mock().call(()));
+/// mock.set_max_threads_mock().call(9001u32);
+/// assert!(mock.set_max_threads_mock().called_with(9001u32));
+/// # }
+/// ```
+///
+/// Passing return values to `new()` positionally makes it easy to mix up
+/// methods with the same return type. Instead, the return value of a method
+/// can be specified inline by appending `= value` to its declaration. If every
+/// method has an inline return value, the generated `struct` also implements
+/// `Default`, which uses the inline values. Otherwise, the inline values are
+/// used by the builder described below.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+///
+/// mock_trait_no_default!(
+///     MockTaskManager,
+///     max_threads(()) -> Result<u32, String> = Ok(8),
+///     min_threads(()) -> Result<u32, String> = Err("unknown".to_owned()),
+///     set_max_threads(u32) -> () = ()
+/// );
+///
+/// # fn main() {
+/// let mock = MockTaskManager::default();
+/// assert_eq!(Ok(8), mock.max_threads_mock().call(()));
+/// assert_eq!(Err("unknown".to_owned()), mock.min_threads_mock().call(()));
+/// # }
+/// ```
+///
+/// Alternatively, the generated `struct` provides a `builder()`, which has a
+/// method for setting the default return value of each mocked method by
+/// name. `build()` constructs the mock, using the inline return value of any
+/// method that was not set on the builder, or the `Default` value of its
+/// return type. It panics if a method has none of these, so only the methods
+/// a test uses need to be set.
+///
+/// The builder has an `Option<double::Mock<..>>` field for each method, which
+/// is `None` until the method's return value is set on the builder. For
+/// `MockUserStore` below, the macro generates:
+///
+/// ```
+/// # struct MockUserStore;
+/// pub struct Builder {
+///     get_user: Option<double::Mock<(i32), Result<String, String>>>,
+///     user_count: Option<double::Mock<(()), u32>>,
+///     delete_user: Option<double::Mock<(i32), Result<(), String>>>,
+/// }
+///
+/// impl MockUserStore {
+///     pub fn builder() -> Builder {
+///         Builder { get_user: None, user_count: None, delete_user: None }
+///     }
+/// }
+/// ```
+///
+/// `Builder` has a method named after each mocked method, which sets its
+/// field to a `double::Mock` returning the given value, and `build()`, which
+/// moves the set mocks into the mock object and creates the rest from their
+/// fallback return values. `Builder` is declared in an anonymous scope, so it
+/// can't be named, but its methods can still be called.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+///
+/// mock_trait_no_default!(
+///     MockUserStore,
+///     get_user(i32) -> Result<String, String>,
+///     user_count(()) -> u32,
+///     delete_user(i32) -> Result<(), String>
+/// );
+///
+/// # fn main() {
+/// // `user_count` returns `u32::default()`, since it wasn't set
+/// let mock = MockUserStore::builder()
+///     .get_user(Err("no such user".to_owned()))
+///     .delete_user(Ok(()))
+///     .build();
+/// assert_eq!(Err("no such user".to_owned()), mock.get_user_mock().call(42));
+/// assert_eq!(0, mock.user_count_mock().call(()));
+/// assert_eq!(Ok(()), mock.delete_user_mock().call(42));
+/// # }
+/// ```
+///
+/// ```should_panic
+/// # #[macro_use] extern crate double;
+///
+/// mock_trait_no_default!(
+///     MockUserStore,
+///     get_user(i32) -> Result<String, String>,
+///     user_count(()) -> u32,
+///     delete_user(i32) -> Result<(), String>
+/// );
+///
+/// # fn main() {
+/// // panics, since no default return value was provided for `delete_user`
+/// // and `Result` doesn't implement `Default`
+/// let mock = MockUserStore::builder()
+///     .get_user(Err("no such user".to_owned()))
+///     .build();
 /// # }
 /// ```
 #[macro_export]
 macro_rules! mock_trait_no_default {
-    ($mock_name:ident $(, $method:ident($($arg_type:ty),* ) -> $retval:ty )* ) => (
-        #[derive(Debug, Clone)]
-        struct $mock_name {
-            $(
-                $method: double::Mock<(($($arg_type),*)), $retval>
-            ),*
+    // every method has an inline default return value, so `Default` can be
+    // implemented
+    ($vis:vis $mock_name:ident $(: $bound:tt $(+ $more_bounds:tt)*)? $(, $method:ident($($arg_type:ty),* ) -> $retval:ty = $default:expr )* ) => (
+        $crate::__private_paste! {
+            #[derive(Clone)]
+            $vis struct $mock_name {
+                $(
+                    $vis [<m_ $method>]: double::Mock<(($($arg_type),*)), $retval>
+                ),*
+            }
         }
 
+        $crate::__private_mock_trait_accessors_impl!(
+            $mock_name $(, $method($($arg_type),*): $retval)*);
+        $crate::__private_mock_trait_debug_impl!($mock_name $(, $method)*);
         $crate::__private_mock_trait_new_impl!($mock_name $(, $method: $retval)*);
+        $crate::__private_mock_trait_deep_clone_impl!($mock_name $(, $method)*);
+        $crate::__private_mock_trait_calls_impl!($mock_name $(, $method)*);
+        $crate::__private_mock_trait_interactions_impl!($vis $mock_name $(, $method)*);
+        $crate::mock_method_args!($mock_name $(, $method($($arg_type),*))*);
+        $crate::__private_mock_trait_default_impl!($mock_name $(, $method: $retval = $default)*);
+        $crate::__private_mock_trait_builder_impl!(
+            $mock_name $(, $method($($arg_type),*): $retval = $default)*);
+        $crate::__private_mock_trait_bounds_impl!($mock_name $(: $bound $(+ $more_bounds)*)?);
     );
 
-    (pub $mock_name:ident $(, $method:ident($($arg_type:ty),* ) -> $retval:ty )* ) => (
-        #[derive(Debug, Clone)]
-        pub struct $mock_name {
-            $(
-                $method: double::Mock<(($($arg_type),*)), $retval>
-            ),*
+    // only some methods have an inline default return value
+    ($vis:vis $mock_name:ident $(: $bound:tt $(+ $more_bounds:tt)*)? $(, $method:ident($($arg_type:ty),* ) -> $retval:ty $(= $default:expr)? )* ) => (
+        $crate::__private_paste! {
+            #[derive(Clone)]
+            $vis struct $mock_name {
+                $(
+                    $vis [<m_ $method>]: double::Mock<(($($arg_type),*)), $retval>
+                ),*
+            }
         }
 
+        $crate::__private_mock_trait_accessors_impl!(
+            $mock_name $(, $method($($arg_type),*): $retval)*);
+        $crate::__private_mock_trait_debug_impl!($mock_name $(, $method)*);
         $crate::__private_mock_trait_new_impl!($mock_name $(, $method: $retval)*);
+        $crate::__private_mock_trait_deep_clone_impl!($mock_name $(, $method)*);
+        $crate::__private_mock_trait_calls_impl!($mock_name $(, $method)*);
+        $crate::__private_mock_trait_interactions_impl!($vis $mock_name $(, $method)*);
+        $crate::mock_method_args!($mock_name $(, $method($($arg_type),*))*);
+        $crate::__private_mock_trait_builder_impl!(
+            $mock_name $(, $method($($arg_type),*): $retval $(= $default)?)*);
+        $crate::__private_mock_trait_bounds_impl!($mock_name $(: $bound $(+ $more_bounds)*)?);
     );
 }
 
@@ -270,14 +1344,17 @@ macro_rules! mock_trait_no_default {
 ///
 /// # fn main() {
 /// let mut mock = MockTaskManager::default();
-/// mock.max_threads.return_value(42u32);
+/// mock.max_threads_mock().return_value(42u32);
 /// assert_eq!(42, mock.max_threads());
-/// assert!(mock.max_threads.called_with(()));
+/// assert!(mock.max_threads_mock().called_with(()));
 /// mock.set_max_threads(9001u32);
-/// assert!(mock.set_max_threads.called_with(9001u32));
+/// assert!(mock.set_max_threads_mock().called_with(9001u32));
 /// # }
 /// ```
 ///
+/// The generated method passes its arguments to the `Mock` in the field
+/// named after the method with an `m_` prefix, e.g. `self.m_max_threads`.
+///
 /// There are many different variants of `mock_method`. In total there are 12
 /// variants. 8 variants provides a combination of the following:
 ///
@@ -325,7 +1402,7 @@ macro_rules! mock_trait_no_default {
 ///     mock_method!(write(&mut self, text: &str), self, {
 ///         // manually convert the reference to an owned `String` before
 ///         // passing it to the underlying mock object
-///         self.write.call(text.to_owned())
+///         self.write_mock().call(text.to_owned())
 ///     });
 /// }
 /// # fn main() {
@@ -341,84 +1418,482 @@ macro_rules! mock_trait_no_default {
 /// The name of the underlying mock object is always the same as the mocked
 /// method's name.
 ///
-/// `&str` parameters are common. It can be inconvenient haven't to manually
-/// specify the body each time they appear. There are plans to add a macro to
-/// generate a body that calls `to_owned()` automatically.
-/// (TODO: implement the macro)
+/// `&str` parameters are common, so auto-generated bodies already convert
+/// `&str`, `&[T]` and `&[&str]` arguments to `String`, `Vec<T>` and
+/// `Vec<String>` respectively, as well as `&Path`, `&OsStr` and `Cow`
+/// arguments to their owned types (see `IntoOwnedArg`). A custom body is only
+/// needed for other conversions, so the `write` mock above could also use
+/// `mock_method!(write(&mut self, text: &str))`.
+///
+/// ### Type Parameters
+///
+/// There are an additional 8 variants to handle method type parameters
+/// (e.g. `fn foo<T: Eq>(&self, a: &T)`). These variants allow one to generate
+/// mock methods which take some generic type parameters.
+///
+/// For example, suppose one had a `Comparator` trait that was responsible for
+/// comparing any two values in the program. It might look something like this:
+///
+/// ```
+/// trait Comparator {
+///    fn is_equal<T: Eq>(&self, a: &T, b: &T) -> bool;
+/// }
+/// ```
+///
+/// `T` can be multiple types. Currently, we cannot store call arguments that
+/// have generic types in the underlying `Mock` objects. Therefore, one has to
+/// convert the generic types to a different, common representation. One way
+/// to get around this limitation is converting each generic type to a `String`.
+/// e.g. for the `Comparator` trait:
+///
+/// ```
+/// # #[macro_use] extern crate double;
+///
+/// use std::string::ToString;
+///
+/// trait Comparator {
+///    fn is_equal<T: Eq + ToString>(&self, a: &T, b: &T) -> bool;
+/// }
+///
+/// mock_trait!(
+///     MockComparator,
+///     // store all passed in call args as strings
+///     is_equal((String, String)) -> bool
+/// );
+///
+/// impl Comparator for MockComparator {
+///     mock_method!(is_equal<(T: Eq + ToString)>(&self, a: &T, b: &T) -> bool, self, {
+///         // Convert both arguments to strings and manually pass to underlying
+///         // mock object.
+///         // Notice how the both arguments as passed as a single tuple. The
+///         // underlying mock object always expects a single tuple.
+///         self.is_equal_mock().call((a.to_string(), b.to_string()))
+///     });
+/// }
+/// # fn main() {
+///     // only here to make `cargo test` happy
+/// }
+/// ```
+///
+/// If the `to_string` conversions for all `T` are not lossy, then our mock
+/// expectations can be exact. If the `to_string` conversions _are_ lossy, then
+/// this mechanism can still be used, providing all the properties of the passed
+/// in objects are captured in the resultant `String`s.
+///
+/// This approach requires the writer to ensure the code under test adds the
+/// `ToString` trait to the `trait`'s type argument constraints. This limitation
+/// forces test writers to modify production code to use `double` for mocking.
+///
+/// Despite this, there is still value in using `double` for mocking generic
+/// methods with type arguments. Despite adding boilerplate to production code
+/// and manually implementing mock method bodies being cumbersome, the value add
+/// is that all argument matching, expectations, calling test functions, etc.
+/// are all still handled by `double`. Arguably, reimplenting those features is
+/// more cumbersome than the small amount of boilerplate required to mock
+/// methods with type arguments.
+///
+/// No custom body is needed if each generic argument only has to be converted
+/// to an owned type, e.g. `P: AsRef<Path>` to `PathBuf`. Annotate those
+/// arguments with `as` followed by the type the mock stores. The generated
+/// body converts them using `FromGenericArg`, and the other arguments as usual:
+///
+/// ```
+/// # #[macro_use] extern crate double;
+///
+/// use std::path::{Path, PathBuf};
+///
+/// trait Store {
+///    fn put<P: AsRef<Path>, S: Into<String>>(&self, path: P, value: S, ttl: u32) -> bool;
+/// }
+///
+/// mock_trait!(
+///     MockStore,
+///     put(PathBuf, String, u32) -> bool
+/// );
+///
+/// impl Store for MockStore {
+///     mock_method!(put<(P: AsRef<Path>, S: Into<String>)>(
+///         &self, path: P as PathBuf, value: S as String, ttl: u32) -> bool);
+/// }
+/// # fn main() {
+/// #     let store = MockStore::default();
+/// #     store.put("a/b", "value", 10);
+/// #     assert!(store.put_mock().called_with((PathBuf::from("a/b"), "value".to_owned(), 10)));
+/// # }
+/// ```
+///
+/// The same approach can be used for methods whose return type depends on a
+/// type parameter. The underlying mock returns a common representation of the
+/// value, which the custom body converts into the type the caller asked for.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+///
+/// use std::str::FromStr;
+///
+/// trait Config {
+///    fn get<T: FromStr>(&self, key: &str) -> Result<T, T::Err>;
+/// }
+///
+/// mock_trait!(
+///     MockConfig,
+///     // store the raw text of each config value
+///     get(String) -> String
+/// );
+///
+/// impl Config for MockConfig {
+///     mock_method!(get<(T: FromStr)>(&self, key: &str) -> Result<T, T::Err>, self, {
+///         self.get_mock().call(key.to_owned()).parse()
+///     });
+/// }
+/// # fn main() {
+/// #     let config = MockConfig::default();
+/// #     config.get_mock().return_value_for("threads".to_owned(), "8");
+/// #     assert_eq!(Ok(8u32), config.get("threads"));
+/// #     assert!(config.get::<bool>("verbose").is_err());
+/// # }
+/// ```
+///
+/// ### Where Clauses and `impl Trait` Return Values
+///
+/// Methods with a custom body can have a `where` clause, which is passed in
+/// parentheses after the return type (or after the arguments, if the method
+/// doesn't return a value). Methods returning `impl Trait` need a custom body
+/// that builds the returned value from data stored in the underlying mock.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+/// use std::fmt::Display;
+///
+/// trait Reporter {
+///     fn report<T>(&mut self, value: T) where T: Display;
+///     fn history(&self) -> impl Iterator<Item = u32>;
+/// }
+///
+/// mock_trait!(
+///     MockReporter,
+///     report(String) -> (),
+///     history(()) -> Vec<u32>);
+///
+/// impl Reporter for MockReporter {
+///     mock_method!(report<(T)>(&mut self, value: T) where (T: Display), self, {
+///         self.report_mock().call(value.to_string())
+///     });
+///     mock_method!(history(&self) -> impl Iterator<Item = u32>, self, {
+///         self.history_mock().call(()).into_iter()
+///     });
+/// }
+/// # fn main() {
+/// #     let mut mock = MockReporter::default();
+/// #     mock.history_mock().return_value(vec!(4, 2));
+/// #     mock.report(mock.history().sum::<u32>());
+/// #     assert!(mock.report_mock().called_with("6".to_owned()));
+/// # }
+/// ```
+///
+/// ### Out-Parameters
+///
+/// Methods that write to an `&mut` argument can be mocked using
+/// `Mock::call_with_effect`. The underlying mock records a copy of the
+/// argument, and an effect modifies the caller's argument using the mock's
+/// return value. To configure what is written to the argument in each test,
+/// the underlying mock can return it, with a custom body converting it into
+/// the method's return value:
+///
+/// ```
+/// # #[macro_use] extern crate double;
+///
+/// trait Reader {
+///     // Appends bytes to `buf` and returns how many were appended.
+///     fn read_into(&self, buf: &mut Vec<u8>) -> usize;
+/// }
+///
+/// mock_trait!(
+///     MockReader,
+///     // returns the bytes to append
+///     read_into(Vec<u8>) -> Vec<u8>);
+///
+/// impl Reader for MockReader {
+///     mock_method!(read_into(&self, buf: &mut Vec<u8>) -> usize, self, {
+///         self.read_into_mock().call_with_effect(
+///             buf.clone(),
+///             buf,
+///             &|buf: &mut Vec<u8>, bytes: &Vec<u8>| buf.extend_from_slice(bytes)
+///         ).len()
+///     });
+/// }
+/// # fn main() {
+/// #     let mock = MockReader::default();
+/// #     mock.read_into_mock().return_value(vec!(4, 2));
+/// #     let mut buf = vec!(1);
+/// #     assert_eq!(2, mock.read_into(&mut buf));
+/// #     assert_eq!(vec!(1, 4, 2), buf);
+/// #     assert!(mock.read_into_mock().called_with(vec!(1)));
+/// # }
+/// ```
+///
+/// If the mock returns the method's return value, the body can be generated
+/// by passing the name of the out-parameter and the effect after the
+/// signature. Arguments are recorded as described above, with `&mut T`
+/// arguments recorded as `T`:
+///
+/// ```
+/// # #[macro_use] extern crate double;
+///
+/// trait Reader {
+///     fn read_zeroes(&mut self, id: u32, buf: &mut Vec<u8>) -> usize;
+/// }
+///
+/// mock_trait!(
+///     MockReader,
+///     read_zeroes(u32, Vec<u8>) -> usize);
+///
+/// impl Reader for MockReader {
+///     mock_method!(read_zeroes(&mut self, id: u32, buf: &mut Vec<u8>) -> usize,
+///         effect(buf, |buf: &mut Vec<u8>, len: &usize| buf.resize(*len, 0)));
+/// }
+/// # fn main() {
+/// #     let mut mock = MockReader::default();
+/// #     mock.read_zeroes_mock().return_value(3usize);
+/// #     let mut buf = vec!();
+/// #     assert_eq!(3, mock.read_zeroes(7, &mut buf));
+/// #     assert_eq!(vec!(0, 0, 0), buf);
+/// #     assert!(mock.read_zeroes_mock().called_with((7, vec!())));
+/// # }
+/// ```
+///
+/// ### Callback Arguments
+///
+/// Arguments that can't be recorded, such as callbacks, can be listed after
+/// the recorded arguments, separated by a `;`. These *live* arguments are
+/// passed to the handler configured using `Mock::use_live_handler` instead,
+/// which can invoke callbacks with canned data. Live arguments must be
+/// owned, and are passed to the handler as a tuple if there are several.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// trait Broker {
+///     fn subscribe(&self, topic: &str, on_msg: Box<dyn Fn(String)>) -> bool;
+/// }
+///
+/// mock_trait!(
+///     MockBroker,
+///     subscribe(String) -> bool);
+///
+/// impl Broker for MockBroker {
+///     mock_method!(subscribe(&self, topic: &str; on_msg: Box<dyn Fn(String)>) -> bool);
+/// }
+///
+/// # fn main() {
+/// let mock = MockBroker::default();
+/// mock.subscribe_mock().return_value(true);
+/// mock.subscribe_mock().use_live_handler(Box::new(|topic: String, on_msg: Box<dyn Fn(String)>| {
+///     on_msg(format!("{}: first", topic));
+///     on_msg(format!("{}: second", topic));
+/// }));
+///
+/// let received = Rc::new(RefCell::new(vec!()));
+/// let sink = received.clone();
+/// assert!(mock.subscribe("news", Box::new(move |msg| sink.borrow_mut().push(msg))));
+///
+/// assert_eq!(*received.borrow(), vec!("news: first", "news: second"));
+/// assert!(mock.subscribe_mock().called_with("news".to_owned()));
+/// # }
+/// ```
+///
+/// ### Methods Returning `Self`
+///
+/// Methods which return `Self`, such as the methods of builders, are listed
+/// in `mock_trait!` with `-> Self`. Their mocks record calls with a return
+/// type of `()`, since a mock can't store values of its own type. Methods
+/// taking `self` by value return `self`. Methods taking `&self` or
+/// `&mut self` return a clone of the prototype set using
+/// `Mock::return_self_prototype`, or the `Default` value of the mock if no
+/// prototype was set.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+/// trait Query {
+///     fn filter(self, condition: &str) -> Self;
+///     fn limit(self, rows: u32) -> Self;
+///     fn count(self) -> u32;
+/// }
+///
+/// mock_trait!(
+///     MockQuery,
+///     filter(String) -> Self,
+///     limit(u32) -> Self,
+///     count(()) -> u32);
+///
+/// impl Query for MockQuery {
+///     mock_method!(filter(self, condition: &str) -> Self);
+///     mock_method!(limit(self, rows: u32) -> Self);
+///     mock_method!(count(self) -> u32);
+/// }
+///
+/// # fn main() {
+/// let mock = MockQuery::default();
+/// mock.count_mock().return_value(3u32);
+///
+/// // clones share the mock's calls, so `mock` can still be checked
+/// assert_eq!(3, mock.clone().filter("age > 30").limit(10).count());
+/// assert!(mock.filter_mock().called_with("age > 30".to_owned()));
+/// assert!(mock.limit_mock().called_with(10u32));
+/// # }
+/// ```
+///
+/// Methods returning `Box<dyn Trait>` can't be mocked this way, since `Self`
+/// is boxed. Instead, the mock can return a mock `struct` which a custom body
+/// boxes. See the `builder` example, which uses `Mock::use_closure` to create
+/// a new mock for each call.
+///
+/// ### Smart Pointer Receivers
+///
+/// Methods that take `self: Arc<Self>` or `self: Rc<Self>` are mocked the
+/// same way as `&self` methods, with or without a custom body. Code under
+/// test may consume the pointer to the mock, but clones of a mock share its
+/// configuration and calls, so a clone made before wrapping the mock can
+/// still be used to check it.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+/// use std::rc::Rc;
+///
+/// trait Job {
+///     fn run(self: Rc<Self>, attempts: u32) -> bool;
+/// }
+///
+/// mock_trait!(
+///     MockJob,
+///     run(u32) -> bool);
+///
+/// impl Job for MockJob {
+///     mock_method!(run(self: Rc<Self>, attempts: u32) -> bool);
+/// }
+///
+/// # fn main() {
+/// let mock = MockJob::default();
+/// mock.run_mock().return_value(true);
+///
+/// assert!(Rc::new(mock.clone()).run(3));
+/// assert!(mock.run_mock().called_with(3u32));
+/// # }
+/// ```
+///
+/// ### Async Methods
+///
+/// Prefixing the method with `async` generates a method with the signature
+/// `#[async_trait]` gives `async fn`s, for each of the `&self` and `&mut self`
+/// variants above that don't take type parameters. The underlying mock is
+/// called immediately and its result is returned as an already completed
+/// future. With a custom body, the value of the body is the future's output.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+/// use std::future::Future;
+/// use std::pin::Pin;
+///
+/// // What `#[async_trait]` expands `async fn fetch(&self, id: u64) -> u32` to.
+/// trait Fetcher {
+///     fn fetch<'life0, 'async_trait>(&'life0 self, id: u64)
+///         -> Pin<Box<dyn Future<Output = u32> + Send + 'async_trait>>
+///         where 'life0: 'async_trait, Self: 'async_trait;
+/// }
+///
+/// mock_trait!(
+///     MockFetcher,
+///     fetch(u64) -> u32);
+///
+/// impl Fetcher for MockFetcher {
+///     mock_method!(async fetch(&self, id: u64) -> u32);
+/// }
+/// # fn main() {
+/// #     let mock = MockFetcher::default();
+/// #     let _ = mock.fetch(3);
+/// #     assert!(mock.fetch_mock().called_with(3u64));
+/// # }
+/// ```
 ///
-/// ### Type Parameters
+/// Arguments must be owned, since `#[async_trait]` adds a lifetime parameter
+/// for each borrowed argument.
 ///
-/// There are an additional 4 variants to handle method type parameters
-/// (e.g. `fn foo<T: Eq>(&self, a: &T)`). These variants allow one to generate
-/// mock methods which take some generic type parameters.
+/// ### Troubleshooting
 ///
-/// For example, suppose one had a `Comparator` trait that was responsible for
-/// comparing any two values in the program. It might look something like this:
+/// The argument types listed for a method in `mock_trait!` have to line up
+/// with the arguments passed to `mock_method!`. The generated body stores the
+/// arguments in a variable with the listed types, so when they don't match,
+/// the compiler points at the `mock_method!` line. Listing too few or too many
+/// types reports the argument types the mock expects:
 ///
-/// ```
-/// trait Comparator {
-///    fn is_equal<T: Eq>(&self, a: &T, b: &T) -> bool;
+/// ```compile_fail,E0308
+/// # #[macro_use] extern crate double;
+/// trait Calculator {
+///     fn add(&self, a: i32, b: i32) -> i32;
 /// }
-/// ```
 ///
-/// `T` can be multiple types. Currently, we cannot store call arguments that
-/// have generic types in the underlying `Mock` objects. Therefore, one has to
-/// convert the generic types to a different, common representation. One way
-/// to get around this limitation is converting each generic type to a `String`.
-/// e.g. for the `Comparator` trait:
+/// mock_trait!(
+///     MockCalculator,
+///     add(i32) -> i32);  // should be `add(i32, i32)`
 ///
+/// impl Calculator for MockCalculator {
+///     // error: expected `i32`, found `(i32, i32)`
+///     mock_method!(add(&self, a: i32, b: i32) -> i32);
+/// }
+/// # fn main() {}
 /// ```
-/// # #[macro_use] extern crate double;
 ///
-/// use std::string::ToString;
+/// Listing a type that an argument can't be converted into names both types:
 ///
-/// trait Comparator {
-///    fn is_equal<T: Eq + ToString>(&self, a: &T, b: &T) -> bool;
+/// ```compile_fail,E0277
+/// # #[macro_use] extern crate double;
+/// trait Store {
+///     fn put(&self, key: u32, value: &str);
 /// }
 ///
 /// mock_trait!(
-///     MockComparator,
-///     // store all passed in call args as strings
-///     is_equal((String, String)) -> bool
-/// );
+///     MockStore,
+///     put((u32, i32)) -> ());  // should be `put((u32, String))`
 ///
-/// impl Comparator for MockComparator {
-///     mock_method!(is_equal<(T: Eq + ToString)>(&self, a: &T, b: &T) -> bool, self, {
-///         // Convert both arguments to strings and manually pass to underlying
-///         // mock object.
-///         // Notice how the both arguments as passed as a single tuple. The
-///         // underlying mock object always expects a single tuple.
-///         self.is_equal.call((a.to_string(), b.to_string()))
-///     });
-/// }
-/// # fn main() {
-///     // only here to make `cargo test` happy
+/// impl Store for MockStore {
+///     // error: `mock_method!` can't pass a `&str` argument to a mock that
+///     // stores `i32`
+///     mock_method!(put(&self, key: u32, value: &str));
 /// }
+/// # fn main() {}
 /// ```
 ///
-/// If the `to_string` conversions for all `T` are not lossy, then our mock
-/// expectations can be exact. If the `to_string` conversions _are_ lossy, then
-/// this mechanism can still be used, providing all the properties of the passed
-/// in objects are captured in the resultant `String`s.
+/// Borrowed arguments are stored as their owned equivalents, so `&str`
+/// arguments are listed as `String` rather than `&'static str`. Listing a
+/// borrowed type requires every argument to outlive the mock:
 ///
-/// This approach requires the writer to ensure the code under test adds the
-/// `ToString` trait to the `trait`'s type argument constraints. This limitation
-/// forces test writers to modify production code to use `double` for mocking.
+/// ```compile_fail,E0521
+/// # #[macro_use] extern crate double;
+/// trait Greeter {
+///     fn greet(&self, name: &str) -> String;
+/// }
 ///
-/// Despite this, there is still value in using `double` for mocking generic
-/// methods with type arguments. Despite adding boilerplate to production code
-/// and manually implementing mock method bodies being cumbersome, the value add
-/// is that all argument matching, expectations, calling test functions, etc.
-/// are all still handled by `double`. Arguably, reimplenting those features is
-/// more cumbersome than the small amount of boilerplate required to mock
-/// methods with type arguments.
+/// mock_trait!(
+///     MockGreeter,
+///     greet(&'static str) -> String);  // should be `greet(String)`
+///
+/// impl Greeter for MockGreeter {
+///     // error: borrowed data escapes outside of method
+///     mock_method!(greet(&self, name: &str) -> String);
+/// }
+/// # fn main() {}
+/// ```
 #[macro_export]
 macro_rules! mock_method {
 
     // immutable, no return value, no type parameter, no body
     ( $method:ident(&self $(,$arg_name:ident: $arg_type:ty)*)) => (
         fn $method(&self $(,$arg_name: $arg_type)*) {
-            self.$method.call(($($arg_name.clone()),*))
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args)
         }
     );
 
@@ -427,8 +1902,15 @@ macro_rules! mock_method {
         fn $method(&$sel $(,$arg_name: $arg_type)*) $body
     );
 
-    // immutable, no return value, type parameter, no body
-    // not provided, since type parameters need a custom body 99% of the time
+    // immutable, no return value, type parameter, stored conversions, no body
+    ( $method:ident<($($type_params: tt)*)>(&self
+        $(,$arg_name:ident: $arg_type:ty $(as $store_type:ty)?)*)) => (
+            fn $method<$($type_params)*>(&self $(,$arg_name: $arg_type)*) {
+                let args: $crate::__private_mock_args!($method) =
+                    ($($crate::__private_store_arg!($arg_name $(as $store_type)?)),*);
+                $crate::__private_mock_field!(self, $method).call(args)
+            }
+    );
 
     // immutable, no return value, type parameter, body
     ( $method:ident<($($type_params: tt)*)>(&self $(,$arg_name:ident: $arg_type:ty)*),
@@ -436,10 +1918,59 @@ macro_rules! mock_method {
             fn $method<$($type_params)*>(&$sel $(,$arg_name: $arg_type)*) $body
     );
 
+    // immutable, no return value, optional type parameter, where clause, body
+    ( $method:ident$(<($($type_params: tt)*)>)?(&self $(,$arg_name:ident: $arg_type:ty)*)
+        where ($($where_clause: tt)*), $sel:ident, $body:tt) => (
+            fn $method$(<$($type_params)*>)?(&$sel $(,$arg_name: $arg_type)*)
+                where $($where_clause)* $body
+    );
+
+    // immutable, returns `Self`, no type parameter, no body
+    ( $method:ident(&self $(,$arg_name:ident: $arg_type:ty)*) -> Self ) => (
+        fn $method(&self $(,$arg_name: $arg_type)*) -> Self {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args);
+            $crate::__private_mock_field!(self, $method).self_prototype().unwrap_or_default()
+        }
+    );
+
     // immutable, return value, no type parameter, no body
     ( $method:ident(&self $(,$arg_name:ident: $arg_type:ty)*) -> $retval:ty ) => (
         fn $method(&self $(,$arg_name: $arg_type)*) -> $retval {
-            self.$method.call(($($arg_name.clone()),*))
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args)
+        }
+    );
+
+    // immutable, return value, no type parameter, out-parameter effect
+    ( $method:ident(&self $(,$arg_name:ident: $arg_type:ty)*) -> $retval:ty,
+        effect($out:ident, $effect:expr) ) => (
+        fn $method(&self $(,$arg_name: $arg_type)*) -> $retval {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::ToOwnedArg::to_owned_arg(&$arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call_with_effect(args, $out, &$effect)
+        }
+    );
+
+    // immutable, no return value, no type parameter, live args
+    ( $method:ident(&self $(,$arg_name:ident: $arg_type:ty)*;
+        $($live_name:ident: $live_type:ty),+) ) => (
+        fn $method(&self $(,$arg_name: $arg_type)* $(,$live_name: $live_type)+) {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call_with_live_args(args, ($($live_name),+))
+        }
+    );
+
+    // immutable, return value, no type parameter, live args
+    ( $method:ident(&self $(,$arg_name:ident: $arg_type:ty)*;
+        $($live_name:ident: $live_type:ty),+) -> $retval:ty ) => (
+        fn $method(&self $(,$arg_name: $arg_type)* $(,$live_name: $live_type)+) -> $retval {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call_with_live_args(args, ($($live_name),+))
         }
     );
 
@@ -448,8 +1979,15 @@ macro_rules! mock_method {
         fn $method(&$sel $(,$arg_name: $arg_type)*) -> $retval $body
     );
 
-    // immutable, return value, type parameter, no body
-    // not provided, since type parameters need a custom body 99% of the time
+    // immutable, return value, type parameter, stored conversions, no body
+    ( $method:ident<($($type_params: tt)*)>(&self
+        $(,$arg_name:ident: $arg_type:ty $(as $store_type:ty)?)*) -> $retval:ty ) => (
+            fn $method<$($type_params)*>(&self $(,$arg_name: $arg_type)*) -> $retval {
+                let args: $crate::__private_mock_args!($method) =
+                    ($($crate::__private_store_arg!($arg_name $(as $store_type)?)),*);
+                $crate::__private_mock_field!(self, $method).call(args)
+            }
+    );
 
     // immutable, return value, type parameter, body
     ( $method:ident<($($type_params: tt)*)>(&self $(,$arg_name:ident: $arg_type:ty)*)
@@ -457,10 +1995,19 @@ macro_rules! mock_method {
             fn $method<$($type_params)*>(&$sel $(,$arg_name: $arg_type)*) -> $retval $body
     );
 
+    // immutable, return value, optional type parameter, where clause, body
+    ( $method:ident$(<($($type_params: tt)*)>)?(&self $(,$arg_name:ident: $arg_type:ty)*)
+        -> $retval:ty where ($($where_clause: tt)*), $sel:ident, $body:tt ) => (
+            fn $method$(<$($type_params)*>)?(&$sel $(,$arg_name: $arg_type)*) -> $retval
+                where $($where_clause)* $body
+    );
+
     // mutable, no return value, no type parameter, no body
     ( $method:ident(&mut self $(,$arg_name:ident: $arg_type:ty)*)) => (
         fn $method(&mut self $(,$arg_name: $arg_type)*) {
-            self.$method.call(($($arg_name.clone()),*))
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args)
         }
     );
 
@@ -469,8 +2016,15 @@ macro_rules! mock_method {
         fn $method(&mut $sel $(,$arg_name: $arg_type)*) $body
     );
 
-    // mutable, no return value, type parameter, no body
-    // not provided, since type parameters need a custom body 99% of the time
+    // mutable, no return value, type parameter, stored conversions, no body
+    ( $method:ident<($($type_params: tt)*)>(&mut self
+        $(,$arg_name:ident: $arg_type:ty $(as $store_type:ty)?)*)) => (
+            fn $method<$($type_params)*>(&mut self $(,$arg_name: $arg_type)*) {
+                let args: $crate::__private_mock_args!($method) =
+                    ($($crate::__private_store_arg!($arg_name $(as $store_type)?)),*);
+                $crate::__private_mock_field!(self, $method).call(args)
+            }
+    );
 
     // mutable, no return value, type parameter, body
     ( $method:ident<($($type_params: tt)*)>(&mut self $(,$arg_name:ident: $arg_type:ty)*),
@@ -478,10 +2032,59 @@ macro_rules! mock_method {
             fn $method<$($type_params)*>(&mut $sel $(,$arg_name: $arg_type)*) $body
     );
 
+    // mutable, no return value, optional type parameter, where clause, body
+    ( $method:ident$(<($($type_params: tt)*)>)?(&mut self $(,$arg_name:ident: $arg_type:ty)*)
+        where ($($where_clause: tt)*), $sel:ident, $body:tt) => (
+            fn $method$(<$($type_params)*>)?(&mut $sel $(,$arg_name: $arg_type)*)
+                where $($where_clause)* $body
+    );
+
+    // mutable, returns `Self`, no type parameter, no body
+    ( $method:ident(&mut self $(,$arg_name:ident: $arg_type:ty)*) -> Self ) => (
+        fn $method(&mut self $(,$arg_name: $arg_type)*) -> Self {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args);
+            $crate::__private_mock_field!(self, $method).self_prototype().unwrap_or_default()
+        }
+    );
+
     // mutable, return value, no type parameter, no body
     ( $method:ident(&mut self $(,$arg_name:ident: $arg_type:ty)*) -> $retval:ty ) => (
         fn $method(&mut self $(,$arg_name: $arg_type)*) -> $retval {
-            self.$method.call(($($arg_name.clone()),*))
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args)
+        }
+    );
+
+    // mutable, return value, no type parameter, out-parameter effect
+    ( $method:ident(&mut self $(,$arg_name:ident: $arg_type:ty)*) -> $retval:ty,
+        effect($out:ident, $effect:expr) ) => (
+        fn $method(&mut self $(,$arg_name: $arg_type)*) -> $retval {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::ToOwnedArg::to_owned_arg(&$arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call_with_effect(args, $out, &$effect)
+        }
+    );
+
+    // mutable, no return value, no type parameter, live args
+    ( $method:ident(&mut self $(,$arg_name:ident: $arg_type:ty)*;
+        $($live_name:ident: $live_type:ty),+) ) => (
+        fn $method(&mut self $(,$arg_name: $arg_type)* $(,$live_name: $live_type)+) {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call_with_live_args(args, ($($live_name),+))
+        }
+    );
+
+    // mutable, return value, no type parameter, live args
+    ( $method:ident(&mut self $(,$arg_name:ident: $arg_type:ty)*;
+        $($live_name:ident: $live_type:ty),+) -> $retval:ty ) => (
+        fn $method(&mut self $(,$arg_name: $arg_type)* $(,$live_name: $live_type)+) -> $retval {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call_with_live_args(args, ($($live_name),+))
         }
     );
 
@@ -490,8 +2093,15 @@ macro_rules! mock_method {
         fn $method(&mut $sel $(,$arg_name: $arg_type)*) -> $retval $body
     );
 
-    // mutable, return value, type parameter, no body
-    // not provided, since type parameters need a custom body 99% of the time
+    // mutable, return value, type parameter, stored conversions, no body
+    ( $method:ident<($($type_params: tt)*)>(&mut self
+        $(,$arg_name:ident: $arg_type:ty $(as $store_type:ty)?)*) -> $retval:ty ) => (
+            fn $method<$($type_params)*>(&mut self $(,$arg_name: $arg_type)*) -> $retval {
+                let args: $crate::__private_mock_args!($method) =
+                    ($($crate::__private_store_arg!($arg_name $(as $store_type)?)),*);
+                $crate::__private_mock_field!(self, $method).call(args)
+            }
+    );
 
     // mutable, return value, type parameter, body
     ( $method:ident<($($type_params: tt)*)>(&mut self $(,$arg_name:ident: $arg_type:ty)*)
@@ -499,4 +2109,418 @@ macro_rules! mock_method {
             fn $method<$($type_params)*>(&mut $sel $(,$arg_name: $arg_type)*) -> $retval $body
     );
 
+    // mutable, return value, optional type parameter, where clause, body
+    ( $method:ident$(<($($type_params: tt)*)>)?(&mut self $(,$arg_name:ident: $arg_type:ty)*)
+        -> $retval:ty where ($($where_clause: tt)*), $sel:ident, $body:tt ) => (
+            fn $method$(<$($type_params)*>)?(&mut $sel $(,$arg_name: $arg_type)*) -> $retval
+                where $($where_clause)* $body
+    );
+
+    // by value, no return value, no body
+    ( $method:ident(self $(,$arg_name:ident: $arg_type:ty)*)) => (
+        fn $method(self $(,$arg_name: $arg_type)*) {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args)
+        }
+    );
+
+    // by value, no return value, body
+    ( $method:ident(self $(,$arg_name:ident: $arg_type:ty)*), $sel:ident, $body:tt ) => (
+        fn $method($sel $(,$arg_name: $arg_type)*) $body
+    );
+
+    // by value, returns `Self`, no body
+    ( $method:ident(self $(,$arg_name:ident: $arg_type:ty)*) -> Self ) => (
+        fn $method(self $(,$arg_name: $arg_type)*) -> Self {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args);
+            self
+        }
+    );
+
+    // by value, return value, no body
+    ( $method:ident(self $(,$arg_name:ident: $arg_type:ty)*) -> $retval:ty ) => (
+        fn $method(self $(,$arg_name: $arg_type)*) -> $retval {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args)
+        }
+    );
+
+    // by value, return value, body
+    ( $method:ident(self $(,$arg_name:ident: $arg_type:ty)*) -> $retval:ty, $sel:ident, $body:tt ) => (
+        fn $method($sel $(,$arg_name: $arg_type)*) -> $retval $body
+    );
+
+    // smart pointer receiver, no return value, no body
+    ( $method:ident(self: $ptr:ident<Self> $(,$arg_name:ident: $arg_type:ty)*)) => (
+        fn $method(self: $ptr<Self> $(,$arg_name: $arg_type)*) {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args)
+        }
+    );
+
+    // smart pointer receiver, no return value, body
+    ( $method:ident(self: $ptr:ident<Self> $(,$arg_name:ident: $arg_type:ty)*), $sel:ident, $body:tt ) => (
+        fn $method($sel: $ptr<Self> $(,$arg_name: $arg_type)*) $body
+    );
+
+    // smart pointer receiver, return value, no body
+    ( $method:ident(self: $ptr:ident<Self> $(,$arg_name:ident: $arg_type:ty)*) -> $retval:ty ) => (
+        fn $method(self: $ptr<Self> $(,$arg_name: $arg_type)*) -> $retval {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args)
+        }
+    );
+
+    // smart pointer receiver, return value, body
+    ( $method:ident(self: $ptr:ident<Self> $(,$arg_name:ident: $arg_type:ty)*) -> $retval:ty, $sel:ident, $body:tt ) => (
+        fn $method($sel: $ptr<Self> $(,$arg_name: $arg_type)*) -> $retval $body
+    );
+
+    // async, immutable, no return value, no body
+    ( async $method:ident(&self $(,$arg_name:ident: $arg_type:ty)*)) => (
+        $crate::mock_method!(async $method(&self $(,$arg_name: $arg_type)*) -> (), self, {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args)
+        });
+    );
+
+    // async, immutable, no return value, body
+    ( async $method:ident(&self $(,$arg_name:ident: $arg_type:ty)*), $sel:ident, $body:tt ) => (
+        $crate::mock_method!(async $method(&self $(,$arg_name: $arg_type)*) -> (), $sel, $body);
+    );
+
+    // async, immutable, return value, no body
+    ( async $method:ident(&self $(,$arg_name:ident: $arg_type:ty)*) -> $retval:ty ) => (
+        $crate::mock_method!(async $method(&self $(,$arg_name: $arg_type)*) -> $retval, self, {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args)
+        });
+    );
+
+    // async, immutable, return value, body
+    ( async $method:ident(&self $(,$arg_name:ident: $arg_type:ty)*) -> $retval:ty, $sel:ident, $body:tt ) => (
+        fn $method<'life0, 'async_trait>(&'life0 $sel $(,$arg_name: $arg_type)*)
+            -> ::std::pin::Pin<Box<
+                dyn std::future::Future<Output = $retval> + Send + 'async_trait>>
+            where 'life0: 'async_trait, Self: 'async_trait
+        {
+            Box::pin(::std::future::ready($body))
+        }
+    );
+
+    // async, mutable, no return value, no body
+    ( async $method:ident(&mut self $(,$arg_name:ident: $arg_type:ty)*)) => (
+        $crate::mock_method!(async $method(&mut self $(,$arg_name: $arg_type)*) -> (), self, {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args)
+        });
+    );
+
+    // async, mutable, no return value, body
+    ( async $method:ident(&mut self $(,$arg_name:ident: $arg_type:ty)*), $sel:ident, $body:tt ) => (
+        $crate::mock_method!(async $method(&mut self $(,$arg_name: $arg_type)*) -> (), $sel, $body);
+    );
+
+    // async, mutable, return value, no body
+    ( async $method:ident(&mut self $(,$arg_name:ident: $arg_type:ty)*) -> $retval:ty ) => (
+        $crate::mock_method!(async $method(&mut self $(,$arg_name: $arg_type)*) -> $retval, self, {
+            let args: $crate::__private_mock_args!($method) =
+                ($($crate::macros::IntoOwnedArg::into_owned_arg($arg_name)),*);
+            $crate::__private_mock_field!(self, $method).call(args)
+        });
+    );
+
+    // async, mutable, return value, body
+    ( async $method:ident(&mut self $(,$arg_name:ident: $arg_type:ty)*) -> $retval:ty, $sel:ident, $body:tt ) => (
+        fn $method<'life0, 'async_trait>(&'life0 mut $sel $(,$arg_name: $arg_type)*)
+            -> ::std::pin::Pin<Box<
+                dyn std::future::Future<Output = $retval> + Send + 'async_trait>>
+            where 'life0: 'async_trait, Self: 'async_trait
+        {
+            Box::pin(::std::future::ready($body))
+        }
+    );
+
+}
+
+/// Declares the argument types of the methods of a mock `struct` that isn't
+/// generated by `mock_trait!`, so their bodies can be generated by
+/// `mock_method!`.
+///
+/// `mock_method!` builds the arguments it passes to a method's mock with the
+/// types listed for the method in `mock_trait!`, which `mock_trait!`
+/// records by implementing `double::macros::MockMethodArgs` for each method.
+/// Mocks written by hand declare the same types using this macro, which takes
+/// the name of the mock and each method's argument types in the same format
+/// as `mock_trait!`. Lifetimes of the mock are listed in brackets after its
+/// name, e.g. `MockStore['a]`.
+///
+/// ```
+/// # #[macro_use] extern crate double;
+/// use double::Mock;
+///
+/// trait Source {
+///     fn ids(&self, limit: usize) -> Box<dyn Iterator<Item = u64>>;
+/// }
+///
+/// // `mock_trait!` requires return types to implement `Clone`. `mock_method!`
+/// // passes the arguments of `ids` to the `m_ids` field.
+/// struct MockSource {
+///     m_ids: Mock<usize, Box<dyn Iterator<Item = u64>>>,
+/// }
+///
+/// mock_method_args!(
+///     MockSource,
+///     ids(usize));
+///
+/// impl Source for MockSource {
+///     mock_method!(ids(&self, limit: usize) -> Box<dyn Iterator<Item = u64>>);
+/// }
+///
+/// # fn main() {
+/// let mock = MockSource {
+///     m_ids: Mock::with_producer(Box::new(|| Box::new(1..3))),
+/// };
+/// assert_eq!(vec!(1, 2), mock.ids(2).collect::<Vec<_>>());
+/// assert!(mock.m_ids.called_with(2usize));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! mock_method_args {
+    // The lifetimes are captured in a single token tree, so they can be
+    // repeated for each method.
+    (@methods $mock_name:ident $lifetimes:tt $(, $method:ident($($arg_type:ty),*))*) => (
+        $( $crate::mock_method_args!(@method $mock_name $lifetimes $method($($arg_type),*)); )*
+    );
+    (@method $mock_name:ident [$($lt:lifetime),*] $method:ident($($arg_type:ty),*)) => (
+        impl<$($lt),*> $crate::macros::MockMethodArgs<
+            { $crate::macros::method_id(stringify!($method)) }> for $mock_name<$($lt),*>
+        {
+            type Args = (($($arg_type),*));
+        }
+    );
+
+    ($mock_name:ident $([$($lt:lifetime),+])? $(, $method:ident($($arg_type:ty),*))* $(,)?) => (
+        $crate::mock_method_args!(@methods $mock_name [$($($lt),+)?] $(, $method($($arg_type),*))*);
+    );
+}
+
+/// Macro that evaluates every given `bool` mock check, then panics with a
+/// report listing all of the checks that failed.
+///
+/// Using a separate `assert!` for each check stops the test at the first
+/// failing check, which hides whether the checks after it passed. `verify!`
+/// always evaluates all of the checks, in the order they are given.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate double;
+/// use double::Mock;
+///
+/// # fn main() {
+/// let mock = Mock::<(i32, i32), i32>::default();
+/// mock.call((1, 2));
+///
+/// verify!(
+///     mock.called(),
+///     mock.called_with((1, 2)),
+///     mock.num_calls() == 1);
+/// # }
+/// ```
+///
+/// If any checks fail, the panic message contains the source text of each
+/// failed check:
+///
+/// ```
+/// # #[macro_use] extern crate double;
+/// use double::Mock;
+/// use std::panic;
+///
+/// # fn main() {
+/// let mock = Mock::<(i32, i32), i32>::default();
+/// mock.call((1, 2));
+///
+/// let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+///     verify!(
+///         mock.called_with((3, 4)),
+///         mock.called(),
+///         mock.num_calls() == 2);
+/// }));
+/// let message = result.unwrap_err().downcast::<String>().unwrap();
+/// assert_eq!(
+///     *message,
+///     "verify! failed 2 of 3 checks:\n  \
+///      mock.called_with((3, 4))\n  \
+///      mock.num_calls() == 2");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! verify {
+    ($($check:expr),+ $(,)?) => ({
+        let mut num_checks = 0;
+        let mut failed_checks: Vec<&str> = Vec::new();
+        $(
+            num_checks += 1;
+            if !$check {
+                failed_checks.push(stringify!($check));
+            }
+        )+
+        if !failed_checks.is_empty() {
+            panic!(
+                "verify! failed {} of {} checks:\n  {}",
+                failed_checks.len(),
+                num_checks,
+                failed_checks.join("\n  "));
+        }
+    });
+}
+
+/// Asserts that calls were made to mocks in the given relative order, using
+/// `double::mock::check_call_order`. Panics with a message describing the
+/// first call which was out of order if they weren't.
+///
+/// A mock can be listed several times to check it was called several times.
+/// Other calls made to the mocks are ignored.
+///
+/// Every `Mock` in the process shares the counter used to order calls, so
+/// calls to any mocks, including mocks of different traits, can be compared.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate double;
+/// use double::Mock;
+///
+/// struct Store {
+///     save: Mock<&'static str, ()>,
+/// }
+///
+/// # fn main() {
+/// let store = Store { save: Mock::default() };
+/// let notify = Mock::<(), ()>::default();
+///
+/// store.save.call("draft");
+/// notify.call(());
+/// store.save.call("final");
+///
+/// assert_call_order!(store.save, notify, store.save);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_call_order {
+    ($($mock:expr),+ $(,)?) => ({
+        let calls = [$( (stringify!($mock), $mock.call_timestamps()) ),+];
+        if let Err(message) = $crate::mock::check_call_order(&calls) {
+            panic!("assert_call_order! failed: {}", message);
+        }
+    });
+}
+
+/// Asserts that `mock` was called with `args`, using `Mock::check_called_with`.
+/// Unlike `assert!(mock.called_with(args))`, the panic message includes the
+/// mock's expression, the expected arguments, every recorded call and the
+/// location of the assertion.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate double;
+/// use double::Mock;
+///
+/// struct BalanceSheet {
+///     profit: Mock<(i32, i32), i32>,
+/// }
+///
+/// # fn main() {
+/// let sheet = BalanceSheet { profit: Mock::default() };
+/// sheet.profit.call((500, 250));
+///
+/// expect_called!(sheet.profit, (500, 250));
+/// # }
+/// ```
+///
+/// ```should_panic
+/// # #[macro_use] extern crate double;
+/// # use double::Mock;
+/// # fn main() {
+/// let profit = Mock::<(i32, i32), i32>::default();
+/// profit.call((0, 0));
+///
+/// // panics with:
+/// //   expect_called!(profit) failed at <file>:<line>
+/// //   Mock::called_with failed
+/// //     expected: (500, 250)
+/// //     actual calls: [(0, 0)]
+/// expect_called!(profit, (500, 250));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! expect_called {
+    ($mock:expr, $args:expr $(,)?) => (
+        $crate::__private_expect!(expect_called, $mock, $mock.check_called_with($args))
+    );
+}
+
+/// Asserts that `mock` was called with arguments matching `pattern`, using
+/// `Mock::check_called_with_pattern`. Panics with the same details as
+/// `expect_called!` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate double;
+/// use double::Mock;
+/// use double::matcher::*;
+///
+/// # fn main() {
+/// let profit = Mock::<(i32, i32), i32>::default();
+/// profit.call((500, 250));
+///
+/// expect_called_pattern!(profit, matcher!(p!(eq, 500), p!(any)));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! expect_called_pattern {
+    ($mock:expr, $pattern:expr $(,)?) => (
+        $crate::__private_expect!(
+            expect_called_pattern, $mock, $mock.check_called_with_pattern($pattern))
+    );
+}
+
+/// Asserts that `mock` was called exactly with `calls`, in order, using
+/// `Mock::check_has_calls_exactly_in_order`. Panics with the same details as
+/// `expect_called!` otherwise.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate double;
+/// use double::Mock;
+///
+/// # fn main() {
+/// let profit = Mock::<(i32, i32), i32>::default();
+/// profit.call((500, 250));
+/// profit.call((0, 0));
+///
+/// expect_has_calls_exactly_in_order!(profit, vec!((500, 250), (0, 0)));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! expect_has_calls_exactly_in_order {
+    ($mock:expr, $calls:expr $(,)?) => (
+        $crate::__private_expect!(
+            expect_has_calls_exactly_in_order,
+            $mock,
+            $mock.check_has_calls_exactly_in_order($calls))
+    );
 }
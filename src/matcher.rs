@@ -2,11 +2,103 @@ extern crate float_cmp;
 
 use std::f32;
 use std::f64;
+use std::iter::Sum;
+use std::ops::Sub;
+use std::rc::Rc;
+use std::sync::Arc;
 use self::float_cmp::ApproxEqUlps;
 
 
 include!(concat!(env!("OUT_DIR"), "/matcher_generated.rs"));
 
+/// Combines a matcher for each argument of a mocked method into a single
+/// matcher for the tuple of arguments stored by its `Mock`.
+///
+/// Each argument's matcher can be a `p!` matcher or a borrowed closure, `_`
+/// to match any value, or a literal to match values equal to it.
+///
+/// # Examples
+///
+/// ```
+/// let mock = double::Mock::<(i32, &str, bool), ()>::default();
+/// mock.call((1, "apple", true));
+///
+/// assert!(mock.called_with_pattern(double::matcher!(_, "apple", &|b: &bool| *b)));
+/// assert!(!mock.called_with_pattern(double::matcher!(2, _, _)));
+/// ```
+//
+// The arguments are munched one at a time, replacing each `_` with the `any`
+// matcher and each literal with an `eq` matcher. The shorthands are tried
+// before `$m:expr`, since `_` and literals are also expressions. The
+// matchers are then passed to the `match_impl_n` function for their number.
+// Recursive calls use `$crate::matcher!`, so the macro can be invoked by path
+// without importing it.
+#[macro_export]
+macro_rules! matcher {
+    (@munch [$(($m:expr))+]) => (
+        &|args| -> bool {
+            $crate::matcher::MatchImpl::<{ [$(stringify!($m)),+].len() }>::matches(args, ($($m),+))
+        }
+    );
+    (@munch [$($done:tt)*] _ $(, $($rest:tt)*)?) => (
+        $crate::matcher!(@munch [$($done)* (
+            &|potential_match| -> bool { $crate::matcher::any(potential_match) }
+        )] $($($rest)*)?)
+    );
+    (@munch [$($done:tt)*] $value:literal $(, $($rest:tt)*)?) => (
+        $crate::matcher!(@munch [$($done)* (
+            &|potential_match| -> bool { $crate::matcher::eq(potential_match, $value) }
+        )] $($($rest)*)?)
+    );
+    (@munch [$($done:tt)*] $m:expr $(, $($rest:tt)*)?) => (
+        $crate::matcher!(@munch [$($done)* ($m)] $($($rest)*)?)
+    );
+    ($($args:tt)+) => (
+        $crate::matcher!(@munch [] $($args)+)
+    );
+}
+
+/// Returns a matcher that applies `matcher` to the named field of the
+/// argument, so a struct argument can be matched on some of its fields. A
+/// path such as `address.city` can be used to match nested fields.
+///
+/// Field matchers can be combined with `all_of` to match multiple fields.
+/// Like closures, the type of the argument must be known where the matcher
+/// is used, which it is when the matcher is passed to a `Mock`'s checks.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate double;
+///
+/// use double::matcher::*;
+/// use double::Mock;
+///
+/// #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// struct User {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// fn main() {
+///     let mock = Mock::<User, ()>::default();
+///     mock.call(User { id: 42, name: "Alice".to_owned() });
+///
+///     assert!(mock.called_with_pattern(p_field!(id, p!(eq, 42))));
+///     assert!(mock.called_with_pattern(p!(all_of, vec!(
+///         p_field!(id, p!(eq, 42)),
+///         p_field!(name, p!(eq, "Alice".to_owned()))))));
+///     assert!(!mock.called_with_pattern(p_field!(id, p!(gt, 42))));
+/// }
+/// ```
+#[macro_export]
+macro_rules! p_field {
+    ($($field:tt).+, $matcher:expr) => (
+        &|potential_match| -> bool { ($matcher)(&potential_match.$($field).+) }
+    );
+}
+
 
 // ============================================================================
 // * Comparison Matchers
@@ -27,6 +119,33 @@ pub fn ne<T: PartialEq>(arg: &T, target_val: T) -> bool {
     *arg != target_val
 }
 
+/// Matcher that matches if `arg` is equal to `target_val`. Unlike `eq`, this
+/// takes `target_val` by reference, so it can be used with `pr!` to compare
+/// against large values without copying them into the matcher.
+pub fn eq_ref<T: PartialEq>(arg: &T, target_val: &T) -> bool {
+    *arg == *target_val
+}
+
+/// Matcher that matches if `arg` is not equal to `target_val`. Unlike `ne`,
+/// this takes `target_val` by reference, so it can be used with `pr!`.
+pub fn ne_ref<T: PartialEq>(arg: &T, target_val: &T) -> bool {
+    *arg != *target_val
+}
+
+/// Matcher that matches if `arg` points to the same allocation as `target`,
+/// i.e. if the same `Rc` instance, or a clone of it, was passed. Unlike `eq`,
+/// `Rc`s holding equal values in different allocations don't match. Use with
+/// `pr!`.
+pub fn same_rc<T: ?Sized>(arg: &Rc<T>, target: &Rc<T>) -> bool {
+    Rc::ptr_eq(arg, target)
+}
+
+/// Matcher that matches if `arg` points to the same allocation as `target`.
+/// The `Arc` equivalent of `same_rc`.
+pub fn same_arc<T: ?Sized>(arg: &Arc<T>, target: &Arc<T>) -> bool {
+    Arc::ptr_eq(arg, target)
+}
+
 /// Matcher that matches if `arg` is less than `target_val`.
 pub fn lt<T: PartialOrd>(arg: &T, target_val: T) -> bool {
     *arg < target_val
@@ -57,6 +176,55 @@ pub fn between_inc<T: PartialEq + PartialOrd>(arg: &T, low: T, high: T) -> bool
     low <= *arg && *arg <= high
 }
 
+/// Matcher that matches if `arg` is in the half-open range `[low,high)`, like
+/// Rust's `low..high` ranges.
+pub fn in_range<T: PartialOrd>(arg: &T, low: T, high: T) -> bool {
+    low <= *arg && *arg < high
+}
+
+/// Matcher that matches if `arg` is in the range `[low,high)`. Equivalent to
+/// `in_range`, for use alongside `in_range_exc_inc` when the inclusivity of
+/// each bound should be explicit.
+pub fn in_range_inc_exc<T: PartialOrd>(arg: &T, low: T, high: T) -> bool {
+    in_range(arg, low, high)
+}
+
+/// Matcher that matches if `arg` is in the range `(low,high]`.
+pub fn in_range_exc_inc<T: PartialOrd>(arg: &T, low: T, high: T) -> bool {
+    low < *arg && *arg <= high
+}
+
+/// Matcher that matches if `arg` is within `tolerance` of `target_val`, i.e.
+/// the difference between them is at most `tolerance`. Works with any type
+/// that can be subtracted and ordered, such as fixed-point or decimal types.
+pub fn within<T: Clone + PartialOrd + Sub<Output = T>>(arg: &T, target_val: T, tolerance: T) -> bool {
+    let difference = if *arg >= target_val {
+        arg.clone() - target_val
+    } else {
+        target_val - arg.clone()
+    };
+    difference <= tolerance
+}
+
+/// Matcher that matches if `close(arg, target_val)` returns `true`. This is
+/// useful for approximate equality of types `within` can't be used with,
+/// using a custom distance function.
+pub fn approx<T, F: Fn(&T, &T) -> bool>(arg: &T, target_val: T, close: F) -> bool {
+    close(arg, &target_val)
+}
+
+/// Matcher that matches if `arg` is equal to at least one of the values in
+/// `allowed`. An empty `allowed` never matches.
+pub fn one_of<T: PartialEq>(arg: &T, allowed: Vec<T>) -> bool {
+    allowed.contains(arg)
+}
+
+/// Matcher that matches if `arg` is not equal to any of the values in
+/// `disallowed`. An empty `disallowed` always matches.
+pub fn none_of<T: PartialEq>(arg: &T, disallowed: Vec<T>) -> bool {
+    !one_of(arg, disallowed)
+}
+
 /// Matcher that matches if `arg` is a populated `Option` whose stored value
 /// matches the specified `matcher`.
 pub fn is_some<T>(arg: &Option<T>, matcher: &dyn Fn(&T) -> bool) -> bool {
@@ -66,6 +234,29 @@ pub fn is_some<T>(arg: &Option<T>, matcher: &dyn Fn(&T) -> bool) -> bool {
     }
 }
 
+/// Matcher that matches if `arg` is an empty `Option`.
+pub fn is_none<T>(arg: &Option<T>) -> bool {
+    arg.is_none()
+}
+
+/// Matcher that matches if `arg` is a populated `Option`, regardless of its
+/// stored value.
+pub fn is_some_any<T>(arg: &Option<T>) -> bool {
+    arg.is_some()
+}
+
+/// Matcher that matches if `arg` is a `Result::Ok`, regardless of its stored
+/// value.
+pub fn is_ok_any<T, U>(arg: &Result<T, U>) -> bool {
+    arg.is_ok()
+}
+
+/// Matcher that matches if `arg` is a `Result::Err`, regardless of its stored
+/// value.
+pub fn is_err_any<T, U>(arg: &Result<T, U>) -> bool {
+    arg.is_err()
+}
+
 /// Matcher that matches if `arg` is a `Result::Ok` whose stored value matches
 /// the specified `matcher`.
 pub fn is_ok<T, U>(arg: &Result<T, U>, matcher: &dyn Fn(&T) -> bool) -> bool {
@@ -135,6 +326,20 @@ pub fn nan_sensitive_f64_eq(arg: &f64, target_val: f64) -> bool {
     }
 }
 
+/// Matcher that matches if `arg` is within `max_abs_error` of `target_val`.
+/// Unlike `f32_eq`, the tolerance is an absolute difference, which is easier
+/// to reason about for values computed by the code under test.
+pub fn f32_near(arg: &f32, target_val: f32, max_abs_error: f32) -> bool {
+    (*arg - target_val).abs() <= max_abs_error
+}
+
+/// Matcher that matches if `arg` is within `max_abs_error` of `target_val`.
+/// Unlike `f64_eq`, the tolerance is an absolute difference, which is easier
+/// to reason about for values computed by the code under test.
+pub fn f64_near(arg: &f64, target_val: f64, max_abs_error: f64) -> bool {
+    (*arg - target_val).abs() <= max_abs_error
+}
+
 
 // ============================================================================
 // * String Matchers
@@ -166,12 +371,93 @@ pub fn ne_nocase(arg: &str, string: &str) -> bool {
     arg.to_lowercase() != string
 }
 
+/// Matcher that matches if `arg` contains the substring specified by `string`
+/// after ignoring case. This is the case-insensitive version of `contains`.
+///
+/// Both strings are lowercased with `str::to_lowercase`, which is
+/// Unicode-aware. Lowercasing can change the length of a string (e.g. `'İ'`
+/// lowercases to two `char`s), so don't assume byte offsets in the
+/// lowercased strings correspond to offsets in `arg`. Characters without a
+/// single-character lowercase form, like `'ß'`, only match themselves.
+pub fn has_substr_nocase(arg: &str, string: &str) -> bool {
+    arg.to_lowercase().contains(&string.to_lowercase())
+}
+
+/// Matcher that matches if `arg` starts with the specified `prefix` after
+/// ignoring case. Lowercasing is Unicode-aware, with the same caveats as
+/// `has_substr_nocase`.
+pub fn starts_with_nocase(arg: &str, prefix: &str) -> bool {
+    arg.to_lowercase().starts_with(&prefix.to_lowercase())
+}
+
+/// Matcher that matches if `arg` ends with the specified `suffix` after
+/// ignoring case. Lowercasing is Unicode-aware, with the same caveats as
+/// `has_substr_nocase`.
+pub fn ends_with_nocase(arg: &str, suffix: &str) -> bool {
+    arg.to_lowercase().ends_with(&suffix.to_lowercase())
+}
+
 
 // ============================================================================
 // * Container Matchers
 // ============================================================================
 
-// TODO
+/// Matcher that matches if the sum of the items in `arg` matches the
+/// specified `matcher`. The sum of an empty collection is zero.
+pub fn sum_is<C, T>(arg: &C, matcher: &dyn Fn(&T) -> bool) -> bool
+    where C: AsRef<[T]>,
+          T: for<'a> Sum<&'a T>
+{
+    let sum: T = arg.as_ref().iter().sum();
+    matcher(&sum)
+}
+
+/// Matcher that matches if the mean of the items in `arg` matches the
+/// specified `matcher`. The mean is computed as an `f64`.
+///
+/// An empty collection has no mean, so this matcher never matches an empty
+/// `arg`.
+pub fn avg_is<C, T>(arg: &C, matcher: &dyn Fn(&f64) -> bool) -> bool
+    where C: AsRef<[T]>,
+          T: Clone + Into<f64>
+{
+    let items = arg.as_ref();
+    if items.is_empty() {
+        false
+    } else {
+        let sum: f64 = items.iter().map(|item| item.clone().into()).sum();
+        matcher(&(sum / items.len() as f64))
+    }
+}
+
+/// Matcher that matches if the items in `arg` start with the items in
+/// `prefix`. An empty `prefix` matches any `arg`.
+pub fn seq_starts_with<C, T>(arg: &C, prefix: Vec<T>) -> bool
+    where C: AsRef<[T]>,
+          T: PartialEq
+{
+    arg.as_ref().starts_with(&prefix)
+}
+
+/// Matcher that matches if the items in `arg` end with the items in
+/// `suffix`. An empty `suffix` matches any `arg`.
+pub fn seq_ends_with<C, T>(arg: &C, suffix: Vec<T>) -> bool
+    where C: AsRef<[T]>,
+          T: PartialEq
+{
+    arg.as_ref().ends_with(&suffix)
+}
+
+/// Matcher that matches if the items in `sub` appear in `arg` in the same
+/// order, though not necessarily next to each other. An empty `sub` matches
+/// any `arg`.
+pub fn seq_contains_subsequence<C, T>(arg: &C, sub: Vec<T>) -> bool
+    where C: AsRef<[T]>,
+          T: PartialEq
+{
+    let mut items = arg.as_ref().iter();
+    sub.iter().all(|expected| items.any(|item| item == expected))
+}
 
 
 // ============================================================================
@@ -206,6 +492,13 @@ pub fn any_of<T>(arg: &T, matchers: Vec<&dyn Fn(&T) -> bool>) -> bool {
     false
 }
 
+/// Matcher that matches if `arg` matches *none* of the specified `matchers`.
+/// If `matchers` is empty, this matcher always matches. Use `none_of` to
+/// match values that aren't equal to any of a list of values instead.
+pub fn none_of_matchers<T>(arg: &T, matchers: Vec<&dyn Fn(&T) -> bool>) -> bool {
+    !any_of(arg, matchers)
+}
+
 
 // ============================================================================
 // * Unit Tests
@@ -223,6 +516,21 @@ mod tests {
         assert!(any(&vec!(1, 2, 3, 4, 5)));
     }
 
+    #[test]
+    fn zero_arg_matchers_through_p_and_pr() {
+        let matcher = p!(any);
+        assert!(matcher(&1));
+        let matcher = pr!(any);
+        assert!(matcher(&"anything"));
+
+        let (some, none): (Option<i32>, Option<i32>) = (Some(0), None);
+        let (matching, first_some, second_none) = ((none, some), (some, some), (none, none));
+        let matcher = matcher!(p!(is_none), pr!(is_some_any));
+        assert!(matcher(&matching));
+        assert!(!matcher(&first_some));
+        assert!(!matcher(&second_none));
+    }
+
     #[test]
     fn eq_matcher() {
         let matcher1 = p!(eq, 1);
@@ -257,6 +565,56 @@ mod tests {
         assert!(matcher3(&vec_arg2));
     }
 
+    #[test]
+    fn eq_ref_matcher() {
+        let target: Vec<u8> = vec![42; 1024 * 1024];
+        let same = target.clone();
+        let mut different = target.clone();
+        different[1000] = 0;
+
+        let matcher = pr!(eq_ref, target);
+        assert!(matcher(&same));
+        assert!(!matcher(&different));
+
+        // `target` was borrowed by the matcher, not moved into it
+        assert_eq!(target.len(), 1024 * 1024);
+    }
+
+    #[test]
+    fn ne_ref_matcher() {
+        let target = "hello".to_owned();
+        let (same, different) = ("hello".to_owned(), "bye_bye".to_owned());
+
+        let matcher = pr!(ne_ref, target);
+        assert!(!matcher(&same));
+        assert!(matcher(&different));
+    }
+
+    #[test]
+    fn same_rc_matcher() {
+        let target = Rc::new("hello".to_owned());
+        let clone = target.clone();
+        let equal = Rc::new("hello".to_owned());
+
+        let matcher = pr!(same_rc, target);
+        assert!(matcher(&clone));
+        assert!(!matcher(&equal));
+        // `eq` can't tell the allocations apart
+        assert!(eq(&equal, target.clone()));
+    }
+
+    #[test]
+    fn same_arc_matcher() {
+        let target = Arc::new(vec!(1, 2, 3));
+        let clone = target.clone();
+        let equal = Arc::new(vec!(1, 2, 3));
+
+        let matcher = pr!(same_arc, target);
+        assert!(matcher(&clone));
+        assert!(!matcher(&equal));
+        assert!(eq(&equal, target.clone()));
+    }
+
     #[test]
     fn lt_matcher() {
         let matcher1 = p!(lt, 10);
@@ -319,6 +677,36 @@ mod tests {
         assert!(!matcher(&12));
     }
 
+    #[test]
+    fn in_range_matcher() {
+        let matcher = p!(in_range, 9, 11);
+        assert!(!matcher(&8));
+        assert!(matcher(&9));
+        assert!(matcher(&10));
+        assert!(!matcher(&11));
+        assert!(!matcher(&12));
+    }
+
+    #[test]
+    fn in_range_inc_exc_matcher() {
+        let matcher = p!(in_range_inc_exc, 9, 11);
+        assert!(!matcher(&8));
+        assert!(matcher(&9));
+        assert!(matcher(&10));
+        assert!(!matcher(&11));
+        assert!(!matcher(&12));
+    }
+
+    #[test]
+    fn in_range_exc_inc_matcher() {
+        let matcher = p!(in_range_exc_inc, 9, 11);
+        assert!(!matcher(&8));
+        assert!(!matcher(&9));
+        assert!(matcher(&10));
+        assert!(matcher(&11));
+        assert!(!matcher(&12));
+    }
+
     #[test]
     fn between_inc_matcher() {
         let matcher = p!(between_inc, 9, 11);
@@ -329,6 +717,70 @@ mod tests {
         assert!(!matcher(&12));
     }
 
+    #[derive(Clone, Debug, PartialEq, PartialOrd)]
+    struct Money(i64);
+
+    impl Sub for Money {
+        type Output = Money;
+
+        fn sub(self, other: Money) -> Money {
+            Money(self.0 - other.0)
+        }
+    }
+
+    #[test]
+    fn within_matcher() {
+        let matcher = p!(within, Money(1000), Money(1));
+        assert!(!matcher(&Money(998)));
+        assert!(matcher(&Money(999)));
+        assert!(matcher(&Money(1000)));
+        assert!(matcher(&Money(1001)));
+        assert!(!matcher(&Money(1002)));
+
+        let matcher = p!(within, 10u32, 2u32);
+        assert!(!matcher(&7u32));
+        assert!(matcher(&8u32));
+        assert!(matcher(&12u32));
+        assert!(!matcher(&13u32));
+    }
+
+    #[test]
+    fn approx_matcher() {
+        let matcher = p!(approx, Money(1000), |a: &Money, b: &Money| (a.0 - b.0).abs() <= 1);
+        assert!(!matcher(&Money(998)));
+        assert!(matcher(&Money(999)));
+        assert!(matcher(&Money(1001)));
+        assert!(!matcher(&Money(1002)));
+    }
+
+    #[test]
+    fn one_of_matcher() {
+        let matcher = p!(one_of, vec![1, 2, 3]);
+        assert!(!matcher(&0));
+        assert!(matcher(&1));
+        assert!(matcher(&2));
+        assert!(matcher(&3));
+        assert!(!matcher(&4));
+
+        let empty_matcher = p!(one_of, Vec::<i32>::new());
+        assert!(!empty_matcher(&0));
+        assert!(!empty_matcher(&1));
+    }
+
+    #[test]
+    fn none_of_matcher() {
+        let matcher = p!(none_of, vec![1, 2, 3]);
+        assert!(matcher(&0));
+        assert!(!matcher(&1));
+        assert!(!matcher(&2));
+        assert!(!matcher(&3));
+        assert!(matcher(&4));
+
+        let empty_matcher = p!(none_of, Vec::<i32>::new());
+        assert!(empty_matcher(&0));
+        assert!(empty_matcher(&1));
+    }
+
     #[test]
     fn is_some_matcher() {
         let matcher = p!(is_some, p!(gt, 5));
@@ -353,6 +805,46 @@ mod tests {
         assert!(!matcher(&Ok(150.75)));
     }
 
+    #[test]
+    fn is_none_matcher() {
+        let (some, none): (Option<i32>, Option<i32>) = (Some(0), None);
+        let matcher = p!(is_none);
+        assert!(matcher(&none));
+        assert!(!matcher(&some));
+    }
+
+    #[test]
+    fn is_some_any_matcher() {
+        let (some, none): (Option<i32>, Option<i32>) = (Some(0), None);
+        let matcher = p!(is_some_any);
+        assert!(matcher(&some));
+        assert!(!matcher(&none));
+    }
+
+    #[test]
+    fn is_ok_any_matcher() {
+        let (ok, err): (Result<i32, &str>, Result<i32, &str>) = (Ok(-1), Err("boo"));
+        let matcher = p!(is_ok_any);
+        assert!(matcher(&ok));
+        assert!(!matcher(&err));
+    }
+
+    #[test]
+    fn is_err_any_matcher() {
+        let (ok, err): (Result<i32, &str>, Result<i32, &str>) = (Ok(-1), Err("boo"));
+        let matcher = p!(is_err_any);
+        assert!(matcher(&err));
+        assert!(!matcher(&ok));
+    }
+
+    #[test]
+    fn variant_matchers_compose() {
+        let (some, none): (Option<i32>, Option<i32>) = (Some(3), None);
+        let matcher = p!(any_of, vec!(p!(is_none), p!(is_some, p!(gt, 5))));
+        assert!(matcher(&none));
+        assert!(!matcher(&some));
+    }
+
     #[test]
     fn f32_eq_matcher() {
         let matcher = p!(f32_eq, 42.5572f32);
@@ -409,6 +901,28 @@ mod tests {
         assert!(nan_matcher(&f64::NAN));
     }
 
+    #[test]
+    fn f32_near_matcher() {
+        let matcher = p!(f32_near, 10.0f32, 0.5f32);
+        assert!(!matcher(&9.4f32));
+        assert!(matcher(&9.5f32));
+        assert!(matcher(&10.0f32));
+        assert!(matcher(&10.5f32));
+        assert!(!matcher(&10.6f32));
+        assert!(!matcher(&f32::NAN));
+    }
+
+    #[test]
+    fn f64_near_matcher() {
+        let matcher = p!(f64_near, 10.0f64, 0.5f64);
+        assert!(!matcher(&9.4f64));
+        assert!(matcher(&9.5f64));
+        assert!(matcher(&10.0f64));
+        assert!(matcher(&10.5f64));
+        assert!(!matcher(&10.6f64));
+        assert!(!matcher(&f64::NAN));
+    }
+
     #[test]
     fn contains_matcher() {
         let empty_matcher = p!(contains, "");
@@ -475,6 +989,138 @@ mod tests {
         assert!(matcher("barFOO"));
     }
 
+    #[test]
+    fn has_substr_nocase_matcher() {
+        let empty_matcher = p!(has_substr_nocase, "");
+        assert!(empty_matcher(""));
+        assert!(empty_matcher("FOO"));
+
+        let matcher = p!(has_substr_nocase, "Foo");
+        assert!(!matcher(""));
+        assert!(matcher("foo"));
+        assert!(matcher("barFOOban"));
+        assert!(!matcher("ban"));
+
+        let unicode_matcher = p!(has_substr_nocase, "STRASSE");
+        assert!(!unicode_matcher("Hauptstraße"));
+        let unicode_matcher = p!(has_substr_nocase, "STRAßE");
+        assert!(unicode_matcher("Hauptstraße"));
+    }
+
+    #[test]
+    fn starts_with_nocase_matcher() {
+        let matcher = p!(starts_with_nocase, "Foo");
+        assert!(!matcher(""));
+        assert!(matcher("FOObar"));
+        assert!(matcher("foo"));
+        assert!(!matcher("barfoo"));
+
+        let unicode_matcher = p!(starts_with_nocase, "ÉCOLE");
+        assert!(unicode_matcher("école primaire"));
+    }
+
+    #[test]
+    fn ends_with_nocase_matcher() {
+        let matcher = p!(ends_with_nocase, "Ban");
+        assert!(!matcher(""));
+        assert!(matcher("fooBAN"));
+        assert!(matcher("ban"));
+        assert!(!matcher("banfoo"));
+
+        let unicode_matcher = p!(ends_with_nocase, "STRAßE");
+        assert!(unicode_matcher("Hauptstraße"));
+        assert!(!unicode_matcher("Hauptstrasse"));
+    }
+
+    #[test]
+    fn sum_is_matcher() {
+        let (exact, close, short, empty) =
+            (vec!(1.0, 2.0, 3.0, 4.0), vec!(9.995), vec!(1.0, 2.0, 3.0), vec!());
+        let matcher = p!(sum_is, p!(f64_near, 10.0, 0.01));
+        assert!(matcher(&exact));
+        assert!(matcher(&close));
+        assert!(!matcher(&short));
+        assert!(!matcher(&empty));
+
+        let (zero_sum, non_zero_sum, no_ints) = (vec!(-5, 5), vec!(5), vec!());
+        let int_matcher = p!(sum_is, p!(eq, 0));
+        assert!(int_matcher(&zero_sum));
+        assert!(!int_matcher(&non_zero_sum));
+        assert!(int_matcher(&no_ints));
+    }
+
+    #[test]
+    fn avg_is_matcher() {
+        let (exact, short) = (vec!(1.0, 2.0, 3.0, 4.0), vec!(1.0, 2.0, 3.0));
+        let matcher = p!(avg_is, p!(f64_near, 2.5, 0.01));
+        assert!(matcher(&exact));
+        assert!(!matcher(&short));
+
+        let (ints_matching, ints_not_matching) = (vec!(2u8, 3u8), vec!(2u8, 2u8));
+        let int_matcher = p!(avg_is, p!(f64_near, 2.5, 0.01));
+        assert!(int_matcher(&ints_matching));
+        assert!(!int_matcher(&ints_not_matching));
+    }
+
+    #[test]
+    fn avg_is_matcher_empty_collection_never_matches() {
+        let empty: Vec<f64> = vec!();
+        let matcher = p!(avg_is, p!(any));
+        assert!(!matcher(&empty));
+    }
+
+    #[test]
+    fn seq_starts_with_matcher() {
+        let (arg, other, short, empty) = (vec!(1, 2, 3), vec!(2, 1, 3), vec!(1), vec!());
+        let matcher = p!(seq_starts_with, vec![1, 2]);
+        assert!(matcher(&arg));
+        assert!(!matcher(&other));
+        assert!(!matcher(&short));
+        assert!(!matcher(&empty));
+
+        let empty_matcher = p!(seq_starts_with, vec!());
+        assert!(empty_matcher(&arg));
+        assert!(empty_matcher(&empty));
+    }
+
+    #[test]
+    fn seq_ends_with_matcher() {
+        let (arg, other, short, empty) = (vec!(1, 2, 3), vec!(1, 3, 2), vec!(3), vec!());
+        let matcher = p!(seq_ends_with, vec![2, 3]);
+        assert!(matcher(&arg));
+        assert!(!matcher(&other));
+        assert!(!matcher(&short));
+        assert!(!matcher(&empty));
+
+        let empty_matcher = p!(seq_ends_with, vec!());
+        assert!(empty_matcher(&arg));
+        assert!(empty_matcher(&empty));
+    }
+
+    #[test]
+    fn seq_contains_subsequence_matcher() {
+        let (arg, exact, reversed, short, empty) =
+            (vec!(1, 2, 3), vec!(1, 3), vec!(3, 2, 1), vec!(1), vec!());
+        let matcher = p!(seq_contains_subsequence, vec![1, 3]);
+        assert!(matcher(&arg));
+        assert!(matcher(&exact));
+        assert!(!matcher(&reversed));
+        assert!(!matcher(&short));
+        assert!(!matcher(&empty));
+
+        let reversed_matcher = p!(seq_contains_subsequence, vec![3, 1]);
+        assert!(!reversed_matcher(&arg));
+
+        let repeated = vec!(1, 2, 1);
+        let repeated_matcher = p!(seq_contains_subsequence, vec![1, 1]);
+        assert!(!repeated_matcher(&arg));
+        assert!(repeated_matcher(&repeated));
+
+        let empty_matcher = p!(seq_contains_subsequence, vec!());
+        assert!(empty_matcher(&arg));
+        assert!(empty_matcher(&empty));
+    }
+
     #[test]
     fn not_matcher() {
         let matcher = p!(not, p!(eq, 10));
@@ -509,4 +1155,112 @@ mod tests {
         assert!(!matcher(&42));  // matches none
     }
 
+    #[test]
+    fn none_of_matchers_matcher() {
+        let matcher = p!(none_of_matchers, vec!(
+            p!(eq, 26),
+            p!(le, 40)
+        ));
+        assert!(!matcher(&0));   // matches one
+        assert!(!matcher(&26));  // matches both
+        assert!(matcher(&42));   // matches none
+
+        let empty_matcher = p!(none_of_matchers, Vec::<&dyn Fn(&i32) -> bool>::new());
+        assert!(empty_matcher(&0));
+    }
+
+    #[test]
+    fn matcher_with_explicit_matchers() {
+        let matcher = matcher!(p!(eq, 5), p!(gt, 2));
+        assert!(matcher(&(5, 3)));
+        assert!(!matcher(&(5, 2)));
+        assert!(!matcher(&(4, 3)));
+    }
+
+    #[test]
+    fn matcher_with_wildcards_literals_and_explicit_matchers() {
+        let matcher = matcher!(_, _, 5, p!(gt, 10), _);
+        assert!(matcher(&(1, "a", 5, 11, 2.5)));
+        assert!(matcher(&(2, "b", 5, 20, -1.0)));
+        assert!(!matcher(&(1, "a", 6, 11, 2.5)));
+        assert!(!matcher(&(1, "a", 5, 10, 2.5)));
+
+        let matcher = matcher!(-1, _, "x");
+        assert!(matcher(&(-1, true, "x")));
+        assert!(!matcher(&(1, true, "x")));
+        assert!(!matcher(&(-1, false, "y")));
+    }
+
+    #[test]
+    fn matcher_with_twelve_args() {
+        let matcher = matcher!(
+            p!(eq, 1), p!(eq, 2), p!(eq, 3), p!(eq, 4), p!(eq, 5), p!(eq, 6),
+            p!(eq, 7), p!(eq, 8), p!(eq, 9), p!(eq, 10), p!(eq, 11), p!(eq, "twelve"));
+        assert!(matcher(&(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, "twelve")));
+        assert!(!matcher(&(1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 11, "twelve")));
+        assert!(!matcher(&(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, "eleven")));
+    }
+
+    #[test]
+    fn matcher_with_thirteen_args() {
+        let matcher = matcher!(
+            p!(eq, 1), p!(eq, 2), p!(eq, 3), p!(eq, 4), p!(eq, 5), p!(eq, 6),
+            p!(eq, 7), p!(eq, 8), p!(eq, 9), p!(eq, 10), p!(eq, 11), p!(eq, 12),
+            p!(eq, "thirteen"));
+        assert!(matcher(&(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, "thirteen")));
+        assert!(!matcher(&(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, "fourteen")));
+        assert!(!matcher(&(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 0, 12, "thirteen")));
+    }
+
+    #[test]
+    fn matcher_with_sixteen_args() {
+        let matcher = matcher!(_, _, _, _, _, _, _, _, _, _, _, _, _, _, _, 16);
+        assert!(matcher(&(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16)));
+        assert!(!matcher(&(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 0)));
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Address {
+        city: &'static str,
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct User {
+        id: u32,
+        name: &'static str,
+        address: Address,
+    }
+
+    #[test]
+    fn field_matcher() {
+        let alice = User { id: 42, name: "Alice", address: Address { city: "Paris" } };
+        let bob = User { id: 42, name: "Bob", address: Address { city: "Oslo" } };
+
+        let matcher: &dyn Fn(&User) -> bool = p!(all_of, vec!(
+            p_field!(id, p!(eq, 42)),
+            p_field!(name, p!(eq, "Alice"))
+        ));
+        assert!(matcher(&alice));
+        assert!(!matcher(&bob));
+
+        let nested: &dyn Fn(&User) -> bool = p_field!(address.city, p!(ne, "Paris"));
+        assert!(!nested(&alice));
+        assert!(nested(&bob));
+
+        type Record = ((i32, &'static str), User);
+        let tuple_matcher: &dyn Fn(&Record) -> bool = matcher!(p_field!(0, p!(lt, 10)), p_field!(id, p!(eq, 42)));
+        assert!(tuple_matcher(&((5, "five"), bob.clone())));
+        assert!(!tuple_matcher(&((15, "fifteen"), bob)));
+    }
+
+    #[test]
+    fn matcher_with_single_wildcard_or_literal() {
+        let wildcard = matcher!(_);
+        assert!(wildcard(&42));
+
+        let literal = matcher!(42);
+        assert!(literal(&42));
+        assert!(!literal(&41));
+    }
+
 }
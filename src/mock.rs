@@ -1,17 +1,403 @@
 extern crate lazysort;
 
+use std::any::Any;
+use std::borrow::Cow;
+#[cfg(not(feature = "sync-mocks"))]
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::ffi::{OsStr, OsString};
 use std::fmt;
 use std::fmt::{Debug, Formatter};
+use std::future;
 use std::hash::Hash;
-use std::iter::FromIterator;
+#[cfg(not(feature = "sync-mocks"))]
 use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "sync-mocks")]
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::thread;
+#[cfg(feature = "sync-mocks")]
+use std::time::{Duration, Instant};
 use self::lazysort::SortedBy;
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "serde")]
+use serde::Serialize;
 
-type Ref<T> = Rc<RefCell<T>>;
-type OptionalRef<T> = Rc<RefCell<Option<T>>>;
+use crate::failure;
+use crate::failure::CallMismatch;
+#[cfg(feature = "sync-mocks")]
+use crate::failure::WaitTimeout;
+
+// With the `sync-mocks` feature, a `Mock`'s state is stored behind `Arc`s
+// and `Mutex`es instead of `Rc`s and `RefCell`s, so mocks are `Send` and
+// `Sync`. `Lock` gives `Mutex` the same `borrow()` and `borrow_mut()` methods
+// as `RefCell`, so the rest of the module doesn't depend on the feature.
+#[cfg(not(feature = "sync-mocks"))]
+type Shared<T> = Rc<T>;
+#[cfg(not(feature = "sync-mocks"))]
+type Cell<T> = RefCell<T>;
+#[cfg(feature = "sync-mocks")]
+type Shared<T> = Arc<T>;
+#[cfg(feature = "sync-mocks")]
+type Cell<T> = Mutex<T>;
+// What `borrow()` returns.
+#[cfg(not(feature = "sync-mocks"))]
+type Borrowed<'a, T> = std::cell::Ref<'a, T>;
+#[cfg(feature = "sync-mocks")]
+type Borrowed<'a, T> = MutexGuard<'a, T>;
+
+#[cfg(feature = "sync-mocks")]
+trait Lock<T> {
+    fn borrow(&self) -> MutexGuard<'_, T>;
+    fn borrow_mut(&self) -> MutexGuard<'_, T>;
+}
+
+#[cfg(feature = "sync-mocks")]
+impl<T> Lock<T> for Mutex<T> {
+    // A `Mock` configured to panic does so while holding a lock, which
+    // poisons it. The mock's state is still consistent, so it's used anyway.
+    fn borrow(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn borrow_mut(&self) -> MutexGuard<'_, T> {
+        self.borrow()
+    }
+}
+
+// The type of a closure configured by the user, which must be `Send` and
+// `Sync` with the `sync-mocks` feature.
+#[cfg(not(feature = "sync-mocks"))]
+macro_rules! dyn_fn {
+    ($($fn_type:tt)*) => { dyn $($fn_type)* };
+}
+#[cfg(feature = "sync-mocks")]
+macro_rules! dyn_fn {
+    ($($fn_type:tt)*) => { dyn $($fn_type)* + Send + Sync };
+}
+
+/// Implemented by every type, or by types that are `Send` and `Sync` if the
+/// `sync-mocks` feature is enabled. Used by methods that store values in
+/// closures, which need to be thread-safe when mocks are.
+#[cfg(not(feature = "sync-mocks"))]
+pub trait ThreadSafe {}
+#[cfg(not(feature = "sync-mocks"))]
+impl<T> ThreadSafe for T {}
+
+/// Implemented by every type, or by types that are `Send` and `Sync` if the
+/// `sync-mocks` feature is enabled. Used by methods that store values in
+/// closures, which need to be thread-safe when mocks are.
+#[cfg(feature = "sync-mocks")]
+pub trait ThreadSafe: Send + Sync {}
+#[cfg(feature = "sync-mocks")]
+impl<T: Send + Sync> ThreadSafe for T {}
+
+/// Converts the arguments passed to a `Mock` method such as `called_with`
+/// into the mock's argument type `C`.
+///
+/// Arguments are converted with `Into`, so `"a"` can be passed to a mock
+/// that takes a `String`. A tuple of arguments is also converted element by
+/// element, so `("a", "b")` can be passed to a mock that takes
+/// `(String, String)`. Each element is converted the same way `mock_method!`
+/// converts an argument to its owned type (see `IntoOwnedArg`).
+///
+/// `M` is `ConvertWhole` or `ConvertElements`, and is always inferred. It
+/// keeps the two kinds of conversion from overlapping.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be converted into the mock's arguments `{C}`",
+    note = "arguments must implement `Into<{C}>`, or be a tuple of the borrowed versions of \
+            the arguments, e.g. `(&str, &str)` for `(String, String)`"
+)]
+pub trait IntoCallArgs<C, M> {
+    fn into_call_args(self) -> C;
+}
+
+/// Selects the `IntoCallArgs` impl that converts the arguments with `Into`.
+#[doc(hidden)]
+pub enum ConvertWhole {}
+
+/// Selects the `IntoCallArgs` impls that convert a tuple element by element.
+#[doc(hidden)]
+pub enum ConvertElements {}
+
+impl<T: Into<C>, C> IntoCallArgs<C, ConvertWhole> for T {
+    fn into_call_args(self) -> C {
+        self.into()
+    }
+}
+
+/// Converts one element of a tuple of arguments. `Converts` is `Converted`
+/// unless the element is already of type `T`.
+#[doc(hidden)]
+pub trait IntoCallArg<T> {
+    type Converts;
+    fn into_call_arg(self) -> T;
+}
+
+#[doc(hidden)]
+pub enum Converted {}
+
+#[doc(hidden)]
+pub enum Unchanged {}
+
+/// Implemented by nested pairs of `Converts` flags, such as
+/// `(Unchanged, (Converted, ()))`, if any of them is `Converted`.
+#[doc(hidden)]
+pub trait AnyConverted {}
+
+impl<T> AnyConverted for (Converted, T) {}
+
+impl<T: AnyConverted> AnyConverted for (Unchanged, T) {}
+
+impl<T> IntoCallArg<T> for T {
+    type Converts = Unchanged;
+    fn into_call_arg(self) -> T {
+        self
+    }
+}
+
+impl<T: Clone> IntoCallArg<T> for &T {
+    type Converts = Converted;
+    fn into_call_arg(self) -> T {
+        self.clone()
+    }
+}
+
+impl IntoCallArg<String> for &str {
+    type Converts = Converted;
+    fn into_call_arg(self) -> String {
+        self.to_owned()
+    }
+}
+
+impl<T: Clone> IntoCallArg<Vec<T>> for &[T] {
+    type Converts = Converted;
+    fn into_call_arg(self) -> Vec<T> {
+        self.to_vec()
+    }
+}
+
+impl IntoCallArg<Vec<String>> for &[&str] {
+    type Converts = Converted;
+    fn into_call_arg(self) -> Vec<String> {
+        self.iter().map(|part| (*part).to_owned()).collect()
+    }
+}
+
+impl IntoCallArg<PathBuf> for &Path {
+    type Converts = Converted;
+    fn into_call_arg(self) -> PathBuf {
+        self.to_path_buf()
+    }
+}
+
+impl IntoCallArg<OsString> for &OsStr {
+    type Converts = Converted;
+    fn into_call_arg(self) -> OsString {
+        self.to_os_string()
+    }
+}
+
+impl IntoCallArg<String> for Cow<'_, str> {
+    type Converts = Converted;
+    fn into_call_arg(self) -> String {
+        self.into_owned()
+    }
+}
+
+impl<T: Clone> IntoCallArg<Vec<T>> for Cow<'_, [T]> {
+    type Converts = Converted;
+    fn into_call_arg(self) -> Vec<T> {
+        self.into_owned()
+    }
+}
+
+impl IntoCallArg<PathBuf> for Cow<'_, Path> {
+    type Converts = Converted;
+    fn into_call_arg(self) -> PathBuf {
+        self.into_owned()
+    }
+}
+
+impl IntoCallArg<OsString> for Cow<'_, OsStr> {
+    type Converts = Converted;
+    fn into_call_arg(self) -> OsString {
+        self.into_owned()
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/mock_generated.rs"));
+
+type Ref<T> = Shared<Cell<T>>;
+type OptionalRef<T> = Shared<Cell<Option<T>>>;
+// A function configured with `use_fn` or `use_fn_for`.
+type ReturnFn<C, R> = fn(C) -> R;
+// Closures are stored in `Shared`s so they can be shared by deep clones.
+type CallHook<C> = (Option<C>, Shared<dyn_fn!(Fn(&C))>);
+type CallObserver<C> = Shared<dyn_fn!(Fn(&C))>;
+// Checks that calls with the given args were made, e.g. `Mock::check_has_calls`.
+type ExpectationsCheck<C, R> = fn(&Mock<C, R>, Vec<C>) -> Result<(), CallMismatch>;
+// Transforms a call's args into the args to record.
+type CallRecorder<C> = Shared<dyn_fn!(Fn(&C) -> C)>;
+// A `LiveHandlerFn` for the type of live args it was configured with.
+type LiveHandler = Shared<dyn_fn!(Any)>;
+type LiveHandlerFn<C, L> = Shared<dyn_fn!(Fn(C, L))>;
+// The value of the type that's returned by methods returning `Self`.
+type SelfPrototype = Shared<dyn_fn!(Any)>;
+// Notified after each call, to wake threads waiting for calls.
+#[cfg(feature = "sync-mocks")]
+type CallSignal = (Mutex<()>, Condvar);
+// Priority, pattern and the value to return for calls matching the pattern.
+type PatternReturnValue<C, R> = (i32, Shared<dyn_fn!(Fn(&C) -> bool)>, R);
+
+// A closure that determines the `Mock`'s return value.
+enum Closure<C, R> {
+    Args(Shared<dyn_fn!(Fn(C) -> R)>),
+    // Also passed the zero-based index of the call.
+    Indexed(Shared<dyn_fn!(Fn(usize, C) -> R)>),
+}
+
+impl<C, R> Closure<C, R> {
+    fn call(&self, call_index: usize, args: C) -> R {
+        match *self {
+            Closure::Args(ref closure) => closure(args),
+            Closure::Indexed(ref closure) => closure(call_index, args),
+        }
+    }
+}
+
+impl<C, R> Clone for Closure<C, R> {
+    fn clone(&self) -> Self {
+        match *self {
+            Closure::Args(ref closure) => Closure::Args(closure.clone()),
+            Closure::Indexed(ref closure) => Closure::Indexed(closure.clone()),
+        }
+    }
+}
+
+// Shared by every `Mock` in the process, so the timestamps of calls to
+// different mocks can be compared.
+static NEXT_CALL_TIMESTAMP: AtomicUsize = AtomicUsize::new(0);
+
+fn next_call_timestamp() -> usize {
+    NEXT_CALL_TIMESTAMP.fetch_add(1, Ordering::SeqCst)
+}
+
+fn copy_of<T: Clone>(value: &Ref<T>) -> Ref<T> {
+    Ref::new(Cell::new(value.borrow().clone()))
+}
+
+// Moves the value out of `value` if no other `Ref` shares it, or copies it
+// otherwise.
+fn into_value<T: Clone>(value: Ref<T>) -> T {
+    match Shared::try_unwrap(value) {
+        #[cfg(not(feature = "sync-mocks"))]
+        Ok(cell) => cell.into_inner(),
+        #[cfg(feature = "sync-mocks")]
+        Ok(cell) => cell.into_inner().unwrap_or_else(PoisonError::into_inner),
+        Err(value) => value.borrow().clone(),
+    }
+}
+
+// The call history of a `Mock` with argument interning enabled. Each
+// distinct set of recorded args is stored once, and each call stores the
+// index of its args.
+struct InternedArgs<C> {
+    args: Vec<C>,
+    indices: HashMap<C, usize>,
+    // Parallel to `Mock::returned_values`.
+    calls: Vec<usize>,
+}
+
+impl<C: Clone + Eq + Hash> InternedArgs<C> {
+    fn new() -> Self {
+        InternedArgs { args: vec![], indices: HashMap::new(), calls: vec![] }
+    }
+
+    // Records a call with `args`, which are only copied if they haven't
+    // been seen before.
+    fn record(&mut self, args: &C) {
+        let index = match self.indices.get(args) {
+            Some(&index) => index,
+            None => {
+                self.args.push(args.clone());
+                self.indices.insert(args.clone(), self.args.len() - 1);
+                self.args.len() - 1
+            }
+        };
+        self.calls.push(index);
+    }
+
+    fn calls(&self) -> Vec<C> {
+        self.calls.iter().map(|&index| self.args[index].clone()).collect()
+    }
+}
+
+impl<C: Debug> Debug for InternedArgs<C> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("InternedArgs")
+            .field("args", &self.args)
+            .field("calls", &self.calls)
+            .finish()
+    }
+}
+
+// A `Mock`'s recorded calls. They're borrowed, unless argument interning is
+// enabled, in which case they're copied out of the interned args when read.
+enum RecordedCalls<'a, C> {
+    Borrowed(Borrowed<'a, Vec<C>>),
+    Materialized(Vec<C>),
+}
+
+impl<'a, C: Clone> RecordedCalls<'a, C> {
+    fn into_vec(self) -> Vec<C> {
+        match self {
+            RecordedCalls::Borrowed(calls) => calls.clone(),
+            RecordedCalls::Materialized(calls) => calls,
+        }
+    }
+}
+
+impl<'a, C> std::ops::Deref for RecordedCalls<'a, C> {
+    type Target = [C];
+
+    fn deref(&self) -> &[C] {
+        match self {
+            RecordedCalls::Borrowed(calls) => calls,
+            RecordedCalls::Materialized(calls) => calls,
+        }
+    }
+}
+
+// Marks a `Mock`'s calls as being read by the current thread, until it's
+// dropped.
+struct CallsReader<'a> {
+    reader: &'a Cell<Option<thread::ThreadId>>,
+}
+
+impl<'a> CallsReader<'a> {
+    fn new(reader: &'a Cell<Option<thread::ThreadId>>) -> Self {
+        *reader.borrow_mut() = Some(thread::current().id());
+        CallsReader { reader }
+    }
+}
+
+impl<'a> Drop for CallsReader<'a> {
+    fn drop(&mut self) {
+        *self.reader.borrow_mut() = None;
+    }
+}
+
+// Formats `count` followed by the singular or plural form of a noun.
+fn plural(count: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", count, if count == 1 { singular } else { plural })
+}
 
 /// Used for tracking function call arguments and specifying a predetermined
 /// return value or mock function.
@@ -20,21 +406,91 @@ type OptionalRef<T> = Rc<RefCell<Option<T>>>;
 /// that demonstrate how to use `Mock` for methods that have multiple arguments
 /// as well as methods with argument or return types that do not implement
 /// `Clone`.
-#[derive(Clone)]
 pub struct Mock<C, R>
-    where C: Clone + Eq + Hash,
-          R: Clone
+    where C: Clone + Eq + Hash
 {
     // Ordered from lowest precedence to highest
-    default_return_value: Ref<R>,
-    return_value_sequence: Ref<Vec<R>>,
-    default_fn: OptionalRef<fn(C) -> R>,
-    default_closure: OptionalRef<Box<dyn Fn(C) -> R>>,
+    default_return_value: OptionalRef<R>,
+    // Drained from the front, or repeated forever starting from the value at
+    // the cursor, depending on the mode. The cursor only moves when a cycled
+    // value is returned, and isn't reset with the calls.
+    return_value_sequence: Ref<VecDeque<R>>,
+    sequence_mode: Ref<SequenceMode>,
+    sequence_cursor: Ref<usize>,
+    default_producer: OptionalRef<Shared<dyn_fn!(Fn() -> R)>>,
+    default_fn: OptionalRef<ReturnFn<C, R>>,
+    default_closure: OptionalRef<Closure<C, R>>,
+    return_value_once: OptionalRef<R>,
+    // Ordered from highest priority to lowest.
+    pattern_return_values: Ref<Vec<PatternReturnValue<C, R>>>,
     return_values: Ref<HashMap<C, R>>,
-    fns: Ref<HashMap<C, fn(C) -> R>>,
-    closures: Ref<HashMap<C, Box<dyn Fn(C) -> R>>>,
+    fns: Ref<HashMap<C, ReturnFn<C, R>>>,
+    // Keyed by call index, counting every call.
+    call_return_values: Ref<HashMap<usize, R>>,
+    // Keyed by args, then by the index of the call among calls with the args.
+    nth_call_return_values: Ref<HashMap<C, HashMap<usize, R>>>,
+    closures: Ref<HashMap<C, Closure<C, R>>>,
+    // Number of calls that use other behaviour, and the value returned by
+    // every call after them.
+    failure_after: OptionalRef<(usize, R)>,
+    panic_message: OptionalRef<&'static str>,
+    panic_messages: Ref<HashMap<C, &'static str>>,
+
+    // Used to hand out copies of stored return values. This is only set by
+    // methods that require `R: Clone`, which are also the only methods that
+    // store return values that need to be copied.
+    clone_fn: OptionalRef<fn(&R) -> R>,
+
+    // Calls expected to be made before the next `Mock::checkpoint`, and the
+    // check used to verify them. The check is only set by
+    // `Mock::expect_call`, which requires `C: Debug` to describe failures.
+    expected_calls: Ref<Vec<C>>,
+    expectations_check: OptionalRef<ExpectationsCheck<C, R>>,
 
+    // Hooks run on every call, in registration order. Hooks with args only
+    // run when the call's args match.
+    call_hooks: Ref<Vec<CallHook<C>>>,
+    // Observers run on every call, in registration order, after the hooks.
+    call_observers: Ref<Vec<CallObserver<C>>>,
+    // Passed the args and live args of calls made using
+    // `Mock::call_with_live_args`.
+    live_handler: OptionalRef<LiveHandler>,
+    // Returned by mocked methods which return `Self`. See
+    // `Mock::return_self_prototype`.
+    self_prototype: OptionalRef<SelfPrototype>,
+
+    call_recorder: OptionalRef<CallRecorder<C>>,
+    // If set, only this many of the most recent calls are kept in `calls`
+    // and the vectors parallel to it.
+    max_recorded_calls: OptionalRef<usize>,
+    // Empty while argument interning is enabled, in which case calls are
+    // recorded in `interned_args` instead.
     calls: Ref<Vec<C>>,
+    interned_args: OptionalRef<InternedArgs<C>>,
+    // Number of recorded calls dropped from the start of `calls`.
+    num_dropped_calls: Ref<usize>,
+    // Parallel to `calls`. `None` if the call's return value couldn't be
+    // copied (see `clone_fn`) or the call was recorded without invoking it.
+    returned_values: Ref<Vec<Option<R>>>,
+    // Parallel to `calls`.
+    call_sources: Ref<Vec<BehaviourSource>>,
+    // Parallel to `calls`.
+    call_times: Ref<Vec<u64>>,
+    clock: OptionalRef<Shared<dyn_fn!(Fn() -> u64)>>,
+    // Parallel to `calls`.
+    call_timestamps: Ref<Vec<usize>>,
+    #[cfg(feature = "sync-mocks")]
+    call_signal: Shared<CallSignal>,
+    // Counts every call, including calls whose args were not recorded.
+    num_calls: Ref<usize>,
+    // Counts calls per set of args in `nth_call_return_values`, including
+    // calls whose args were not recorded. Other args are not counted, so
+    // they're not cloned.
+    num_calls_per_args: Ref<HashMap<C, usize>>,
+    // The thread borrowing `calls` in `Mock::with_calls`, if any, so calls
+    // made from inside the borrow can be reported.
+    calls_reader: Ref<Option<thread::ThreadId>>,
+    record_calls: Ref<bool>,
 }
 
 impl<C, R> Mock<C, R>
@@ -43,88 +499,33 @@ impl<C, R> Mock<C, R>
 {
     /// Creates a new `Mock` that will return `return_value`.
     pub fn new<T: Into<R>>(return_value: T) -> Self {
-        Mock {
-            default_return_value: Ref::new(RefCell::new(return_value.into())),
-            return_value_sequence: Ref::new(RefCell::new(Vec::new())),
-            default_fn: OptionalRef::new(RefCell::new(None)),
-            default_closure: OptionalRef::new(RefCell::new(None)),
-            return_values: Ref::new(RefCell::new(HashMap::new())),
-            fns: Ref::new(RefCell::new(HashMap::new())),
-            closures: Ref::new(RefCell::new(HashMap::new())),
-            calls: Ref::new(RefCell::new(vec![])),
-        }
+        let mock = Self::unconfigured();
+        mock.return_value(return_value);
+        mock
     }
 
-    /// Use the `Mock` to return a value, keeping track of the arguments used.
+    /// Creates a new `Mock` that will return `return_value` and whose call
+    /// history already contains `calls`, in order from first to last.
     ///
-    /// If specific behaviour has been configured for a specific set of
-    /// arguments, this will return (in this order of precedence):
-    ///     1. the return value returned by the configured closure
-    ///     2. the return value returned by the configured function
-    ///     3. the configured return value
-    /// If no specific behaviour has been configured for the input argument set,
-    /// the mock falls back to default behaviour, in this order of precedence:
-    ///     1. the return value returned by the default closure (if configured)
-    ///     2. the return value returned by the default function (if configured)
-    ///     3. next return value in default sequence (if sequence is not empty)
-    ///     4. the default return value (always configured)
+    /// This is test infrastructure API, intended for testing assertion helpers
+    /// built on top of `Mock` against synthetic call histories. It requires
+    /// the `testing-internals` feature.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<&str, _>::new("return value");
-    /// assert_eq!(mock.call("something"), "return value");
-    ///
-    /// mock.return_value("different value");
-    /// assert_eq!(mock.call("something"), "different value");
-    ///
-    /// mock.return_values(vec!("one", "two"));
-    /// assert_eq!(mock.call("something"), "one");
-    /// assert_eq!(mock.call("something"), "two");
-    /// assert_eq!(mock.call("something"), "different value");
-    ///
-    /// mock.use_fn(str::trim);
-    /// assert_eq!(mock.call("  test  "), "test");
-    ///
-    /// mock.use_closure(Box::new(|x| x.trim_left()));
-    /// assert_eq!(mock.call("  test  "), "test  ");
+    /// let mock = Mock::<i64, i64>::with_recorded_calls(0, vec!(1, 2, 3));
     ///
-    /// mock.use_fn(str::trim);
-    /// assert_eq!(mock.call("  test  "), "test");
-    ///
-    /// mock.return_value_for("  banana", "tasty");
-    /// assert_eq!(mock.call("  banana"), "tasty");
-    ///
-    /// mock.use_fn_for("  banana  ", str::trim);
-    /// assert_eq!(mock.call("  banana  "), "banana");
-    ///
-    /// mock.use_closure_for("  banana  ", Box::new(|x| x.trim_left()));
-    /// assert_eq!(mock.call("  banana  "), "banana  ");
+    /// assert_eq!(mock.num_calls(), 3);
+    /// assert!(mock.has_calls_exactly_in_order(vec!(1, 2, 3)));
     /// ```
-    pub fn call(&self, args: C) -> R {
-        self.calls.borrow_mut().push(args.clone());
-
-        if let Some(ref closure) = self.closures.borrow().get(&args) {
-            return closure(args)
-        } else if let Some(ref function) = self.fns.borrow().get(&args) {
-            return function(args)
-        } else if let Some(return_value) = self.return_values.borrow().get(&args) {
-            return return_value.clone()
-        } else if let Some(ref default_fn) = *self.default_fn.borrow() {
-            return default_fn(args);
-        } else if let Some(ref default_closure) = *self.default_closure.borrow() {
-            return default_closure(args);
-        } else {
-            // If there are no return values in the value sequence left, fall
-            // back to the configured default value.
-            let ref mut sequence = *self.return_value_sequence.borrow_mut();
-            match sequence.pop() {
-                Some(return_value) => return_value,
-                None => self.default_return_value.borrow().clone()
-            }
-        }
+    #[cfg(feature = "testing-internals")]
+    pub fn with_recorded_calls<T: Into<R>>(return_value: T, calls: Vec<C>) -> Self {
+        let mock = Self::new(return_value);
+        mock.seed_calls(calls);
+        mock
     }
 
     /// Override the default return value.
@@ -140,11 +541,51 @@ impl<C, R> Mock<C, R>
     /// assert_eq!(mock.call("something"), "new value");
     /// ```
     pub fn return_value<T: Into<R>>(&self, value: T) {
-        *self.default_return_value.borrow_mut() = value.into();
+        self.enable_cloning();
+        *self.default_return_value.borrow_mut() = Some(value.into());
+    }
+
+    /// Return `value` by default, replacing any other default behaviour.
+    ///
+    /// `Mock::return_value` only replaces the default return value, which
+    /// has the lowest precedence of all behaviours. If a default function,
+    /// closure or producer was configured, e.g. in an earlier phase of the
+    /// test, it's still used instead of `value`. This clears them, along
+    /// with any return value sequences and one-shot return value, so `value`
+    /// is returned. Behaviour configured for specific arguments is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, i32>::default();
+    /// mock.use_closure(Box::new(|x| x.len() as i32));
+    /// mock.return_value_for("banana", 5);
+    ///
+    /// // the closure takes precedence over the default return value
+    /// mock.return_value(42);
+    /// assert_eq!(mock.call("something"), 9);
+    ///
+    /// mock.use_value_only(42);
+    /// assert_eq!(mock.call("something"), 42);
+    /// assert_eq!(mock.call("banana"), 5);
+    /// ```
+    pub fn use_value_only<T: Into<R>>(&self, value: T) {
+        *self.default_producer.borrow_mut() = None;
+        *self.default_fn.borrow_mut() = None;
+        *self.default_closure.borrow_mut() = None;
+        *self.return_value_once.borrow_mut() = None;
+        self.return_value_sequence.borrow_mut().clear();
+        self.return_value(value);
     }
 
     /// Provide a sequence of default return values. The specified are returned
-    /// in the same order they are specified in `values`.
+    /// in the same order they are specified in `values` (first in, first out),
+    /// one value per call. Once every value has been returned, the `Mock`
+    /// falls back to the default return value.
+    ///
+    /// This is `Mock::return_values_in_mode` with `SequenceMode::Drain`.
     ///
     /// # Examples
     ///
@@ -160,13 +601,77 @@ impl<C, R> Mock<C, R>
     /// assert_eq!(mock.call("farewell"), "default");
     /// ```
     pub fn return_values<T: Into<R>>(&self, values: Vec<T>) {
-        // Reverse so efficient back pop() can be used to extract  the next
-        // value in the sequence
+        self.return_values_in_mode(values, SequenceMode::Drain);
+    }
+
+    /// Provide a sequence of default return values which is repeated forever,
+    /// one value per call.
+    ///
+    /// This is `Mock::return_values_in_mode` with `SequenceMode::Cycle`. The
+    /// position in the sequence is kept by a cursor, which only moves when a
+    /// value from the sequence is returned. Calls which use other behaviour,
+    /// e.g. a value configured for their arguments, don't skip values.
+    /// `Mock::reset_calls` doesn't move the cursor, but
+    /// `Mock::reset_behaviour_cursor` moves it back to the first value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i32, &str>::new("default");
+    /// mock.return_values_cycled(vec!("a", "b", "c"));
+    ///
+    /// let returned: Vec<&str> = (0..7).map(|x| mock.call(x)).collect();
+    /// assert_eq!(returned, vec!("a", "b", "c", "a", "b", "c", "a"));
+    ///
+    /// mock.reset_calls();
+    /// assert_eq!(mock.call(7), "b");
+    ///
+    /// mock.reset_behaviour_cursor();
+    /// assert_eq!(mock.call(8), "a");
+    /// ```
+    pub fn return_values_cycled<T: Into<R>>(&self, values: Vec<T>) {
+        self.return_values_in_mode(values, SequenceMode::Cycle);
+    }
+
+    /// Provide a sequence of default return values, which are returned one
+    /// value per call as described by `mode`.
+    ///
+    /// A `Mock` has a single sequence, so this replaces any sequence
+    /// configured before, whatever its mode, and moves the cursor back to the
+    /// first value. An empty `values` clears the sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    /// use double::mock::SequenceMode;
+    ///
+    /// let mock = Mock::<(), &str>::new("default");
+    /// mock.return_values_in_mode(vec!("ping", "pong"), SequenceMode::Cycle);
+    /// assert_eq!(mock.call(()), "ping");
+    /// assert_eq!(mock.call(()), "pong");
+    /// assert_eq!(mock.call(()), "ping");
+    ///
+    /// mock.return_values_in_mode(vec!("last"), SequenceMode::Drain);
+    /// assert_eq!(mock.call(()), "last");
+    /// assert_eq!(mock.call(()), "default");
+    /// ```
+    pub fn return_values_in_mode<T: Into<R>>(&self, values: Vec<T>, mode: SequenceMode) {
+        self.enable_cloning();
         *self.return_value_sequence.borrow_mut() = values
             .into_iter()
             .map(|r| r.into())
-            .rev()
             .collect();
+        *self.sequence_mode.borrow_mut() = mode;
+        *self.sequence_cursor.borrow_mut() = 0;
+    }
+
+    /// Move the cursor of the sequence configured using
+    /// `Mock::return_values_cycled` back to its first value.
+    pub fn reset_behaviour_cursor(&self) {
+        *self.sequence_cursor.borrow_mut() = 0;
     }
 
     /// Override the return value for a specific set of call arguments.
@@ -183,885 +688,6151 @@ impl<C, R> Mock<C, R>
     /// assert_eq!(mock.call("something"), "new value");
     /// assert_eq!(mock.call("banana"), "tasty");
     /// ```
-    pub fn return_value_for<S: Into<C>, T: Into<R>>(&self, args: S, return_value: T) {
+    pub fn return_value_for<S: IntoCallArgs<C, M>, M, T: Into<R>>(&self, args: S, return_value: T) {
+        self.enable_cloning();
         self.return_values.borrow_mut().insert(
-            args.into(),
+            args.into_call_args(),
             return_value.into());
     }
 
-    /// Specify a function to determine the `Mock`'s return value based on
-    /// the arguments provided to `Mock::call`.
+    /// Returns a copy of the value configured for `args` using
+    /// `Mock::return_value_for`, or `None` if no value was configured for
+    /// `args`. The `Mock` is not called, so no call is recorded.
     ///
-    /// Arguments of `Mock::call` are still tracked.
+    /// This does not determine what `Mock::call` would return for `args`.
+    /// Only values configured for specific arguments are reported. Other
+    /// behaviour, such as default return values, sequences and functions or
+    /// closures configured for `args`, is ignored.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// fn add_two(x: i64) -> i64 {
-    ///     x + 2
-    /// }
-    ///
-    /// let mock = Mock::<i64, i64>::new(10);
-    /// mock.use_fn(add_two);
+    /// let mock = Mock::<&str, &str>::new("original value");
+    /// mock.return_value_for("banana", "tasty");
     ///
-    /// assert_eq!(mock.call(1), 3);
-    /// assert_eq!(mock.call(10), 12);
+    /// assert_eq!(mock.configured_return_for("banana"), Some("tasty"));
+    /// assert_eq!(mock.configured_return_for("apple"), None);
+    /// assert!(!mock.called());
     /// ```
+    pub fn configured_return_for<T: IntoCallArgs<C, M>, M>(&self, args: T) -> Option<R> {
+        self.return_values.borrow().get(&args.into_call_args()).cloned()
+    }
+
+    /// Return `value` when `Mock::call` is called with arguments that match
+    /// `pattern`.
     ///
-    /// For functions with multiple arguments, use a tuple:
+    /// If the arguments match more than one pattern, the value configured
+    /// with the highest `priority` is returned. If those patterns have the
+    /// same priority, the value configured first is returned. Values
+    /// configured for exact arguments using `Mock::return_value_for` take
+    /// precedence over patterns, and patterns take precedence over default
+    /// behaviour.
+    ///
+    /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// fn add((x, y, z): (i64, i64, i64)) -> i64 {
-    ///     x + y + z
-    /// }
-    ///
-    /// let mock = Mock::<(i64, i64, i64), i64>::default();
-    /// mock.use_fn(add);
+    /// let mock = Mock::<i32, &str>::new("default");
+    /// mock.return_value_for_matching(0, Box::new(|x| *x > 0), "positive");
+    /// mock.return_value_for_matching(10, Box::new(|x| *x > 100), "large");
+    /// mock.return_value_for(500, "exact");
     ///
-    /// assert_eq!(mock.call((1, 1, 1)), 3);
-    /// assert_eq!(mock.call((1, 2, 3,)), 6);
+    /// assert_eq!(mock.call(-5), "default");
+    /// assert_eq!(mock.call(5), "positive");
+    /// assert_eq!(mock.call(200), "large");
+    /// assert_eq!(mock.call(500), "exact");
     /// ```
-    pub fn use_fn(&self, default_fn: fn(C) -> R) {
-        *self.default_closure.borrow_mut() = None;
-        *self.default_fn.borrow_mut() = Some(default_fn)
+    pub fn return_value_for_matching<T: Into<R>>(
+        &self,
+        priority: i32,
+        pattern: Box<dyn_fn!(Fn(&C) -> bool)>,
+        value: T)
+    {
+        self.enable_cloning();
+        let mut pattern_return_values = self.pattern_return_values.borrow_mut();
+        let index = pattern_return_values
+            .iter()
+            .position(|&(other_priority, _, _)| other_priority < priority)
+            .unwrap_or_else(|| pattern_return_values.len());
+        pattern_return_values.insert(index, (priority, Shared::from(pattern), value.into()));
     }
 
-    /// Specify a function to determine the `Mock`'s return value based on
-    /// the arguments provided to `Mock::call`. This function will only be
-    /// invoked if the arguments match the specified `args`.
+    /// Return `value` from the call with the given `index` (starting at 0),
+    /// regardless of the call's arguments. Every call counts towards the
+    /// index, including calls whose arguments were not recorded.
     ///
-    /// Arguments of `Mock::call` are still tracked.
+    /// This takes precedence over all other configured behaviour, except for
+    /// closures configured for specific arguments using
+    /// `Mock::use_closure_for`, panics and failures configured using
+    /// `Mock::fail_after`. `Mock::reset_calls` restarts the count, so
+    /// the value is returned again by the `index`th call after a reset.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// fn add_two(x: i64) -> i64 {
-    ///     x + 2
-    /// }
+    /// let mock = Mock::<&str, &str>::new("pending");
+    /// mock.return_values(vec!("first", "second"));
+    /// mock.return_value_for_call(2, "ready");
     ///
-    /// let mock = Mock::<i64, i64>::new(10);
-    /// mock.return_value(42);
-    /// mock.use_fn_for(5, add_two);
+    /// assert_eq!(mock.call("poll"), "first");
+    /// assert_eq!(mock.call("poll"), "second");
+    /// assert_eq!(mock.call("poll"), "ready");
+    /// assert_eq!(mock.call("poll"), "pending");
     ///
-    /// assert_eq!(mock.call(1), 42);  // uses default value
-    /// assert_eq!(mock.call(5), 7);   // uses function since args match
+    /// mock.reset_calls();
+    /// mock.return_values(vec!("first"));
+    ///
+    /// assert_eq!(mock.call("poll"), "first");
+    /// assert_eq!(mock.call("poll"), "pending");
+    /// assert_eq!(mock.call("poll"), "ready");
     /// ```
+    pub fn return_value_for_call<T: Into<R>>(&self, index: usize, value: T) {
+        self.enable_cloning();
+        self.call_return_values.borrow_mut().insert(index, value.into());
+    }
+
+    /// Return the `Mock`'s other configured behaviour from the first `n`
+    /// calls, then return `failure` from every call after them, regardless
+    /// of the calls' arguments. Useful for simulating a dependency that works
+    /// for a while, then breaks.
     ///
-    /// For functions with multiple arguments, use a tuple:
+    /// Every call counts towards `n`, including calls whose arguments were
+    /// not recorded. Once `n` calls have been made, this takes precedence
+    /// over all other configured behaviour, except for panics.
+    /// `Mock::reset_calls` restarts the count.
+    ///
+    /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// fn add((x, y, z): (i64, i64, i64)) -> i64 {
-    ///     x + y + z
-    /// }
+    /// let mock = Mock::<&str, Result<usize, String>>::new(Ok(0));
+    /// mock.return_value_for("hello", Ok(5));
+    /// mock.fail_after(2, Err("disk full".to_owned()));
     ///
-    /// let mock = Mock::<(i64, i64, i64), i64>::default();
-    /// mock.return_value(42);
-    /// mock.use_fn_for((1, 2, 3), add);
-    ///
-    /// assert_eq!(mock.call((1, 1, 1)), 42);
-    /// assert_eq!(mock.call((1, 2, 3)), 6);
+    /// assert_eq!(mock.call("hello"), Ok(5));
+    /// assert_eq!(mock.call("bye"), Ok(0));
+    /// assert_eq!(mock.call("hello"), Err("disk full".to_owned()));
+    /// assert_eq!(mock.call("bye"), Err("disk full".to_owned()));
     /// ```
-    pub fn use_fn_for<T: Into<C>>(&self, args: T, function: fn(C) -> R) {
-        self.fns.borrow_mut().insert(args.into(), function);
+    pub fn fail_after<T: Into<R>>(&self, n: usize, failure: T) {
+        self.enable_cloning();
+        *self.failure_after.borrow_mut() = Some((n, failure.into()));
     }
 
-    /// Specify a closure to determine the `Mock`'s return value based on
-    /// the arguments provided to `Mock::call`.
-    ///
-    /// Arguments of `Mock::call` are still tracked.
+    /// Return `value` from the `nth` call (starting at 0) made with `args`
+    /// only. Other calls with `args` fall back to the `Mock`'s other
+    /// configured behaviour.
+    ///
+    /// Every call with `args` made after a value has first been configured
+    /// for `args` counts towards `nth`, including calls whose arguments were
+    /// not recorded. This takes precedence over all other
+    /// behaviour configured for `args`, except for closures configured using
+    /// `Mock::use_closure_for` and values configured for a call index using
+    /// `Mock::return_value_for_call`. Panics and failures configured using
+    /// `Mock::fail_after` also take precedence. `Mock::reset_calls` restarts
+    /// the count.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<i64, i64>::new(10);
-    /// let add_two = |x| x + 2;
-    /// mock.use_closure(Box::new(add_two));
-    ///
-    /// assert_eq!(mock.call(1), 3);
-    /// assert_eq!(mock.call(10), 12);
-    /// ```
-    ///
-    /// For functions with multiple arguments, use a tuple:
-    ///
-    /// ```
-    /// use double::Mock;
-    ///
-    /// let mock = Mock::<(i64, i64, i64), i64>::default();
-    /// let add = |(x, y, z)| x + y + z;
-    /// mock.use_closure(Box::new(add));
+    /// let mock = Mock::<&str, Result<i32, &str>>::new(Ok(0));
+    /// mock.return_value_for("banana", Ok(1));
+    /// mock.return_value_for_nth("banana", 1, Err("bruised"));
     ///
-    /// assert_eq!(mock.call((1, 1, 1)), 3);
-    /// assert_eq!(mock.call((1, 2, 3,)), 6);
+    /// assert_eq!(mock.call("banana"), Ok(1));
+    /// assert_eq!(mock.call("apple"), Ok(0));
+    /// assert_eq!(mock.call("banana"), Err("bruised"));
+    /// assert_eq!(mock.call("banana"), Ok(1));
     /// ```
-    pub fn use_closure(&self, default_fn: Box<dyn Fn(C) -> R>) {
-        *self.default_fn.borrow_mut() = None;
-        *self.default_closure.borrow_mut() = Some(default_fn)
+    pub fn return_value_for_nth<S: IntoCallArgs<C, M>, M, T: Into<R>>(&self, args: S, nth: usize, value: T) {
+        self.enable_cloning();
+        self.nth_call_return_values.borrow_mut()
+            .entry(args.into_call_args())
+            .or_default()
+            .insert(nth, value.into());
     }
 
-    /// Specify a closure to determine the `Mock`'s return value based on
-    /// the arguments provided to `Mock::call`. This closure will only be
-    /// invoked if the arguments match the specified `args`.
+    /// Returns an independent copy of the `Mock`, with the same configured
+    /// behaviour and an empty call history.
     ///
-    /// Arguments of `Mock::call` are still tracked.
+    /// Unlike `clone()`, configuring or calling the copy does not affect this
+    /// `Mock`, and vice versa. Return values and functions are copied.
+    /// Closures, producers, hooks and observers can't be copied, so the same closures
+    /// are used by both mocks. Replacing a closure on one mock does not
+    /// replace it on the other.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<i64, i64>::new(10);
-    /// let add_two = |x| x + 2;
-    /// mock.return_value(42);
-    /// mock.use_closure_for(10, Box::new(add_two));
-    ///
-    /// assert_eq!(mock.call(1), 42);
-    /// assert_eq!(mock.call(10), 12);
-    /// ```
+    /// let template = Mock::<&str, i32>::new(1);
+    /// template.return_value_for("banana", 2);
     ///
-    /// For functions with multiple arguments, use a tuple:
+    /// let mock = template.deep_clone();
+    /// mock.return_value_for("banana", 3);
+    /// mock.call("banana");
     ///
+    /// assert_eq!(template.call("banana"), 2);
+    /// assert_eq!(mock.call("banana"), 3);
+    /// assert_eq!(template.num_calls(), 1);
+    /// assert_eq!(mock.num_calls(), 2);
     /// ```
-    /// use double::Mock;
+    pub fn deep_clone(&self) -> Self {
+        Mock {
+            default_return_value: copy_of(&self.default_return_value),
+            return_value_sequence: copy_of(&self.return_value_sequence),
+            sequence_mode: copy_of(&self.sequence_mode),
+            sequence_cursor: copy_of(&self.sequence_cursor),
+            default_producer: copy_of(&self.default_producer),
+            default_fn: copy_of(&self.default_fn),
+            default_closure: copy_of(&self.default_closure),
+            return_value_once: copy_of(&self.return_value_once),
+            pattern_return_values: copy_of(&self.pattern_return_values),
+            return_values: copy_of(&self.return_values),
+            fns: copy_of(&self.fns),
+            call_return_values: copy_of(&self.call_return_values),
+            nth_call_return_values: copy_of(&self.nth_call_return_values),
+            closures: copy_of(&self.closures),
+            failure_after: copy_of(&self.failure_after),
+            panic_message: copy_of(&self.panic_message),
+            panic_messages: copy_of(&self.panic_messages),
+            clone_fn: copy_of(&self.clone_fn),
+            expected_calls: copy_of(&self.expected_calls),
+            expectations_check: copy_of(&self.expectations_check),
+            call_hooks: copy_of(&self.call_hooks),
+            call_observers: copy_of(&self.call_observers),
+            live_handler: copy_of(&self.live_handler),
+            self_prototype: copy_of(&self.self_prototype),
+            call_recorder: copy_of(&self.call_recorder),
+            max_recorded_calls: copy_of(&self.max_recorded_calls),
+            calls: Ref::new(Cell::new(vec![])),
+            interned_args: OptionalRef::new(Cell::new(
+                self.interned_args.borrow().as_ref().map(|_| InternedArgs::new()))),
+            num_dropped_calls: Ref::new(Cell::new(0)),
+            returned_values: Ref::new(Cell::new(vec![])),
+            call_sources: Ref::new(Cell::new(vec![])),
+            call_times: Ref::new(Cell::new(vec![])),
+            clock: copy_of(&self.clock),
+            call_timestamps: Ref::new(Cell::new(vec![])),
+            #[cfg(feature = "sync-mocks")]
+            call_signal: Shared::new((Mutex::new(()), Condvar::new())),
+            num_calls: Ref::new(Cell::new(0)),
+            num_calls_per_args: Ref::new(Cell::new(HashMap::new())),
+            calls_reader: Ref::new(Cell::new(None)),
+            record_calls: copy_of(&self.record_calls),
+        }
+    }
+
+    /// Returns the arguments and return value of each call to `Mock::call`,
+    /// in order from first to last.
     ///
-    /// let mock = Mock::<(i64, i64, i64), i64>::default();
-    /// let add = |(x, y, z)| x + y + z;
-    /// mock.return_value(42);
-    /// mock.use_closure_for((1, 2, 3), Box::new(add));
+    /// Return values are recorded for calls made after the `Mock` was
+    /// created with, or configured by, a method that requires `R: Clone`,
+    /// such as `Mock::new`, `Mock::default` or `Mock::return_value`. Calls
+    /// made before then, and calls whose arguments were not recorded, are
+    /// not included.
+    ///
+    /// # Examples
     ///
-    /// assert_eq!(mock.call((1, 1, 1)), 42);
-    /// assert_eq!(mock.call((1, 2, 3)), 6);
     /// ```
-    pub fn use_closure_for<T: Into<C>>(&self, args: T, function: Box<dyn Fn(C) -> R>) {
-        self.closures.borrow_mut().insert(args.into(), function);
-    }
-
-    /// Returns true if `Mock::call` has been called.
     /// use double::Mock;
     ///
-    /// let mock = Mock::<i64, ()>::default();
-    ///
-    /// assert!(!mock.called());
+    /// let mock = Mock::<&str, i32>::new(0);
+    /// mock.return_values(vec!(1, 2));
+    /// mock.return_value_for("banana", 10);
     ///
-    /// mock.call(10);
+    /// mock.call("apple");
+    /// mock.call("banana");
+    /// mock.call("cherry");
     ///
-    /// assert!(mock.called());
+    /// assert_eq!(
+    ///     mock.call_log(),
+    ///     vec!(("apple", 1), ("banana", 10), ("cherry", 2)));
     /// ```
-    pub fn called(&self) -> bool {
-        !self.calls.borrow().is_empty()
+    pub fn call_log(&self) -> Vec<(C, R)> {
+        self.recorded_calls().iter()
+            .zip(self.returned_values.borrow().iter())
+            .filter_map(|(args, returned)| {
+                returned.as_ref().map(|value| (args.clone(), value.clone()))
+            })
+            .collect()
     }
 
-    /// Returns the number of times `Mock::call` has been called.
+    /// Returns the values returned by `Mock::call`, in order from first to
+    /// last. The same calls are included as in `Mock::call_log`.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<i64, i64>::new(0);
+    /// let mock = Mock::<i32, i32>::new(0);
+    /// mock.use_closure(Box::new(|x| x * 2));
     ///
-    /// assert_eq!(mock.num_calls(), 0);
+    /// mock.call(1);
     /// mock.call(5);
-    /// assert_eq!(mock.num_calls(), 1);
-    /// mock.call(10);
-    /// assert_eq!(mock.num_calls(), 2);
+    ///
+    /// assert_eq!(mock.returned_values(), vec!(2, 10));
     /// ```
-    pub fn num_calls(&self) -> usize {
-        self.calls.borrow().len()
+    pub fn returned_values(&self) -> Vec<R> {
+        self.returned_values.borrow().iter()
+            .filter_map(|returned| returned.clone())
+            .collect()
     }
 
-    /// Returns the arguments to `Mock::call` in order from first to last.
+    fn enable_cloning(&self) {
+        *self.clone_fn.borrow_mut() = Some(R::clone);
+    }
+}
+
+impl<C, R> Mock<C, R>
+    where C: Clone + Eq + Hash
+{
+    /// Creates a new `Mock` that constructs a fresh return value using
+    /// `producer` every time it is called.
+    ///
+    /// Unlike `Mock::new`, this does not require `R` to implement `Clone`,
+    /// so it can be used to mock methods that return values that can't be
+    /// copied, such as connections or file handles.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<&str, &str>::new("");
+    /// // Does not implement `Clone`
+    /// #[derive(Debug, PartialEq)]
+    /// struct Connection {
+    ///     id: u32,
+    /// }
     ///
-    /// mock.call("first");
-    /// mock.call("second");
-    /// mock.call("third");
+    /// let mock = Mock::<(), Connection>::with_producer(
+    ///     Box::new(|| Connection { id: 0 }));
+    /// mock.return_value_once(Connection { id: 42 });
     ///
-    /// assert_eq!(mock.calls().as_slice(), ["first", "second", "third"]);
+    /// assert_eq!(mock.call(()), Connection { id: 42 });
+    /// assert_eq!(mock.call(()), Connection { id: 0 });
+    /// assert_eq!(mock.call(()), Connection { id: 0 });
     /// ```
-    pub fn calls(&self) -> Vec<C> {
-        self.calls.borrow().clone()
+    pub fn with_producer(producer: Box<dyn_fn!(Fn() -> R)>) -> Self {
+        let mock = Self::unconfigured();
+        mock.use_producer(producer);
+        mock
     }
 
-    /// Reset the call history for the `Mock`.
+    /// Use the `Mock` to return a value, keeping track of the arguments used.
+    ///
+    /// The configured behaviour used to produce the return value is chosen
+    /// in this order of precedence:
+    ///     1. a panic configured for the arguments, or for any arguments
+    ///     2. the failure configured using `Mock::fail_after`, once enough
+    ///        calls have been made
+    ///     3. the return value returned by the closure configured for the
+    ///        arguments
+    ///     4. the return value configured for the call's index
+    ///     5. the return value configured for the nth call with the arguments
+    ///     6. the return value returned by the function configured for the
+    ///        arguments
+    ///     7. the return value configured for the arguments
+    ///     8. the return value configured for the highest priority pattern
+    ///        that matches the arguments
+    ///     9. the one-shot return value (if configured and not yet returned)
+    ///    10. the return value returned by the default closure (if configured)
+    ///    11. the return value returned by the default function (if configured)
+    ///    12. the return value constructed by the producer (if configured)
+    ///    13. next return value in default sequence (if sequence is not
+    ///        empty), or the return value at the cursor if it's cycled
+    ///    14. the default return value
+    ///
+    /// If none of these are configured, the call panics. The choice made for
+    /// each call is returned by `Mock::call_sources`.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<&str, &str>::default();
+    /// let mock = Mock::<&str, _>::new("return value");
+    /// assert_eq!(mock.call("something"), "return value");
     ///
-    /// mock.call("first");
-    /// mock.call("second");
+    /// mock.return_value("different value");
+    /// assert_eq!(mock.call("something"), "different value");
     ///
-    /// assert!(mock.called());
-    /// assert_eq!(mock.num_calls(), 2);
-    /// assert!(mock.called_with("first"));
-    /// assert!(mock.called_with("second"));
+    /// mock.return_values(vec!("one", "two"));
+    /// assert_eq!(mock.call("something"), "one");
+    /// assert_eq!(mock.call("something"), "two");
+    /// assert_eq!(mock.call("something"), "different value");
     ///
-    /// mock.reset_calls();
+    /// mock.use_fn(str::trim);
+    /// assert_eq!(mock.call("  test  "), "test");
     ///
-    /// assert!(!mock.called());
-    /// assert_eq!(mock.num_calls(), 0);
-    /// assert!(!mock.called_with("first"));
-    /// assert!(!mock.called_with("second"));
+    /// mock.use_closure(Box::new(|x| x.trim_left()));
+    /// assert_eq!(mock.call("  test  "), "test  ");
+    ///
+    /// mock.use_fn(str::trim);
+    /// assert_eq!(mock.call("  test  "), "test");
+    ///
+    /// mock.return_value_for("  banana", "tasty");
+    /// assert_eq!(mock.call("  banana"), "tasty");
+    ///
+    /// mock.use_fn_for("  banana  ", str::trim);
+    /// assert_eq!(mock.call("  banana  "), "banana");
+    ///
+    /// mock.use_closure_for("  banana  ", Box::new(|x| x.trim_left()));
+    /// assert_eq!(mock.call("  banana  "), "banana  ");
     /// ```
-    pub fn reset_calls(&self) {
-        self.calls.borrow_mut().clear()
+    pub fn call(&self, args: C) -> R {
+        if !*self.record_calls.borrow() {
+            return self.call_without_recording(args);
+        }
+        self.assert_calls_not_borrowed("Mock::call");
+
+        // Reserve the call's slot before invoking the mock, so calls made
+        // from inside closures are recorded after this one.
+        let time = self.current_time();
+        let index = self.record_call(&args, time);
+        let result = self.invoke(args, Some(index));
+        let returned = self.clone_fn.borrow().map(|clone_fn| clone_fn(&result));
+        if let Some(position) = self.recorded_position(index) {
+            if let Some(slot) = self.returned_values.borrow_mut().get_mut(position) {
+                *slot = returned;
+            }
+        }
+        self.notify_waiters();
+        result
     }
-}
 
-impl<C, R> Default for Mock<C, R>
-    where C: Clone + Eq + Hash,
-          R: Clone + Default
-{
-    /// Use `R::default()` as the initial return value.
+    /// Use the `Mock` to return a value, like `Mock::call`, but return any
+    /// panic raised by the call as an `Err` instead of unwinding, e.g. if a
+    /// closure configured using `Mock::use_closure` panics.
+    ///
+    /// The call is made inside `std::panic::catch_unwind`, with `args` and
+    /// the `Mock` asserted to be unwind safe. The `Mock`'s own state is
+    /// consistent after a panic, but anything else a panicking closure
+    /// captured may not be. The panic is still reported by the panic hook,
+    /// which prints it to stderr by default, and panics that abort the
+    /// process can't be caught.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<i64, i64>::default();
-    /// assert_eq!(mock.call(10), 0);
-    ///
-    /// let mock = Mock::<(), String>::default();
-    /// assert_eq!(&mock.call(()), "");
+    /// let mock = Mock::<i32, i32>::new(0);
+    /// mock.use_closure(Box::new(|x| if x < 0 { panic!("negative") } else { x }));
     ///
-    /// let mock = Mock::<(i64, &str), Option<bool>>::default();
-    /// assert_eq!(mock.call((10, "test")), None);
+    /// assert_eq!(mock.try_call(5).ok(), Some(5));
+    /// let error = mock.try_call(-1).unwrap_err();
+    /// assert_eq!(error.downcast_ref::<&str>(), Some(&"negative"));
+    /// assert!(mock.called_with(-1));
     /// ```
-    fn default() -> Self {
-        Self::new(R::default())
+    pub fn try_call(&self, args: C) -> thread::Result<R> {
+        panic::catch_unwind(panic::AssertUnwindSafe(|| self.call(args)))
     }
-}
-
-impl<C, R> Mock<C, R>
-    where C: Clone + Debug + Eq + Hash,
-          R: Clone
-{
-    // ========================================================================
-    // * Exact Argument Checks
-    // ========================================================================
 
-    /// Returns true if the specified argument has been used for `Mock::call`.
+    /// Use the `Mock` to return a value, like `Mock::call`, and return it as
+    /// an already completed future.
+    ///
+    /// This is for mocking methods which return futures, such as the methods
+    /// `#[async_trait]` generates for `async fn`s, which return
+    /// `Pin<Box<dyn Future<Output = R> + Send + 'async_trait>>`. The call is
+    /// recorded when `call_ready` is called, not when the future is awaited.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<&str, ()>::new(());
-    /// mock.call("foo");
-    /// mock.call("bar");
+    /// let mock = Mock::<u32, u32>::default();
+    /// mock.return_value_for(42u32, 7u32);
     ///
-    /// assert!(mock.called_with("foo"));
-    /// assert!(mock.called_with("bar"));
-    /// assert!(!mock.called_with("baz"));
+    /// let future = mock.call_ready(42);
+    /// assert!(mock.called_with(42u32));
+    /// assert_eq!(future.into_inner(), 7);
     /// ```
-    pub fn called_with<T: Into<C>>(&self, args: T) -> bool {
-        let expected_calls: Vec<T> = vec!(args);
-        self.get_match_info(expected_calls).expectations_matched()
+    pub fn call_ready(&self, args: C) -> future::Ready<R> {
+        future::ready(self.call(args))
     }
 
-    /// Returns true if `Mock::call` has been called with all of the specified
-    /// `calls`. The calls can be made in any order.  They don't have to be in
-    /// the order specified by `calls`.
+    /// Use the `Mock` to return a value, like `Mock::call`, then apply
+    /// `effect` to `effect_input` and the return value before returning it.
+    ///
+    /// This is for mocking methods with out-parameters, such as
+    /// `fn read_into(&self, buf: &mut Vec<u8>) -> usize`. `Mock` only stores
+    /// copies of arguments, so `recorded` is the copy of the arguments that
+    /// is recorded and used to select the return value, while `effect_input`
+    /// is the caller's real argument, which `effect` can modify.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<&str, ()>::new(());
-    /// mock.call("foo");
-    /// mock.call("bar");
+    /// // Returns the bytes to write into the caller's buffer.
+    /// let mock = Mock::<Vec<u8>, Vec<u8>>::new(vec!(1, 2, 3));
     ///
-    /// let expected_calls1 = vec!("foo", "bar");
-    /// assert!(mock.has_calls(expected_calls1));
-    /// let expected_calls2 = vec!("bar", "foo");
-    /// assert!(mock.has_calls(expected_calls2));
-    /// let expected_calls3 = vec!("foo");
-    /// assert!(mock.has_calls(expected_calls3));
-    /// let expected_calls4 = vec!("not_in_calls");
-    /// assert!(!mock.has_calls(expected_calls4));
-    /// let expected_calls5 = vec!("foo", "not_in_calls");
-    /// assert!(!mock.has_calls(expected_calls5));
+    /// let mut buf = vec!(0);
+    /// let bytes = mock.call_with_effect(
+    ///     buf.clone(),
+    ///     &mut buf,
+    ///     &|buf: &mut Vec<u8>, bytes: &Vec<u8>| buf.extend_from_slice(bytes));
+    ///
+    /// assert_eq!(bytes, vec!(1, 2, 3));
+    /// assert_eq!(buf, vec!(0, 1, 2, 3));
+    /// assert!(mock.called_with(vec!(0)));
     /// ```
-    pub fn has_calls<T: Into<C>>(&self, calls: Vec<T>) -> bool {
-        self.get_match_info(calls).expectations_matched()
+    pub fn call_with_effect<A: ?Sized>(
+        &self,
+        recorded: C,
+        effect_input: &mut A,
+        effect: &dyn Fn(&mut A, &R)) -> R
+    {
+        let result = self.call(recorded);
+        effect(effect_input, &result);
+        result
     }
 
-    /// Returns true if `Mock::call` has been called with all of the specified
-    /// `calls`. The `calls` must be made in the order they are specified in
-    /// the vector.
+    /// Use the `Mock` to return a value, like `Mock::call`, then pass `args`
+    /// and `live_args` to the handler configured using
+    /// `Mock::use_live_handler`, if any.
+    ///
+    /// This is for mocking methods with arguments that can't be recorded,
+    /// such as callbacks, which the mocked method is expected to use. Only
+    /// `args` are recorded, while `live_args` are moved into the handler. If
+    /// there are several live args, they're passed as a tuple.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handler was configured for a different type of live
+    /// args.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<(i32, i32), ()>::new(());
-    /// mock.call((42, 0));
-    /// mock.call((42, 1));
-    /// mock.call((42, 0));  // called with same args as first call!
+    /// let mock = Mock::<String, bool>::new(true);
+    /// mock.use_live_handler(Box::new(|topic: String, on_msg: Box<dyn Fn(String)>| {
+    ///     on_msg(format!("welcome to {}", topic));
+    /// }));
     ///
-    /// assert!(mock.has_calls_in_order(vec!( (42, 0) )));
-    /// assert!(mock.has_calls_in_order(vec!( (42, 1) )));
-    /// assert!(mock.has_calls_in_order(vec!( (42, 0), (42, 1) )));
-    /// assert!(mock.has_calls_in_order(vec!( (42, 1), (42, 0) )));
-    /// assert!(mock.has_calls_in_order(vec!( (42, 0), (42, 1), (42, 0) )));
-    /// assert!(!mock.has_calls_in_order(vec!( (42, 0), (42, 0), (42, 1) )));
-    /// assert!(!mock.has_calls_in_order(vec!( (84, 0) )));
-    /// assert!(!mock.has_calls_in_order(vec!( (42, 0), (84, 0) )));
+    /// let on_msg: Box<dyn Fn(String)> = Box::new(|msg| assert_eq!(msg, "welcome to news"));
+    /// assert!(mock.call_with_live_args("news".to_owned(), on_msg));
+    /// assert!(mock.called_with("news".to_owned()));
     /// ```
-    pub fn has_calls_in_order<T: Into<C>>(&self, calls: Vec<T>) -> bool {
-        self.get_match_info(calls).expectations_matched_in_order()
+    pub fn call_with_live_args<L: 'static>(&self, args: C, live_args: L) -> R
+        where C: 'static
+    {
+        let live_handler = self.live_handler.borrow().clone();
+        let live_handler = match live_handler {
+            Some(live_handler) => live_handler,
+            None => return self.call(args),
+        };
+        let live_handler = live_handler
+            .downcast_ref::<LiveHandlerFn<C, L>>()
+            .expect("Mock::use_live_handler was configured for a different type of live args")
+            .clone();
+        let result = self.call(args.clone());
+        live_handler(args, live_args);
+        result
     }
 
-    /// Returns true if `Mock::call` has been called with all of the specified
-    /// `calls` and it has not been called any other times. The calls can be
-    /// made in any order. They don't have to be in the order specified by
-    /// `calls`.
+    /// Use the `Mock` to return a value without recording the arguments used.
+    ///
+    /// The call still counts towards `Mock::num_calls`, but `args` are not
+    /// cloned, so they will not be returned by `Mock::calls` or checked by
+    /// `Mock::called_with` and the other argument checks. Behaviour is
+    /// selected in the same way as `Mock::call`.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<(i32, i32), ()>::new(());
-    /// mock.call((42, 0));
-    /// mock.call((42, 1));
-    /// mock.call((42, 0));
+    /// let mock = Mock::<&str, &str>::new("default");
+    /// mock.return_value_for("banana", "tasty");
     ///
-    /// assert!(!mock.has_calls_exactly(vec!( (42, 0) )));
-    /// assert!(!mock.has_calls_exactly(vec!( (42, 1) )));
-    /// assert!(!mock.has_calls_exactly(vec!( (84, 0) )));
-    /// assert!(!mock.has_calls_exactly(vec!( (42, 0), (42, 1) )));
-    /// assert!(!mock.has_calls_exactly(vec!( (42, 1), (42, 0) )));
-    /// assert!(mock.has_calls_exactly(vec!( (42, 0), (42, 0), (42, 1) )));
-    /// assert!(mock.has_calls_exactly(vec!( (42, 0), (42, 1), (42, 0) )));
-    /// assert!(!mock.has_calls_exactly(vec!( (42, 0), (42, 1), (84, 0) )));
+    /// assert_eq!(mock.call_without_recording("banana"), "tasty");
+    /// assert_eq!(mock.call_without_recording("apple"), "default");
+    ///
+    /// assert_eq!(mock.num_calls(), 2);
+    /// assert!(mock.calls().is_empty());
     /// ```
-    pub fn has_calls_exactly<T: Into<C>>(&self, calls: Vec<T>) -> bool {
-        self.get_match_info(calls).expectations_matched_exactly()
+    pub fn call_without_recording(&self, args: C) -> R {
+        let result = self.invoke(args, None);
+        self.notify_waiters();
+        result
     }
 
-    /// Returns true if `Mock::call` has been called with all of the specified
-    /// `calls` and it has not been called any other times. The calls must be
-    /// made in the order they are specified in `calls`.
+    /// Returns the configured behaviour that would be used to produce the
+    /// return value if `Mock::call` was called with `args` next. The mock is
+    /// not called, so no call is recorded and no behaviour is invoked.
+    ///
+    /// Hooks registered using `Mock::on_call` and observers registered using
+    /// `Mock::add_call_observer` are invoked before behaviour is chosen, so if they reconfigure the mock, the next call may use
+    /// different behaviour.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
+    /// use double::mock::BehaviourSource;
     ///
-    /// let mock = Mock::<&str, ()>::new(());
-    /// mock.call("foo");
-    /// mock.call("bar");
+    /// let mock = Mock::<&str, &str>::new("default");
+    /// mock.return_values(vec!("first"));
+    /// mock.return_value_for("banana", "tasty");
     ///
-    /// let expected_calls1 = vec!("foo", "bar");
-    /// assert!(mock.has_calls_exactly_in_order(expected_calls1));
-    /// let expected_calls2 = vec!("bar", "foo");
-    /// assert!(!mock.has_calls_exactly_in_order(expected_calls2));
-    /// let expected_calls3 = vec!("foo");
-    /// assert!(!mock.has_calls_exactly_in_order(expected_calls3));
-    /// let expected_calls4 = vec!("bar");
-    /// assert!(!mock.has_calls_exactly_in_order(expected_calls4));
-    pub fn has_calls_exactly_in_order<T: Into<C>>(&self, calls: Vec<T>) -> bool {
-        self.get_match_info(calls).expectations_matched_in_order_exactly()
+    /// assert_eq!(mock.explain_next_call("banana"), BehaviourSource::ValueFor);
+    /// assert_eq!(mock.explain_next_call("apple"), BehaviourSource::Sequence);
+    /// mock.call("apple");
+    /// assert_eq!(mock.explain_next_call("apple"), BehaviourSource::DefaultValue);
+    /// ```
+    pub fn explain_next_call<T: IntoCallArgs<C, M>, M>(&self, args: T) -> BehaviourSource {
+        let args = args.into_call_args();
+        let call_index = *self.num_calls.borrow();
+        let nth_call_with_args = self.num_calls_per_args.borrow()
+            .get(&args)
+            .cloned()
+            .unwrap_or(0);
+        self.behaviour_source(&args, call_index, nth_call_with_args)
     }
 
-    // ========================================================================
-    // * Pattern Matching Argument Checks
-    // ========================================================================
+    // Counts the call, chooses the behaviour to use and invokes it. If the
+    // call was recorded, `index` is its index returned by `record_call`.
+    fn invoke(&self, args: C, index: Option<usize>) -> R {
+        let call_index = *self.num_calls.borrow();
+        *self.num_calls.borrow_mut() += 1;
+        let nth_call_with_args = self.count_call_with_args(&args);
+        self.run_call_hooks(&args);
+        self.run_call_observers(&args);
 
-    // There are apparently plans for the Rust compiler to support associated
-    // types in concrete `impl`s. This would allow the matcher function
-    // signature to be aliased, like below:
-    //
-    // type Matcher = dyn Fn(&C) -> bool;
-    //
-    // TODO: define the above type alias when possible and use that instead of
-    // explicitly defining the function signature everywhere.
+        let source = self.behaviour_source(&args, call_index, nth_call_with_args);
+        if let Some(position) = index.and_then(|index| self.recorded_position(index)) {
+            if let Some(slot) = self.call_sources.borrow_mut().get_mut(position) {
+                *slot = source;
+            }
+        }
 
-    /// Returns true if an argument set passed into `Mock::call` matches the
-    /// specified `pattern`.
+        // Closures and functions are copied out of the mock, releasing its
+        // locks, before they're invoked, so they can call the mock too.
+        match source {
+            BehaviourSource::Panic => {
+                let message = self.panic_messages.borrow().get(&args).cloned()
+                    .or(*self.panic_message.borrow())
+                    .expect("panic message removed");
+                panic!("{}", message)
+            }
+            BehaviourSource::FailAfter => {
+                let failure_after = self.failure_after.borrow();
+                let (_, ref failure) = *failure_after.as_ref().expect("failure removed");
+                self.clone_return_value(failure)
+            }
+            BehaviourSource::ClosureFor => {
+                let closure = self.closures.borrow()[&args].clone();
+                closure.call(call_index, args)
+            }
+            BehaviourSource::ValueForCall => {
+                self.clone_return_value(&self.call_return_values.borrow()[&call_index])
+            }
+            BehaviourSource::ValueForNth => {
+                self.clone_return_value(
+                    &self.nth_call_return_values.borrow()[&args][&nth_call_with_args])
+            }
+            BehaviourSource::FnFor => {
+                let function = self.fns.borrow()[&args];
+                function(args)
+            }
+            BehaviourSource::ValueFor => {
+                self.clone_return_value(&self.return_values.borrow()[&args])
+            }
+            BehaviourSource::Pattern => {
+                self.pattern_return_value(&args).expect("pattern no longer matches")
+            }
+            BehaviourSource::Once => {
+                let return_value = self.return_value_once.borrow_mut().take();
+                return_value.expect("one-shot return value already taken")
+            }
+            BehaviourSource::DefaultClosure => {
+                let default_closure = self.default_closure.borrow().clone();
+                default_closure.expect("default closure removed").call(call_index, args)
+            }
+            BehaviourSource::DefaultFn => {
+                let default_fn = self.default_fn.borrow().expect("default fn removed");
+                default_fn(args)
+            }
+            BehaviourSource::Producer => {
+                let producer = self.default_producer.borrow().clone();
+                producer.expect("producer removed")()
+            }
+            BehaviourSource::Sequence => {
+                let return_value = self.return_value_sequence.borrow_mut().pop_front();
+                return_value.expect("sequence already exhausted")
+            }
+            BehaviourSource::Cycled => {
+                let cycled_values = &*self.return_value_sequence.borrow();
+                let mut cursor = self.sequence_cursor.borrow_mut();
+                let return_value = self.clone_return_value(&cycled_values[*cursor % cycled_values.len()]);
+                *cursor = (*cursor + 1) % cycled_values.len();
+                return_value
+            }
+            BehaviourSource::DefaultValue => match *self.default_return_value.borrow() {
+                Some(ref return_value) => self.clone_return_value(return_value),
+                None => panic!("Mock has no default return value configured"),
+            },
+            BehaviourSource::Unconfigured | BehaviourSource::NotInvoked => {
+                panic!("Mock has no default return value configured")
+            }
+        }
+    }
+
+    // Returns the behaviour `Mock::call` uses for a call with `args`, in the
+    // order of precedence documented by `Mock::call`.
+    fn behaviour_source(&self, args: &C, call_index: usize, nth_call_with_args: usize)
+        -> BehaviourSource
+    {
+        if self.panic_messages.borrow().contains_key(args)
+            || self.panic_message.borrow().is_some()
+        {
+            BehaviourSource::Panic
+        } else if self.failure_after.borrow()
+            .as_ref()
+            .is_some_and(|&(n, _)| call_index >= n)
+        {
+            BehaviourSource::FailAfter
+        } else if self.closures.borrow().contains_key(args) {
+            BehaviourSource::ClosureFor
+        } else if self.call_return_values.borrow().contains_key(&call_index) {
+            BehaviourSource::ValueForCall
+        } else if self.nth_call_return_values.borrow()
+            .get(args)
+            .and_then(|values| values.get(&nth_call_with_args))
+            .is_some()
+        {
+            BehaviourSource::ValueForNth
+        } else if self.fns.borrow().contains_key(args) {
+            BehaviourSource::FnFor
+        } else if self.return_values.borrow().contains_key(args) {
+            BehaviourSource::ValueFor
+        } else if self.pattern_return_values.borrow()
+            .iter()
+            .any(|(_, pattern, _)| pattern(args))
+        {
+            BehaviourSource::Pattern
+        } else if self.return_value_once.borrow().is_some() {
+            BehaviourSource::Once
+        } else if self.default_closure.borrow().is_some() {
+            BehaviourSource::DefaultClosure
+        } else if self.default_fn.borrow().is_some() {
+            BehaviourSource::DefaultFn
+        } else if self.default_producer.borrow().is_some() {
+            BehaviourSource::Producer
+        } else if !self.return_value_sequence.borrow().is_empty() {
+            match *self.sequence_mode.borrow() {
+                SequenceMode::Drain => BehaviourSource::Sequence,
+                SequenceMode::Cycle => BehaviourSource::Cycled,
+            }
+        } else if self.default_return_value.borrow().is_some() {
+            BehaviourSource::DefaultValue
+        } else {
+            BehaviourSource::Unconfigured
+        }
+    }
+
+    /// Return `value` from the next call to `Mock::call` only. Afterwards,
+    /// the `Mock` falls back to its other configured behaviour.
     ///
-    /// A `pattern` is defined a function that receives a tuple containing
-    /// all of a single call's arguments, checks the values of the arguments
-    /// and returns `true` if the args "matched" the pattern and `false`
-    /// otherwise. See the
-    /// [double repository's README.md](https://github.com/DonaldWhyte/double)
-    /// for more information on this.
+    /// The value is moved out of the `Mock` when it is returned, so this can
+    /// be used with return types that do not implement `Clone`. Behaviour
+    /// configured for specific arguments still takes precedence.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<(i32, i32), ()>::new(());
-    /// mock.call((42, 0));
-    /// mock.call((42, 1));
-    ///
-    /// let pattern1 = |args: &(i32, i32)| args.0 == 42 && args.1 != 0;
-    /// let pattern2 = |args: &(i32, i32)| args.0 == 42 && args.1 == 0;
-    /// let pattern3 = |args: &(i32, i32)| args.0 == 84;
+    /// let mock = Mock::<&str, &str>::new("default");
+    /// mock.return_value_once("first");
     ///
-    /// assert!(mock.called_with_pattern(&pattern1));
-    /// assert!(mock.called_with_pattern(&pattern2));
-    /// assert!(!mock.called_with_pattern(&pattern3));
+    /// assert_eq!(mock.call("something"), "first");
+    /// assert_eq!(mock.call("something"), "default");
     /// ```
-    pub fn called_with_pattern(&self, pattern: &dyn Fn(&C) -> bool) -> bool {
-        let patterns: Vec<&dyn Fn(&C) -> bool> = vec!(pattern);
-        self.get_match_info_pattern(patterns).expectations_matched()
+    pub fn return_value_once<T: Into<R>>(&self, value: T) {
+        *self.return_value_once.borrow_mut() = Some(value.into());
     }
 
-    /// Returns true if `Mock::call` has been called with all of the specified
-    /// `patterns`. The calls can be made in any order. They don't have to be
-    /// in the order specified by `patterns`.
+    /// Specify a function that constructs the `Mock`'s return value. The
+    /// `producer` is invoked every time `Mock::call` is called, so the
+    /// returned values do not need to implement `Clone`.
+    ///
+    /// This replaces any default function or closure.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<(i32, i32), ()>::new(());
-    /// mock.call((42, 0));
-    /// mock.call((42, 1));
-    ///
-    /// let pattern1 = |args: &(i32, i32)| args.0 == 42 && args.1 != 0;
-    /// let pattern2 = |args: &(i32, i32)| args.0 == 42 && args.1 == 0;
-    /// let pattern3 = |args: &(i32, i32)| args.0 == 84;
+    /// let mock = Mock::<i64, Vec<i64>>::default();
+    /// mock.use_producer(Box::new(|| vec!(1, 2, 3)));
     ///
-    /// assert!(mock.has_patterns(vec!(&pattern1)));
-    /// assert!(mock.has_patterns(vec!(&pattern2)));
-    /// assert!(mock.has_patterns(vec!(&pattern1, &pattern2)));
-    /// assert!(mock.has_patterns(vec!(&pattern2, &pattern1)));
-    /// assert!(!mock.has_patterns(vec!(&pattern3)));
-    /// assert!(!mock.has_patterns(vec!(&pattern1, &pattern3)));
+    /// assert_eq!(mock.call(1), vec!(1, 2, 3));
+    /// assert_eq!(mock.call(2), vec!(1, 2, 3));
     /// ```
-    pub fn has_patterns(&self, patterns: Vec<&dyn Fn(&C) -> bool>) -> bool {
-        self.get_match_info_pattern(patterns).expectations_matched()
+    pub fn use_producer(&self, producer: Box<dyn_fn!(Fn() -> R)>) {
+        *self.default_fn.borrow_mut() = None;
+        *self.default_closure.borrow_mut() = None;
+        *self.default_producer.borrow_mut() = Some(Shared::from(producer))
     }
 
-    /// Returns true if `Mock::call` has been called with all of the specified
-    /// `patterns`. The `patterns` must be made in the order they are specified
-    /// in the input vector.
+    /// Specify a function to determine the `Mock`'s return value based on
+    /// the arguments provided to `Mock::call`.
+    ///
+    /// Arguments of `Mock::call` are still tracked.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<(i32, i32), ()>::new(());
-    /// mock.call((42, 0));
-    /// mock.call((42, 1));
-    /// mock.call((42, 0));  // called with same args as first call!
+    /// fn add_two(x: i64) -> i64 {
+    ///     x + 2
+    /// }
     ///
-    /// let pattern1 = |args: &(i32, i32)| args.0 == 42 && args.1 != 0;
-    /// let pattern2 = |args: &(i32, i32)| args.0 == 42 && args.1 == 0;
-    /// let pattern3 = |args: &(i32, i32)| args.0 == 84;
+    /// let mock = Mock::<i64, i64>::new(10);
+    /// mock.use_fn(add_two);
     ///
-    /// assert!(mock.has_patterns_in_order(vec!(&pattern1)));
-    /// assert!(mock.has_patterns_in_order(vec!(&pattern2)));
-    /// assert!(mock.has_patterns_in_order(vec!(&pattern1, &pattern2)));
-    /// assert!(mock.has_patterns_in_order(vec!(&pattern2, &pattern1)));
-    /// assert!(mock.has_patterns_in_order(vec!(&pattern2, &pattern1, &pattern2)));
-    /// assert!(!mock.has_patterns_in_order(vec!(&pattern1, &pattern2, &pattern1)));
-    /// assert!(!mock.has_patterns_in_order(vec!(&pattern1, &pattern1, &pattern2)));
-    /// assert!(!mock.has_patterns_in_order(vec!(&pattern2, &pattern2, &pattern1)));
-    /// assert!(!mock.has_patterns_in_order(vec!(&pattern3)));
-    /// assert!(!mock.has_patterns_in_order(vec!(&pattern1, &pattern3)));
+    /// assert_eq!(mock.call(1), 3);
+    /// assert_eq!(mock.call(10), 12);
     /// ```
-    pub fn has_patterns_in_order(&self, patterns: Vec<&dyn Fn(&C) -> bool>) -> bool {
-        self.get_match_info_pattern(patterns).expectations_matched_in_order()
-    }
-
-    /// Returns true if `Mock::call` has been called with all of the specified
-    /// `patterns` and it has not been called any other times. The calls can be
-    /// made in any order. They don't have to be in the order specified by
-    /// `patterns`.
     ///
-    /// # Examples
+    /// For functions with multiple arguments, use a tuple:
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<(i32, i32), ()>::new(());
-    /// mock.call((42, 0));
-    /// mock.call((42, 1));
-    /// mock.call((42, 0));
+    /// fn add((x, y, z): (i64, i64, i64)) -> i64 {
+    ///     x + y + z
+    /// }
     ///
-    /// let pattern1 = |args: &(i32, i32)| args.0 == 42 && args.1 != 0;
-    /// let pattern2 = |args: &(i32, i32)| args.0 == 42 && args.1 == 0;
-    /// let pattern3 = |args: &(i32, i32)| args.0 == 84;
+    /// let mock = Mock::<(i64, i64, i64), i64>::default();
+    /// mock.use_fn(add);
     ///
-    /// assert!(!mock.has_patterns_exactly(vec!(&pattern1)));
-    /// assert!(!mock.has_patterns_exactly(vec!(&pattern2)));
-    /// assert!(!mock.has_patterns_exactly(vec!(&pattern3)));
-    /// assert!(!mock.has_patterns_exactly(vec!(&pattern1, &pattern2)));
-    /// assert!(!mock.has_patterns_exactly(vec!(&pattern2, &pattern1)));
-    /// assert!(mock.has_patterns_exactly(vec!(&pattern1, &pattern1, &pattern2)));
-    /// assert!(mock.has_patterns_exactly(vec!(&pattern1, &pattern2, &pattern1)));
-    /// assert!(!mock.has_patterns_exactly(vec!(&pattern1, &pattern2, &pattern3)));
+    /// assert_eq!(mock.call((1, 1, 1)), 3);
+    /// assert_eq!(mock.call((1, 2, 3,)), 6);
     /// ```
-    pub fn has_patterns_exactly(&self, patterns: Vec<&dyn Fn(&C) -> bool>) -> bool {
-        self.get_match_info_pattern(patterns).expectations_matched_exactly()
+    pub fn use_fn(&self, default_fn: fn(C) -> R) {
+        *self.default_producer.borrow_mut() = None;
+        *self.default_closure.borrow_mut() = None;
+        *self.default_fn.borrow_mut() = Some(default_fn)
     }
 
-    /// Returns true if `Mock::call` has been called with all of the specified
-    /// `patterns` and it has not been called any other times. The calls must
-    /// be made match the patterns in the same order as specified in the
-    /// `patterns` vector.
+    /// Specify a function to determine the `Mock`'s return value based on
+    /// the arguments provided to `Mock::call`. This function will only be
+    /// invoked if the arguments match the specified `args`.
+    ///
+    /// Arguments of `Mock::call` are still tracked.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<(i32, i32), ()>::new(());
-    /// mock.call((42, 0));
-    /// mock.call((42, 1));
-    /// mock.call((42, 0));  // called with same args as first call!
+    /// fn add_two(x: i64) -> i64 {
+    ///     x + 2
+    /// }
     ///
-    /// let pattern1 = |args: &(i32, i32)| args.0 == 42 && args.1 != 0;
-    /// let pattern2 = |args: &(i32, i32)| args.0 == 42 && args.1 == 0;
-    /// let pattern3 = |args: &(i32, i32)| args.0 == 84;
+    /// let mock = Mock::<i64, i64>::new(10);
+    /// mock.return_value(42);
+    /// mock.use_fn_for(5, add_two);
     ///
-    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern1)));
-    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern2)));
-    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern1, &pattern2)));
-    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern2, &pattern1)));
-    /// assert!(mock.has_patterns_exactly_in_order(vec!(&pattern2, &pattern1, &pattern2)));
-    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern1, &pattern2, &pattern1)));
-    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern1, &pattern1, &pattern2)));
-    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern2, &pattern2, &pattern1)));
-    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern3)));
-    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern1, &pattern3)));
+    /// assert_eq!(mock.call(1), 42);  // uses default value
+    /// assert_eq!(mock.call(5), 7);   // uses function since args match
     /// ```
-    pub fn has_patterns_exactly_in_order(&self, patterns: Vec<&dyn Fn(&C) -> bool>) -> bool {
-        self.get_match_info_pattern(patterns).expectations_matched_in_order_exactly()
-    }
-
-    // ========================================================================
-    // * Private Helpers
-    // ========================================================================
-    fn get_match_info<T: Into<C>>(&self, expected_calls: Vec<T>) -> MatchInfo {
-        let expected_calls_c: Vec<C> = expected_calls
-            .into_iter()
-            .map(|r| r.into())
-            .collect();
-
-        // Build map from expected arg tuple (its index) to the indices of the
-        // actual calls made to the mock whose args match that tuple exactly.
-        let mut pattern_index_to_match_indices: HashMap<usize, Vec<usize>> =
-            HashMap::new();
-        for (call_index, call_args) in self.calls.borrow().iter().enumerate() {
-            for (expected_index, expected_args) in expected_calls_c.iter().enumerate() {
-                if call_args == expected_args {
-                    pattern_index_to_match_indices
-                        .entry(expected_index)
-                        .or_insert(vec!())
-                        .push(call_index);
-                }
-            }
-        }
-
-        MatchInfo {
-            num_expectations: expected_calls_c.len(),
-            num_actual_calls: self.calls.borrow().len(),
-            pattern_index_to_match_indices: pattern_index_to_match_indices,
-        }
-    }
-
-    fn get_match_info_pattern(&self, patterns: Vec<&dyn Fn(&C) -> bool>) -> MatchInfo {
-        // Build map from pattern (its index) to the indices of the actual
-        // calls made to the mock whose args match that pattern.
-        let mut pattern_index_to_match_indices: HashMap<usize, Vec<usize>> =
-            HashMap::new();
-        for (call_index, call_args) in self.calls.borrow().iter().enumerate() {
-            for (expected_index, pattern_fn) in patterns.iter().enumerate() {
-                if pattern_fn(call_args) {
-                    pattern_index_to_match_indices
-                        .entry(expected_index)
-                        .or_insert(vec!())
-                        .push(call_index);
-                }
-            }
-        }
-
-        MatchInfo {
-            num_expectations: patterns.len(),
-            num_actual_calls: self.calls.borrow().len(),
-            pattern_index_to_match_indices: pattern_index_to_match_indices,
-        }
-    }
-}
-
-impl<C, S> Mock<C, Option<S>>
-    where C: Clone + Eq + Hash,
-          S: Clone
-{
-    /// Return `Some(return_value)` from `Mock::call`.
     ///
-    /// # Examples
+    /// For functions with multiple arguments, use a tuple:
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<(), Option<i64>>::new(None);
-    /// mock.return_some(10);
+    /// fn add((x, y, z): (i64, i64, i64)) -> i64 {
+    ///     x + y + z
+    /// }
     ///
-    /// assert_eq!(mock.call(()), Some(10));
+    /// let mock = Mock::<(i64, i64, i64), i64>::default();
+    /// mock.return_value(42);
+    /// mock.use_fn_for((1, 2, 3), add);
+    ///
+    /// assert_eq!(mock.call((1, 1, 1)), 42);
+    /// assert_eq!(mock.call((1, 2, 3)), 6);
     /// ```
-    pub fn return_some<T: Into<S>>(&self, return_value: T) {
-        self.return_value(Some(return_value.into()))
+    pub fn use_fn_for<T: IntoCallArgs<C, M>, M>(&self, args: T, function: fn(C) -> R) {
+        self.fns.borrow_mut().insert(args.into_call_args(), function);
     }
 
-    /// Return `None` from `Mock::call`.
+    /// Specify a closure to determine the `Mock`'s return value based on
+    /// the arguments provided to `Mock::call`.
+    ///
+    /// Arguments of `Mock::call` are still tracked.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<(), Option<i64>>::new(Some(42));
-    /// mock.return_none();
+    /// let mock = Mock::<i64, i64>::new(10);
+    /// let add_two = |x| x + 2;
+    /// mock.use_closure(Box::new(add_two));
     ///
-    /// assert_eq!(mock.call(()), None);
+    /// assert_eq!(mock.call(1), 3);
+    /// assert_eq!(mock.call(10), 12);
     /// ```
-    pub fn return_none(&self) {
-        self.return_value(None)
+    ///
+    /// For functions with multiple arguments, use a tuple:
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i64, i64, i64), i64>::default();
+    /// let add = |(x, y, z)| x + y + z;
+    /// mock.use_closure(Box::new(add));
+    ///
+    /// assert_eq!(mock.call((1, 1, 1)), 3);
+    /// assert_eq!(mock.call((1, 2, 3,)), 6);
+    /// ```
+    pub fn use_closure(&self, default_fn: Box<dyn_fn!(Fn(C) -> R)>) {
+        *self.default_producer.borrow_mut() = None;
+        *self.default_fn.borrow_mut() = None;
+        *self.default_closure.borrow_mut() = Some(Closure::Args(Shared::from(default_fn)))
     }
-}
 
-impl<C, O, E> Mock<C, Result<O, E>>
-    where C: Clone + Eq + Hash,
-          O: Clone,
-          E: Clone
-{
-    /// Return `Ok(return_value)` from `Mock::call`.
+    /// Specify a closure to determine the `Mock`'s return value based on
+    /// the zero-based index of the call and the arguments provided to
+    /// `Mock::call`. The index is the value of `Mock::num_calls` before the
+    /// call was made.
+    ///
+    /// This replaces any default function or closure, including one set by
+    /// `Mock::use_closure`.
     ///
     /// # Examples
     ///
+    /// Fail the first two attempts of an operation to test retry logic:
+    ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<(), Result<&str, &str>>::new(Err("oh no"));
-    /// mock.return_ok("success");
+    /// fn fetch_with_retries(fetch: &Mock<&'static str, Result<u32, &'static str>>) -> Result<u32, &'static str> {
+    ///     let mut result = fetch.call("key");
+    ///     for _ in 0..4 {
+    ///         if result.is_ok() {
+    ///             break;
+    ///         }
+    ///         result = fetch.call("key");
+    ///     }
+    ///     result
+    /// }
     ///
-    /// assert_eq!(mock.call(()), Ok("success"));
+    /// let mock = Mock::<&'static str, Result<u32, &'static str>>::new(Ok(0));
+    /// mock.use_closure_with_index(Box::new(|index, _| {
+    ///     if index < 2 { Err("unavailable") } else { Ok(42) }
+    /// }));
+    ///
+    /// assert_eq!(fetch_with_retries(&mock), Ok(42));
+    /// assert_eq!(mock.num_calls(), 3);
     /// ```
-    pub fn return_ok<T: Into<O>>(&self, return_value: T) {
-        self.return_value(Ok(return_value.into()))
+    pub fn use_closure_with_index(&self, default_fn: Box<dyn_fn!(Fn(usize, C) -> R)>) {
+        *self.default_producer.borrow_mut() = None;
+        *self.default_fn.borrow_mut() = None;
+        *self.default_closure.borrow_mut() = Some(Closure::Indexed(Shared::from(default_fn)))
     }
 
-    /// Return `Err(return_value)` from `Mock::call`.
+    /// Specify a closure to determine the `Mock`'s return value based on
+    /// the arguments provided to `Mock::call`. This closure will only be
+    /// invoked if the arguments match the specified `args`.
+    ///
+    /// Arguments of `Mock::call` are still tracked.
     ///
     /// # Examples
     ///
     /// ```
     /// use double::Mock;
     ///
-    /// let mock = Mock::<(), Result<&str, &str>>::new(Ok("success"));
-    /// mock.return_err("oh no");
+    /// let mock = Mock::<i64, i64>::new(10);
+    /// let add_two = |x| x + 2;
+    /// mock.return_value(42);
+    /// mock.use_closure_for(10, Box::new(add_two));
     ///
-    /// assert_eq!(mock.call(()), Err("oh no"));
+    /// assert_eq!(mock.call(1), 42);
+    /// assert_eq!(mock.call(10), 12);
     /// ```
-    pub fn return_err<T: Into<E>>(&self, return_value: T) {
-        self.return_value(Err(return_value.into()))
+    ///
+    /// For functions with multiple arguments, use a tuple:
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i64, i64, i64), i64>::default();
+    /// let add = |(x, y, z)| x + y + z;
+    /// mock.return_value(42);
+    /// mock.use_closure_for((1, 2, 3), Box::new(add));
+    ///
+    /// assert_eq!(mock.call((1, 1, 1)), 42);
+    /// assert_eq!(mock.call((1, 2, 3)), 6);
+    /// ```
+    pub fn use_closure_for<T: IntoCallArgs<C, M>, M>(&self, args: T, function: Box<dyn_fn!(Fn(C) -> R)>) {
+        self.closures.borrow_mut().insert(args.into_call_args(), Closure::Args(Shared::from(function)));
     }
-}
 
-impl<C, R> Debug for Mock<C, R>
-    where C: Clone + Debug + Eq + Hash,
-          R: Clone + Debug
-{
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        f.debug_struct("Mock")
-            .field("default_return_value", &self.default_return_value)
-            .field("return_value_sequence", &self.return_value_sequence)
-            .field("return_values", &self.return_values)
-            .field("calls", &self.calls)
-            .finish()
+    /// Specify a closure to determine the `Mock`'s return value based on
+    /// the zero-based index of the call and the arguments provided to
+    /// `Mock::call`. This closure will only be invoked if the arguments match
+    /// the specified `args`.
+    ///
+    /// The index counts every call made to the `Mock`, not just calls with
+    /// matching `args`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, usize>::new(100usize);
+    /// mock.use_closure_with_index_for("banana", Box::new(|index, _| index));
+    ///
+    /// assert_eq!(mock.call("apple"), 100);
+    /// assert_eq!(mock.call("banana"), 1);
+    /// assert_eq!(mock.call("banana"), 2);
+    /// ```
+    pub fn use_closure_with_index_for<T: IntoCallArgs<C, M>, M>(
+        &self,
+        args: T,
+        function: Box<dyn_fn!(Fn(usize, C) -> R)>)
+    {
+        self.closures.borrow_mut().insert(args.into_call_args(), Closure::Indexed(Shared::from(function)));
     }
-}
 
-struct MatchInfo {
-    num_expectations: usize,
-    num_actual_calls: usize,
-    // Maps actual call index to the indices of patterns that match the call
-    pattern_index_to_match_indices: HashMap<usize, Vec<usize>>,
-}
+    /// Make every subsequent call to `Mock::call` panic with `message`,
+    /// regardless of the args. This takes precedence over all configured
+    /// return values and functions.
+    ///
+    /// The call is still recorded, and hooks registered with `Mock::on_call`
+    /// and observers are invoked, before the mock panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    /// use std::panic;
+    ///
+    /// let mock = Mock::<i32, i32>::new(0);
+    /// mock.panic_with("connection lost");
+    ///
+    /// let result = panic::catch_unwind(panic::AssertUnwindSafe(|| mock.call(1)));
+    /// assert!(result.is_err());
+    /// assert!(mock.called_with(1));
+    /// ```
+    pub fn panic_with(&self, message: &'static str) {
+        *self.panic_message.borrow_mut() = Some(message);
+    }
 
-impl MatchInfo {
-    pub fn expectations_matched(&self) -> bool {
-        let expected_indices: HashSet<usize> = HashSet::from_iter(
-            0..self.num_expectations);
-        let expected_indices_matched = HashSet::from_iter(
-            self.pattern_index_to_match_indices
-            .keys()
-            .map(|k| k.clone()));
-        let unmatched_expectation_indices: HashSet<usize> = HashSet::from_iter(
-            expected_indices
-            .difference(&expected_indices_matched)
-            .map(|i| i.clone()));
+    /// Make every subsequent call to `Mock::call` with the given `args` panic
+    /// with `message`. This takes precedence over `Mock::panic_with` and all
+    /// configured return values and functions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    /// use std::panic;
+    ///
+    /// let mock = Mock::<i32, i32>::new(0);
+    /// mock.panic_with_for(13, "unlucky");
+    ///
+    /// assert_eq!(mock.call(12), 0);
+    /// let result = panic::catch_unwind(panic::AssertUnwindSafe(|| mock.call(13)));
+    /// assert!(result.is_err());
+    /// ```
+    pub fn panic_with_for<T: IntoCallArgs<C, M>, M>(&self, args: T, message: &'static str) {
+        self.panic_messages.borrow_mut().insert(args.into_call_args(), message);
+    }
 
-        for index in unmatched_expectation_indices.iter() {
-            println!(
-                "No match found for expected call/pattern with index {}",
-                index);
-        }
-        unmatched_expectation_indices.len() == 0
+    /// Register a `hook` that is invoked with the args of every call to
+    /// `Mock::call`, for performing side effects such as setting a flag or
+    /// logging.
+    ///
+    /// Hooks are invoked in the order they were registered, after the call's
+    /// args are recorded and before the return value is determined. Hooks do
+    /// not affect the return value, so they can observe calls alongside any
+    /// configured behaviour, including `Mock::use_fn` and
+    /// `Mock::use_closure`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use double::Mock;
+    ///
+    /// let events = Arc::new(Mutex::new(Vec::new()));
+    ///
+    /// let mock = Mock::<i64, i64>::default();
+    /// let hook_events = events.clone();
+    /// mock.on_call(Box::new(move |x| hook_events.lock().unwrap().push(format!("hook {}", x))));
+    /// let closure_events = events.clone();
+    /// mock.use_closure(Box::new(move |x| {
+    ///     closure_events.lock().unwrap().push(format!("return {}", x * 2));
+    ///     x * 2
+    /// }));
+    ///
+    /// assert_eq!(mock.call(1), 2);
+    /// assert_eq!(mock.call(2), 4);
+    /// assert_eq!(*events.lock().unwrap(), vec!("hook 1", "return 2", "hook 2", "return 4"));
+    /// ```
+    pub fn on_call(&self, hook: Box<dyn_fn!(Fn(&C))>) {
+        self.call_hooks.borrow_mut().push((None, Shared::from(hook)));
     }
 
-    pub fn expectations_matched_in_order(&self) -> bool {
-        self.expectations_matched() && self.matches_are_in_order()
+    /// Register a `hook` that is invoked whenever `Mock::call` is called with
+    /// the specified `args`. See `Mock::on_call` for when hooks are invoked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use double::Mock;
+    ///
+    /// let flag = Arc::new(AtomicBool::new(false));
+    ///
+    /// let mock = Mock::<&str, ()>::default();
+    /// let hook_flag = flag.clone();
+    /// mock.on_call_for("shutdown", Box::new(move |_| hook_flag.store(true, Ordering::SeqCst)));
+    ///
+    /// mock.call("start");
+    /// assert!(!flag.load(Ordering::SeqCst));
+    /// mock.call("shutdown");
+    /// assert!(flag.load(Ordering::SeqCst));
+    /// ```
+    pub fn on_call_for<T: IntoCallArgs<C, M>, M>(&self, args: T, hook: Box<dyn_fn!(Fn(&C))>) {
+        self.call_hooks.borrow_mut().push((Some(args.into_call_args()), Shared::from(hook)));
     }
 
-    pub fn expectations_matched_exactly(&self) -> bool {
-        self.expectations_matched() &&
-            self.num_expectations_equal_num_actual_calls()
+    /// Register an `observer` that is invoked with the args of every call to
+    /// `Mock::call`, purely for its side effects.
+    ///
+    /// Any number of observers can be registered. They are invoked in the
+    /// order they were registered, after any hooks registered with
+    /// `Mock::on_call` and before the return value is determined. Observers
+    /// are separate from stubbing: they can't change the return value, and
+    /// configuring return values, functions or closures doesn't remove them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use double::Mock;
+    ///
+    /// let num_calls = Arc::new(AtomicUsize::new(0));
+    /// let total = Arc::new(AtomicUsize::new(0));
+    ///
+    /// let mock = Mock::<usize, usize>::default();
+    /// let observed_calls = num_calls.clone();
+    /// mock.add_call_observer(Box::new(move |_| {
+    ///     observed_calls.fetch_add(1, Ordering::SeqCst);
+    /// }));
+    /// let observed_total = total.clone();
+    /// mock.add_call_observer(Box::new(move |&x| {
+    ///     observed_total.fetch_add(x, Ordering::SeqCst);
+    /// }));
+    /// mock.use_fn(|x| x * 2);
+    ///
+    /// assert_eq!(mock.call(3), 6);
+    /// assert_eq!(mock.call(4), 8);
+    /// assert_eq!(num_calls.load(Ordering::SeqCst), 2);
+    /// assert_eq!(total.load(Ordering::SeqCst), 7);
+    /// ```
+    pub fn add_call_observer(&self, observer: Box<dyn_fn!(Fn(&C))>) {
+        self.call_observers.borrow_mut().push(Shared::from(observer));
     }
 
-    pub fn expectations_matched_in_order_exactly(&self) -> bool {
-        self.expectations_matched_in_order() &&
-            self.num_expectations_equal_num_actual_calls()
+    /// Returns an `ArgCapture` that collects the arguments of every
+    /// subsequent call to `Mock::call`.
+    ///
+    /// Unlike `Mock::calls`, a capture only sees calls made after it was
+    /// created and isn't cleared by `Mock::reset_calls`. Any number of
+    /// captures can be active at the same time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::default();
+    /// mock.call("setup");
+    ///
+    /// let capture = mock.capture_args();
+    /// mock.call("foo");
+    /// mock.call("bar");
+    ///
+    /// assert_eq!(capture.all(), vec!("foo", "bar"));
+    /// assert_eq!(capture.last(), Some("bar"));
+    /// ```
+    pub fn capture_args(&self) -> ArgCapture<C>
+        where C: ThreadSafe + 'static
+    {
+        self.capture_args_matching(Box::new(|_| true))
     }
 
-    fn matches_are_in_order(&self) -> bool {
-        // If all the expectations are met, use the indices of all matching
-        // calls (for each pattern) to determine if the calls were made in
-        // the order specified by the expectated patterns.
-        //
-        // This is more difficult than one might think. Each expected pattern
-        // can match multiple calls. Additionally, the total set of
-        // expectations can be smaller than the total number of calls. Both of
-        // two aspects make this problem tricky.
-        //
-        // The following algorithm is used for the check:
-        //
-        // 1. For each pattern, construct a list containing the indices of the
-        //    calls that match it
-        // 2. Generate all permutations of the sequence of actual calls that
-        //    matched each of the N patterns (uses the lists from (1))
-        // 3. For each permutation, check if the call indices in the
-        //    permutation are strictly increasing. If so, we've found a
-        //    permutation that occurred where the call order and the expected
-        //    pattern order match. This means the expectations were indeed
-        //    matched in order and return true.
-        // 4. If none of the permutations are strictly increasing, the
-        //    expected patterns were matched, but not in the expected order.
-        //    Return false.
-        //
-        //
-        // The complexity is O(N!), where N is the number of patterns in the
-        // expected sequence. The factorial complexity is caused by the
-        // generation of all permutations of matching call index sequences in.
-        // step (2). The O(N!) complexity is currently not a concern for two
-        // reasons:
-        //
-        // * Most ordered checks run by clients involve less than 5 patterns,
-        //   so the upper bound typically won't exceed 5!.
-        // * The constant factor is almost always very low (most of the time
-        //   a pattern will only ever match one call arg, meaning the number
-        //   of permutations is very small, even if N is high).
-        //
-        // This algorithm will only be revised if a legitmate performance issue
-        // is found.
-        if self.expectations_matched() {
-            let permutation_constraints = self.pattern_index_to_match_indices
-                .iter()
-                .sorted_by(|a, b| a.0.cmp(&b.0))
-                .map(
-                    |(_, matching_call_indices)| matching_call_indices.clone())
-                .collect();
-            for permutation in generate_permutations(&permutation_constraints) {
-                if is_strictly_increasing(permutation.as_slice()) {
-                    return true;
-                }
+    /// Returns an `ArgCapture` that collects the arguments of every
+    /// subsequent call to `Mock::call` that match `pattern`. See
+    /// `Mock::capture_args`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i32, ()>::default();
+    ///
+    /// let capture = mock.capture_args_matching(Box::new(|x| *x > 10));
+    /// mock.call(5);
+    /// mock.call(50);
+    /// mock.call(500);
+    ///
+    /// assert_eq!(capture.take(), vec!(50, 500));
+    /// assert!(capture.all().is_empty());
+    /// ```
+    pub fn capture_args_matching(&self, pattern: Box<dyn_fn!(Fn(&C) -> bool)>) -> ArgCapture<C>
+        where C: ThreadSafe + 'static
+    {
+        let capture = ArgCapture { args: Ref::new(Cell::new(vec!())) };
+        let captured_args = capture.args.clone();
+        self.on_call(Box::new(move |args| {
+            if pattern(args) {
+                captured_args.borrow_mut().push(args.clone());
             }
-            false
-        } else {
-            false
-        }
+        }));
+        capture
     }
 
-    fn num_expectations_equal_num_actual_calls(&self) -> bool {
-        if self.num_expectations != self.num_actual_calls {
-            println!(
-                "Mock was called {:?} times, not {:?}",
-                self.num_actual_calls,
-                self.num_expectations);
-            false
-        } else {
-            true
-        }
+    /// Returns true if `Mock::call` has been called.
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i64, ()>::default();
+    ///
+    /// assert!(!mock.called());
+    ///
+    /// mock.call(10);
+    ///
+    /// assert!(mock.called());
+    /// ```
+    pub fn called(&self) -> bool {
+        self.num_calls() > 0
     }
-}
-
-fn generate_permutations(constraints: &Vec<Vec<usize>>) -> Vec<Vec<usize>> {
-    let mut output: Vec<Vec<usize>> = vec!();
-    if !constraints.is_empty() {
-        let mut permutation_buffer: Vec<usize> = vec!();
-        permutation_buffer.resize(constraints.len(), 0);
 
-        generate_permutations_impl(
-            &mut output, &mut permutation_buffer, constraints, 0);
-    }
-    output
+    /// Returns the number of times `Mock::call` has been called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i64, i64>::new(0);
+    ///
+    /// assert_eq!(mock.num_calls(), 0);
+    /// mock.call(5);
+    /// assert_eq!(mock.num_calls(), 1);
+    /// mock.call(10);
+    /// assert_eq!(mock.num_calls(), 2);
+    /// ```
+    pub fn num_calls(&self) -> usize {
+        *self.num_calls.borrow()
+    }
+
+    /// Returns true if the number of times `Mock::call` has been called is
+    /// between `min` and `max` inclusive. Use
+    /// `Mock::check_calls_in_count_range` to find out the actual count when
+    /// it isn't.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i64, ()>::default();
+    /// mock.call(1);
+    ///
+    /// assert!(!mock.calls_in_count_range(2, 3));
+    /// mock.call(2);
+    /// assert!(mock.calls_in_count_range(2, 3));
+    /// mock.call(3);
+    /// mock.call(4);
+    /// assert!(!mock.calls_in_count_range(2, 3));
+    /// ```
+    pub fn calls_in_count_range(&self, min: usize, max: usize) -> bool {
+        let num_calls = self.num_calls();
+        min <= num_calls && num_calls <= max
+    }
+
+    /// Returns the arguments to `Mock::call` in order from first to last.
+    ///
+    /// Calls made while call recording is disabled, or made using
+    /// `Mock::call_without_recording`, are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, &str>::new("");
+    ///
+    /// mock.call("first");
+    /// mock.call("second");
+    /// mock.call("third");
+    ///
+    /// assert_eq!(mock.calls().as_slice(), ["first", "second", "third"]);
+    /// ```
+    pub fn calls(&self) -> Vec<C> {
+        self.recorded_calls().into_vec()
+    }
+
+    /// Returns the arguments of the last `n` calls to `Mock::call` in order
+    /// from first to last. The same calls are included as in `Mock::calls`.
+    /// If fewer than `n` calls were recorded, all of them are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i32, ()>::default();
+    /// for i in 1..=5 {
+    ///     mock.call(i);
+    /// }
+    ///
+    /// assert_eq!(mock.last_n_calls(2), vec!(4, 5));
+    /// assert_eq!(mock.last_n_calls(10), vec!(1, 2, 3, 4, 5));
+    /// ```
+    pub fn last_n_calls(&self, n: usize) -> Vec<C> {
+        let calls = self.recorded_calls();
+        calls[calls.len().saturating_sub(n)..].to_vec()
+    }
+
+    /// Returns the arguments to `Mock::call` in order from first to last,
+    /// converted using `f`. The same calls are included as in `Mock::calls`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i32, &str), ()>::default();
+    ///
+    /// mock.call((1, "first"));
+    /// mock.call((5, "second"));
+    ///
+    /// assert_eq!(mock.calls_as(|&(timestamp, _)| timestamp), vec!(1, 5));
+    /// ```
+    pub fn calls_as<U, F: Fn(&C) -> U>(&self, f: F) -> Vec<U> {
+        self.recorded_calls().iter().map(f).collect()
+    }
+
+    /// Returns the result of `f`, which is passed the arguments to
+    /// `Mock::call` in order from first to last. The same calls are included
+    /// as in `Mock::calls`, but they're borrowed rather than copied, which is
+    /// cheaper for large arguments or long call histories. This includes
+    /// calls stored by `Mock::enable_arg_interning`, which are read from the
+    /// interned args.
+    ///
+    /// The calls are borrowed until `f` returns, so `f` must not call the
+    /// `Mock` or any of its clones, or borrow the calls again. Doing so
+    /// panics. With the `sync-mocks` feature, calls made by other threads
+    /// wait until `f` returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<Vec<u8>, ()>::default();
+    /// mock.call(vec!(1, 2, 3));
+    /// mock.call(vec!(4, 5));
+    ///
+    /// let all_short = mock.with_calls(|calls| calls.iter().all(|payload| payload.len() <= 3));
+    /// assert!(all_short);
+    /// ```
+    ///
+    /// ```should_panic
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i32, ()>::default();
+    /// mock.call(1);
+    ///
+    /// // panics, because the calls are borrowed
+    /// mock.with_calls(|calls| mock.call(calls[0] + 1));
+    /// ```
+    pub fn with_calls<T, F: FnOnce(&BorrowedCalls<'_, C>) -> T>(&self, f: F) -> T {
+        self.assert_calls_not_borrowed("Mock::with_calls");
+        {
+            let interned_args = self.interned_args.borrow();
+            if let Some(ref interned_args) = *interned_args {
+                let _reader = CallsReader::new(&self.calls_reader);
+                return f(&BorrowedCalls {
+                    args: &interned_args.args,
+                    indices: Some(&interned_args.calls),
+                });
+            }
+        }
+        let calls = self.calls.borrow();
+        let _reader = CallsReader::new(&self.calls_reader);
+        f(&BorrowedCalls { args: &calls, indices: None })
+    }
+
+    /// Combines the arguments to `Mock::call` into a single value by
+    /// applying `f` to `init` and the first call's arguments, then to the
+    /// result and the second call's arguments, and so on. The calls are
+    /// borrowed rather than copied, as in `Mock::with_calls`, so `f` must not
+    /// call the `Mock` either.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<Vec<u8>, ()>::default();
+    /// mock.call(vec!(1, 2, 3));
+    /// mock.call(vec!(4, 5));
+    ///
+    /// let total_len = mock.fold_calls(0, |total, payload| total + payload.len());
+    /// assert_eq!(total_len, 5);
+    /// ```
+    pub fn fold_calls<B, F: FnMut(B, &C) -> B>(&self, init: B, f: F) -> B {
+        self.with_calls(|calls| calls.iter().fold(init, f))
+    }
+
+    /// Returns an iterator over the arguments of each call to `Mock::call`,
+    /// in order from first to last. `&Mock` can also be iterated over
+    /// directly.
+    ///
+    /// The calls are copied before iterating, so the `Mock` can be called
+    /// while iterating. Those calls are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::default();
+    /// mock.call("foo");
+    /// mock.call("bar");
+    ///
+    /// assert_eq!(mock.iter().collect::<Vec<_>>(), vec!("foo", "bar"));
+    ///
+    /// let mut lengths = vec!();
+    /// for args in &mock {
+    ///     lengths.push(args.len());
+    /// }
+    /// assert_eq!(lengths, vec!(3, 3));
+    /// ```
+    pub fn iter(&self) -> std::vec::IntoIter<C> {
+        self.calls().into_iter()
+    }
+
+    /// Use `clock` to timestamp each subsequent call to `Mock::call`. The
+    /// timestamps are returned by `Mock::call_times`.
+    ///
+    /// The units of the timestamps are chosen by `clock`, which makes it
+    /// possible to use a fake clock controlled by the test.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    ///
+    /// let now_ms = Arc::new(AtomicU64::new(1000));
+    /// let clock = now_ms.clone();
+    ///
+    /// let mock = Mock::<&str, ()>::default();
+    /// mock.set_clock(Box::new(move || clock.load(Ordering::SeqCst)));
+    ///
+    /// mock.call("first");
+    /// now_ms.store(1250, Ordering::SeqCst);
+    /// mock.call("second");
+    ///
+    /// assert_eq!(mock.call_times(), vec!(1000, 1250));
+    /// ```
+    pub fn set_clock(&self, clock: Box<dyn_fn!(Fn() -> u64)>) {
+        *self.clock.borrow_mut() = Some(Shared::from(clock));
+    }
+
+    /// Returns the configured behaviour used to produce the return value of
+    /// each call to `Mock::call`, in order from first to last. The same calls
+    /// are included as in `Mock::calls`.
+    ///
+    /// See `Mock::explain_next_call` to find out which behaviour the next
+    /// call will use.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    /// use double::mock::BehaviourSource;
+    ///
+    /// let mock = Mock::<i32, i32>::new(0);
+    /// mock.return_value_once(1);
+    /// mock.use_fn_for(5, |x| x * 2);
+    ///
+    /// mock.call(5);
+    /// mock.call(6);
+    /// mock.call(7);
+    ///
+    /// assert_eq!(
+    ///     mock.call_sources(),
+    ///     vec!(BehaviourSource::FnFor, BehaviourSource::Once, BehaviourSource::DefaultValue));
+    /// ```
+    pub fn call_sources(&self) -> Vec<BehaviourSource> {
+        self.call_sources.borrow().clone()
+    }
+
+    /// Returns the time each call to `Mock::call` was made, in order from
+    /// first to last. The same calls are included as in `Mock::calls`.
+    ///
+    /// Times are read from the clock set using `Mock::set_clock`. If no clock
+    /// is set, the time is the number of calls made before the call, so
+    /// the times count up from 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::default();
+    ///
+    /// mock.call("first");
+    /// mock.call("second");
+    ///
+    /// assert_eq!(mock.call_times(), vec!(0, 1));
+    /// ```
+    pub fn call_times(&self) -> Vec<u64> {
+        self.call_times.borrow().clone()
+    }
+
+    /// Returns the timestamp of each call to `Mock::call`, in order from
+    /// first to last. The same calls are included as in `Mock::calls`.
+    ///
+    /// Timestamps are shared by all mocks, so they can be used to order the
+    /// calls made to different mocks. Every call has a larger timestamp than
+    /// the calls made before it. See `called_before` and `called_after`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let write = Mock::<&str, ()>::default();
+    /// let flush = Mock::<(), ()>::default();
+    ///
+    /// write.call("foo");
+    /// flush.call(());
+    /// write.call("bar");
+    ///
+    /// let write_timestamps = write.call_timestamps();
+    /// let flush_timestamps = flush.call_timestamps();
+    /// assert!(write_timestamps[0] < flush_timestamps[0]);
+    /// assert!(flush_timestamps[0] < write_timestamps[1]);
+    /// ```
+    pub fn call_timestamps(&self) -> Vec<usize> {
+        self.call_timestamps.borrow().clone()
+    }
+
+    /// Returns the arguments of each call to `Mock::call` along with its
+    /// timestamp, in order from first to last. See `Mock::call_timestamps`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let write = Mock::<&str, ()>::default();
+    /// let flush = Mock::<(), ()>::default();
+    ///
+    /// write.call("foo");
+    /// flush.call(());
+    ///
+    /// let write_calls = write.timestamped_calls();
+    /// let flush_calls = flush.timestamped_calls();
+    /// assert_eq!("foo", write_calls[0].1);
+    /// assert!(write_calls[0].0 < flush_calls[0].0);
+    /// ```
+    pub fn timestamped_calls(&self) -> Vec<(usize, C)> {
+        let calls = self.recorded_calls();
+        let timestamps = self.call_timestamps.borrow();
+        timestamps.iter().cloned().zip(calls.iter().cloned()).collect()
+    }
+
+    /// Returns the arguments of the calls to all of the given `mocks`, in
+    /// the order the calls were made. This is useful to check the combined
+    /// order of calls to a collaborator whose methods are mocked by separate
+    /// `Mock`s.
+    ///
+    /// Calls are ordered by their `Mock::call_timestamps`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let open = Mock::<&str, ()>::default();
+    /// let close = Mock::<&str, ()>::default();
+    ///
+    /// open.call("a.txt");
+    /// open.call("b.txt");
+    /// close.call("a.txt");
+    /// open.call("c.txt");
+    ///
+    /// assert_eq!(
+    ///     Mock::merged_calls(&[&open, &close]),
+    ///     vec!("a.txt", "b.txt", "a.txt", "c.txt"));
+    /// ```
+    pub fn merged_calls(mocks: &[&Mock<C, R>]) -> Vec<C> {
+        let mut timestamped_calls: Vec<(usize, C)> = vec!();
+        for mock in mocks {
+            timestamped_calls.extend(mock.timestamped_calls());
+        }
+        timestamped_calls.sort_by_key(|&(timestamp, _)| timestamp);
+        timestamped_calls.into_iter().map(|(_, args)| args).collect()
+    }
+
+    /// Reset the call history for the `Mock`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, &str>::default();
+    ///
+    /// mock.call("first");
+    /// mock.call("second");
+    ///
+    /// assert!(mock.called());
+    /// assert_eq!(mock.num_calls(), 2);
+    /// assert!(mock.called_with("first"));
+    /// assert!(mock.called_with("second"));
+    ///
+    /// mock.reset_calls();
+    ///
+    /// assert!(!mock.called());
+    /// assert_eq!(mock.num_calls(), 0);
+    /// assert!(!mock.called_with("first"));
+    /// assert!(!mock.called_with("second"));
+    /// assert!(mock.call_log().is_empty());
+    /// ```
+    pub fn reset_calls(&self) {
+        self.calls.borrow_mut().clear();
+        if let Some(ref mut interned_args) = *self.interned_args.borrow_mut() {
+            *interned_args = InternedArgs::new();
+        }
+        *self.num_dropped_calls.borrow_mut() = 0;
+        self.returned_values.borrow_mut().clear();
+        self.call_sources.borrow_mut().clear();
+        self.call_times.borrow_mut().clear();
+        self.call_timestamps.borrow_mut().clear();
+        *self.num_calls.borrow_mut() = 0;
+        self.num_calls_per_args.borrow_mut().clear();
+    }
+
+    /// Returns the arguments of every call made to the mock, in order from
+    /// first to last, and clears the call history like `Mock::reset_calls`.
+    ///
+    /// This is useful for verifying the calls made so far, then checking
+    /// that no further calls are made using `Mock::assert_no_more_calls_guard`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::new(());
+    /// mock.call("first");
+    /// mock.call("second");
+    ///
+    /// assert_eq!(mock.take_calls(), vec!("first", "second"));
+    /// assert_eq!(mock.num_calls(), 0);
+    ///
+    /// mock.call("third");
+    /// assert_eq!(mock.take_calls(), vec!("third"));
+    /// ```
+    pub fn take_calls(&self) -> Vec<C> {
+        let calls: Vec<C> = if self.interned_args.borrow().is_some() {
+            self.recorded_calls().into_vec()
+        } else {
+            self.calls.borrow_mut().drain(..).collect()
+        };
+        self.reset_calls();
+        calls
+    }
+
+    /// Consumes the mock and returns the arguments of every call made to it,
+    /// in order from first to last.
+    ///
+    /// The arguments are moved out of the mock without being copied, unless
+    /// the mock has been cloned. Clones share their call history with the
+    /// mock, so if any clones are still alive the arguments are copied
+    /// instead, and the clones keep their call history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i32, String), ()>::new(());
+    /// mock.call((1, "first".to_owned()));
+    /// mock.call((2, "second".to_owned()));
+    ///
+    /// match mock.into_calls().as_slice() {
+    ///     [(1, first), (2, second)] => assert_eq!(first.len(), second.len() - 1),
+    ///     calls => panic!("unexpected calls: {:?}", calls),
+    /// }
+    /// ```
+    pub fn into_calls(self) -> Vec<C> {
+        if self.interned_args.borrow().is_some() {
+            return self.recorded_calls().into_vec();
+        }
+        into_value(self.calls)
+    }
+
+    /// Start a new phase of a test. First verifies the calls expected using
+    /// `Mock::expect_call`, panicking with the `CallMismatch` returned by
+    /// `Mock::check_expectations` if any weren't made. Then clears the
+    /// expected calls, the call history, like `Mock::reset_calls`, and any
+    /// one-shot behaviour that hasn't been used yet: the value configured
+    /// using `Mock::return_value_once` and the values remaining from
+    /// `Mock::return_values`.
+    ///
+    /// Other configured behaviour is kept, such as the default return value
+    /// and behaviour configured for specific arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, i32>::new(0);
+    /// mock.return_value_for("banana", 5);
+    /// mock.return_values(vec!(1, 2, 3));
+    ///
+    /// // phase 1
+    /// mock.expect_call("banana");
+    /// assert_eq!(mock.call("apple"), 1);
+    /// assert_eq!(mock.call("banana"), 5);
+    /// mock.checkpoint();
+    ///
+    /// // phase 2
+    /// assert!(!mock.called());
+    /// assert_eq!(mock.call("apple"), 0);
+    /// assert_eq!(mock.call("banana"), 5);
+    /// assert!(mock.has_calls_exactly_in_order(vec!("apple", "banana")));
+    /// ```
+    ///
+    /// ```should_panic
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::default();
+    /// mock.expect_call("save");
+    /// mock.call("load");
+    ///
+    /// // panics, as "save" was never called
+    /// mock.checkpoint();
+    /// ```
+    pub fn checkpoint(&self) {
+        failure::assert_check(self.check_expectations());
+        self.expected_calls.borrow_mut().clear();
+        self.reset_calls();
+        *self.return_value_once.borrow_mut() = None;
+        if *self.sequence_mode.borrow() == SequenceMode::Drain {
+            self.return_value_sequence.borrow_mut().clear();
+        }
+    }
+
+    /// Returns `Ok` if every call expected using `Mock::expect_call` since
+    /// the last `Mock::checkpoint` was made, in any order. Otherwise, returns
+    /// a `CallMismatch` listing the expected calls that weren't made.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::default();
+    /// mock.expect_call("load");
+    /// mock.expect_call("save");
+    /// mock.call("load");
+    ///
+    /// let error = mock.check_expectations().unwrap_err();
+    /// assert_eq!(error.check(), "expect_call");
+    /// assert_eq!(
+    ///     error.expected(),
+    ///     "[\"load\", \"save\"], but no call matched the expectation at index 1");
+    ///
+    /// mock.call("save");
+    /// assert!(mock.check_expectations().is_ok());
+    /// ```
+    pub fn check_expectations(&self) -> Result<(), CallMismatch> {
+        let check = *self.expectations_check.borrow();
+        let expected_calls = self.expected_calls.borrow().clone();
+        match check {
+            Some(check) if !expected_calls.is_empty() => check(self, expected_calls)
+                .map_err(|mismatch| CallMismatch::new(
+                    "expect_call", mismatch.expected(), mismatch.actual_calls().to_vec())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Add `args` to the call history as if `Mock::call` had been called with
+    /// them, without selecting or constructing a return value.
+    ///
+    /// This is test infrastructure API, intended for testing assertion helpers
+    /// built on top of `Mock` against synthetic call histories. It requires
+    /// the `testing-internals` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::default();
+    /// mock.use_closure(Box::new(|_| panic!("never invoked")));
+    ///
+    /// mock.record_call_without_invoking("first");
+    /// mock.record_call_without_invoking("second");
+    ///
+    /// assert_eq!(mock.calls(), vec!("first", "second"));
+    /// ```
+    #[cfg(feature = "testing-internals")]
+    pub fn record_call_without_invoking<T: IntoCallArgs<C, M>, M>(&self, args: T) {
+        let time = self.current_time();
+        self.record_call(&args.into_call_args(), time);
+        *self.num_calls.borrow_mut() += 1;
+        self.notify_waiters();
+    }
+
+    /// Add each of `calls` to the call history, in order from first to last,
+    /// as if `Mock::call` had been called with them. Like
+    /// `Mock::record_call_without_invoking`, no return values are selected or
+    /// constructed. To copy the call history of another mock, pass it
+    /// `other.calls()`.
+    ///
+    /// This is test infrastructure API, intended for testing assertion helpers
+    /// built on top of `Mock` against synthetic call histories. It requires
+    /// the `testing-internals` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i32, i32), i32>::default();
+    /// mock.seed_calls(vec!((1, 2), (3, 4), (5, 6)));
+    ///
+    /// assert_eq!(mock.num_calls(), 3);
+    /// assert!(mock.has_calls_in_order(vec!((1, 2), (5, 6))));
+    /// assert!(!mock.has_calls_in_order(vec!((5, 6), (1, 2))));
+    /// ```
+    #[cfg(feature = "testing-internals")]
+    pub fn seed_calls<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) {
+        for args in calls {
+            self.record_call_without_invoking(args);
+        }
+    }
+
+    /// Stop recording the arguments passed to `Mock::call`. Subsequent calls
+    /// only increment the call count, so `args` are never cloned.
+    ///
+    /// `Mock::num_calls` and `Mock::called` remain accurate. However,
+    /// `Mock::calls`, `Mock::called_with` and the other argument checks only
+    /// see calls that were recorded before recording was disabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i64, i64>::new(0);
+    /// mock.call(1);
+    ///
+    /// mock.disable_call_recording();
+    /// mock.call(2);
+    /// mock.call(3);
+    ///
+    /// assert_eq!(mock.num_calls(), 3);
+    /// assert_eq!(mock.calls(), vec!(1));
+    /// assert!(!mock.called_with(2));
+    /// ```
+    pub fn disable_call_recording(&self) {
+        *self.record_calls.borrow_mut() = false;
+    }
+
+    /// Pass the args and live args of each subsequent call made using
+    /// `Mock::call_with_live_args` to `handler`, after the call has been
+    /// recorded and its return value has been chosen. The handler can use the
+    /// live args, e.g. by invoking callbacks with canned data.
+    ///
+    /// `L` must be the type of the live args passed to
+    /// `Mock::call_with_live_args`, which is a tuple if there are several.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    /// use std::cell::Cell;
+    ///
+    /// let mock = Mock::<u32, ()>::default();
+    /// mock.use_live_handler(Box::new(|id: u32, on_done: Box<dyn FnOnce(u32)>| on_done(id * 2)));
+    ///
+    /// let result = std::rc::Rc::new(Cell::new(0));
+    /// let result_setter = result.clone();
+    /// let on_done: Box<dyn FnOnce(u32)> = Box::new(move |x| result_setter.set(x));
+    /// mock.call_with_live_args(21, on_done);
+    ///
+    /// assert_eq!(result.get(), 42);
+    /// ```
+    pub fn use_live_handler<L: 'static>(&self, handler: Box<dyn_fn!(Fn(C, L))>)
+        where C: 'static
+    {
+        let handler: LiveHandlerFn<C, L> = Shared::from(handler);
+        *self.live_handler.borrow_mut() = Some(Shared::new(handler));
+    }
+
+    /// Set the value returned by a mocked method which returns `Self`, such
+    /// as `fn fork(&self) -> Self`. `mock_method!` returns a clone of
+    /// `prototype` from such methods taking `&self` or `&mut self`, or the
+    /// `Default` value of `Self` if no prototype was set.
+    ///
+    /// Clones of mocks share their configuration and calls, so if
+    /// `prototype` is a mock, the calls made to the returned mocks can be
+    /// checked using `prototype`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(), ()>::default();
+    /// assert_eq!(mock.self_prototype::<String>(), None);
+    ///
+    /// mock.return_self_prototype("prototype".to_owned());
+    /// assert_eq!(mock.self_prototype::<String>(), Some("prototype".to_owned()));
+    /// ```
+    pub fn return_self_prototype<S: Clone + ThreadSafe + 'static>(&self, prototype: S) {
+        *self.self_prototype.borrow_mut() = Some(Shared::new(prototype));
+    }
+
+    /// Returns a clone of the prototype set using
+    /// `Mock::return_self_prototype`, or `None` if no prototype was set.
+    ///
+    /// Panics if the prototype isn't of type `S`.
+    pub fn self_prototype<S: Clone + 'static>(&self) -> Option<S> {
+        self.self_prototype.borrow().as_ref().map(|prototype| {
+            prototype
+                .downcast_ref::<S>()
+                .expect("Mock::return_self_prototype was configured with a different type")
+                .clone()
+        })
+    }
+
+    /// Use `recorder` to transform the arguments of each subsequent call to
+    /// `Mock::call` before they're recorded. This bounds the memory used by
+    /// the call history when arguments are large, e.g. by truncating them.
+    ///
+    /// The mock's behaviour is still chosen using the original arguments,
+    /// but `Mock::calls`, `Mock::called_with` and the other argument checks
+    /// only see the transformed arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<Vec<u8>, usize>::new(0usize);
+    /// mock.return_value_for(vec!(1, 2, 3, 4, 5), 5usize);
+    /// mock.set_call_recorder(Box::new(|payload| payload.iter().take(2).cloned().collect()));
+    ///
+    /// assert_eq!(mock.call(vec!(1, 2, 3, 4, 5)), 5);
+    /// assert_eq!(mock.calls(), vec!(vec!(1, 2)));
+    /// ```
+    pub fn set_call_recorder(&self, recorder: Box<dyn_fn!(Fn(&C) -> C)>) {
+        *self.call_recorder.borrow_mut() = Some(Shared::from(recorder));
+    }
+
+    /// Only keep the `n` most recent calls in the call history, dropping the
+    /// oldest call each time a call is recorded once `n` calls are kept.
+    /// Calls already recorded are dropped straight away if there are more
+    /// than `n` of them.
+    ///
+    /// `Mock::num_calls` still counts every call, but `Mock::calls`,
+    /// `Mock::has_calls` and the other argument checks only see the calls
+    /// that were kept. Failed `check_` and `assert_` checks say how many
+    /// calls were dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i32, ()>::default();
+    /// mock.set_max_recorded_calls(2);
+    /// mock.call(1);
+    /// mock.call(2);
+    /// mock.call(3);
+    ///
+    /// assert_eq!(mock.num_calls(), 3);
+    /// assert_eq!(mock.calls(), vec!(2, 3));
+    /// assert!(!mock.called_with(1));
+    /// ```
+    pub fn set_max_recorded_calls(&self, n: usize) {
+        *self.max_recorded_calls.borrow_mut() = Some(n);
+        self.drop_oldest_calls();
+    }
+
+    /// Store each distinct set of recorded arguments once, rather than
+    /// storing a copy of the arguments of every call. The arguments of a
+    /// call are then only copied the first time they're seen, which makes
+    /// `Mock::call` cheaper for mocks called many times with the same
+    /// arguments. Calls already recorded are interned straight away.
+    ///
+    /// The checks behave the same, but reading the calls, e.g. using
+    /// `Mock::calls`, `Mock::with_calls` or any of the argument checks,
+    /// copies the arguments of every call each time. Interned arguments
+    /// aren't freed when calls are dropped by `Mock::set_max_recorded_calls`,
+    /// only by `Mock::reset_calls`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(String, u64), Option<u64>>::default();
+    /// mock.enable_arg_interning();
+    /// for _ in 0..1000 {
+    ///     mock.call(("hot key".to_owned(), 1));
+    /// }
+    /// mock.call(("cold key".to_owned(), 2));
+    ///
+    /// assert_eq!(mock.num_calls(), 1001);
+    /// assert!(mock.called_with(("cold key".to_owned(), 2)));
+    /// assert_eq!(mock.last_n_calls(2), vec!(
+    ///     ("hot key".to_owned(), 1),
+    ///     ("cold key".to_owned(), 2)));
+    /// ```
+    pub fn enable_arg_interning(&self) {
+        let mut interned_args = self.interned_args.borrow_mut();
+        if interned_args.is_none() {
+            let mut interned = InternedArgs::new();
+            for args in self.calls.borrow_mut().drain(..) {
+                interned.record(&args);
+            }
+            *interned_args = Some(interned);
+        }
+    }
+
+    // Creates a `Mock` with no behaviour configured. Callers must configure
+    // either a default return value or a producer before handing it out,
+    // unless the mock is meant to panic when called without being configured.
+    pub(crate) fn unconfigured() -> Self {
+        Mock {
+            default_return_value: OptionalRef::new(Cell::new(None)),
+            return_value_sequence: Ref::new(Cell::new(VecDeque::new())),
+            sequence_mode: Ref::new(Cell::new(SequenceMode::Drain)),
+            sequence_cursor: Ref::new(Cell::new(0)),
+            default_producer: OptionalRef::new(Cell::new(None)),
+            default_fn: OptionalRef::new(Cell::new(None)),
+            default_closure: OptionalRef::new(Cell::new(None)),
+            return_value_once: OptionalRef::new(Cell::new(None)),
+            pattern_return_values: Ref::new(Cell::new(Vec::new())),
+            return_values: Ref::new(Cell::new(HashMap::new())),
+            fns: Ref::new(Cell::new(HashMap::new())),
+            call_return_values: Ref::new(Cell::new(HashMap::new())),
+            nth_call_return_values: Ref::new(Cell::new(HashMap::new())),
+            closures: Ref::new(Cell::new(HashMap::new())),
+            failure_after: OptionalRef::new(Cell::new(None)),
+            panic_message: OptionalRef::new(Cell::new(None)),
+            panic_messages: Ref::new(Cell::new(HashMap::new())),
+            clone_fn: OptionalRef::new(Cell::new(None)),
+            expected_calls: Ref::new(Cell::new(Vec::new())),
+            expectations_check: OptionalRef::new(Cell::new(None)),
+            call_hooks: Ref::new(Cell::new(Vec::new())),
+            call_observers: Ref::new(Cell::new(Vec::new())),
+            live_handler: OptionalRef::new(Cell::new(None)),
+            self_prototype: OptionalRef::new(Cell::new(None)),
+            call_recorder: OptionalRef::new(Cell::new(None)),
+            max_recorded_calls: OptionalRef::new(Cell::new(None)),
+            calls: Ref::new(Cell::new(vec![])),
+            interned_args: OptionalRef::new(Cell::new(None)),
+            num_dropped_calls: Ref::new(Cell::new(0)),
+            returned_values: Ref::new(Cell::new(vec![])),
+            call_sources: Ref::new(Cell::new(vec![])),
+            call_times: Ref::new(Cell::new(vec![])),
+            clock: OptionalRef::new(Cell::new(None)),
+            call_timestamps: Ref::new(Cell::new(vec![])),
+            #[cfg(feature = "sync-mocks")]
+            call_signal: Shared::new((Mutex::new(()), Condvar::new())),
+            num_calls: Ref::new(Cell::new(0)),
+            num_calls_per_args: Ref::new(Cell::new(HashMap::new())),
+            calls_reader: Ref::new(Cell::new(None)),
+            record_calls: Ref::new(Cell::new(true)),
+        }
+    }
+
+    // Adds a call with `args`, transformed by the call recorder if there is
+    // one, to the call history and returns its index, counting calls that
+    // have since been dropped. The call's position in the history can change
+    // as older calls are dropped, so it's found using `recorded_position`.
+    fn record_call(&self, args: &C, time: u64) -> usize {
+        let call_recorder = self.call_recorder.borrow().clone();
+        let recorded_args = call_recorder.map(|call_recorder| call_recorder(args));
+        match *self.interned_args.borrow_mut() {
+            Some(ref mut interned_args) => {
+                interned_args.record(recorded_args.as_ref().unwrap_or(args))
+            }
+            None => {
+                let recorded_args = recorded_args.unwrap_or_else(|| args.clone());
+                self.calls.borrow_mut().push(recorded_args)
+            }
+        }
+        let index = {
+            let mut returned_values = self.returned_values.borrow_mut();
+            returned_values.push(None);
+            self.call_sources.borrow_mut().push(BehaviourSource::NotInvoked);
+            self.call_times.borrow_mut().push(time);
+            self.call_timestamps.borrow_mut().push(next_call_timestamp());
+            *self.num_dropped_calls.borrow() + returned_values.len() - 1
+        };
+        self.drop_oldest_calls();
+        index
+    }
+
+    // Returns the recorded calls, which are copied out of the interned args
+    // if argument interning is enabled.
+    fn recorded_calls(&self) -> RecordedCalls<'_, C> {
+        if let Some(ref interned_args) = *self.interned_args.borrow() {
+            return RecordedCalls::Materialized(interned_args.calls());
+        }
+        RecordedCalls::Borrowed(self.calls.borrow())
+    }
+
+    // Number of calls recorded since the calls were last reset, including
+    // calls dropped to keep within `Mock::set_max_recorded_calls`.
+    fn total_recorded_calls(&self) -> usize {
+        self.num_recorded_calls() + *self.num_dropped_calls.borrow()
+    }
+
+    // Wakes threads waiting for calls in `Mock::wait_until`.
+    #[cfg(feature = "sync-mocks")]
+    fn notify_waiters(&self) {
+        let (ref lock, ref condvar) = *self.call_signal;
+        let _guard = lock.borrow();
+        condvar.notify_all();
+    }
+
+    #[cfg(not(feature = "sync-mocks"))]
+    fn notify_waiters(&self) {}
+
+    // Describes the calls made before a wait for `expected` timed out.
+    #[cfg(feature = "sync-mocks")]
+    fn wait_timeout(&self, expected: String, elapsed: Duration) -> WaitTimeout<C> {
+        WaitTimeout::new(expected, self.num_calls(), self.calls(), elapsed)
+    }
+
+    // Blocks until `condition` returns true or `timeout` has elapsed, in
+    // which case the time spent waiting is returned. `condition` is checked
+    // while holding the lock that's held to notify waiters, so calls made
+    // between checks can't be missed.
+    #[cfg(feature = "sync-mocks")]
+    fn wait_until<F: Fn() -> bool>(&self, timeout: Duration, condition: F)
+        -> Result<(), Duration>
+    {
+        let start = Instant::now();
+        let deadline = start + timeout;
+        let (ref lock, ref condvar) = *self.call_signal;
+        let mut guard = lock.borrow();
+        loop {
+            if condition() {
+                return Ok(());
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(now - start);
+            }
+            guard = condvar
+                .wait_timeout(guard, deadline - now)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    fn num_recorded_calls(&self) -> usize {
+        self.returned_values.borrow().len()
+    }
+
+    // Returns the position in the call history of the call with `index`,
+    // or `None` if it's been dropped.
+    fn recorded_position(&self, index: usize) -> Option<usize> {
+        index.checked_sub(*self.num_dropped_calls.borrow())
+    }
+
+    // Drops calls from the start of the call history until it contains no
+    // more than `max_recorded_calls` calls.
+    fn drop_oldest_calls(&self) {
+        let max_recorded_calls = match *self.max_recorded_calls.borrow() {
+            Some(max_recorded_calls) => max_recorded_calls,
+            None => return,
+        };
+        let mut returned_values = self.returned_values.borrow_mut();
+        if returned_values.len() <= max_recorded_calls {
+            return;
+        }
+        let num_dropped = returned_values.len() - max_recorded_calls;
+        match *self.interned_args.borrow_mut() {
+            Some(ref mut interned_args) => {
+                interned_args.calls.drain(..num_dropped);
+            }
+            None => {
+                self.calls.borrow_mut().drain(..num_dropped);
+            }
+        }
+        returned_values.drain(..num_dropped);
+        self.call_sources.borrow_mut().drain(..num_dropped);
+        self.call_times.borrow_mut().drain(..num_dropped);
+        self.call_timestamps.borrow_mut().drain(..num_dropped);
+        *self.num_dropped_calls.borrow_mut() += num_dropped;
+    }
+
+    // Borrowing the calls again from the thread already borrowing them would
+    // panic with a `BorrowMutError`, or deadlock with the `sync-mocks`
+    // feature, so this panics with a clearer message instead.
+    fn assert_calls_not_borrowed(&self, method: &str) {
+        if *self.calls_reader.borrow() == Some(thread::current().id()) {
+            panic!("{} was called while the mock's calls were borrowed by \
+                    Mock::with_calls or Mock::fold_calls", method);
+        }
+    }
+
+    // Returns the number of calls with `args` made before this one.
+    fn count_call_with_args(&self, args: &C) -> usize {
+        if !self.nth_call_return_values.borrow().contains_key(args) {
+            return 0;
+        }
+        let mut num_calls_per_args = self.num_calls_per_args.borrow_mut();
+        if let Some(num_calls) = num_calls_per_args.get_mut(args) {
+            *num_calls += 1;
+            return *num_calls - 1;
+        }
+        num_calls_per_args.insert(args.clone(), 1);
+        0
+    }
+
+    fn pattern_return_value(&self, args: &C) -> Option<R> {
+        self.pattern_return_values.borrow()
+            .iter()
+            .find(|(_, pattern, _)| pattern(args))
+            .map(|(_, _, return_value)| self.clone_return_value(return_value))
+    }
+
+    fn run_call_hooks(&self, args: &C) {
+        // Copied, so hooks can call the mock too.
+        let call_hooks = self.call_hooks.borrow().clone();
+        for (hook_args, hook) in call_hooks.iter() {
+            match hook_args {
+                Some(hook_args) if hook_args != args => (),
+                _ => hook(args),
+            }
+        }
+    }
+
+    fn run_call_observers(&self, args: &C) {
+        // Copied, so observers can call the mock too.
+        let call_observers = self.call_observers.borrow().clone();
+        for observer in call_observers.iter() {
+            observer(args);
+        }
+    }
+
+    fn current_time(&self) -> u64 {
+        let clock = self.clock.borrow().clone();
+        match clock {
+            Some(clock) => clock(),
+            None => *self.num_calls.borrow() as u64,
+        }
+    }
+
+    // Number of values in the sequence if it's in `mode`, otherwise zero.
+    fn sequence_len(&self, mode: SequenceMode) -> usize {
+        if *self.sequence_mode.borrow() == mode {
+            self.return_value_sequence.borrow().len()
+        } else {
+            0
+        }
+    }
+
+    fn clone_return_value(&self, value: &R) -> R {
+        let clone_fn = self.clone_fn.borrow()
+            .expect("stored return values must be cloneable");
+        clone_fn(value)
+    }
+}
+
+impl<C, R> Clone for Mock<C, R>
+    where C: Clone + Eq + Hash
+{
+    /// Returns a `Mock` that shares its configured behaviour and call history
+    /// with this `Mock`. Configuring or calling either `Mock` affects both,
+    /// so clones act as handles to the same mock. Use `Mock::deep_clone` to
+    /// get an independent copy instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, i32>::new(1);
+    /// let handle = mock.clone();
+    /// handle.return_value(2);
+    /// handle.call("banana");
+    ///
+    /// assert_eq!(mock.num_calls(), 1);
+    /// assert_eq!(mock.call("banana"), 2);
+    /// ```
+    fn clone(&self) -> Self {
+        Mock {
+            default_return_value: self.default_return_value.clone(),
+            return_value_sequence: self.return_value_sequence.clone(),
+            sequence_mode: self.sequence_mode.clone(),
+            sequence_cursor: self.sequence_cursor.clone(),
+            default_producer: self.default_producer.clone(),
+            default_fn: self.default_fn.clone(),
+            default_closure: self.default_closure.clone(),
+            return_value_once: self.return_value_once.clone(),
+            pattern_return_values: self.pattern_return_values.clone(),
+            return_values: self.return_values.clone(),
+            fns: self.fns.clone(),
+            call_return_values: self.call_return_values.clone(),
+            nth_call_return_values: self.nth_call_return_values.clone(),
+            closures: self.closures.clone(),
+            failure_after: self.failure_after.clone(),
+            panic_message: self.panic_message.clone(),
+            panic_messages: self.panic_messages.clone(),
+            clone_fn: self.clone_fn.clone(),
+            expected_calls: self.expected_calls.clone(),
+            expectations_check: self.expectations_check.clone(),
+            call_hooks: self.call_hooks.clone(),
+            call_observers: self.call_observers.clone(),
+            live_handler: self.live_handler.clone(),
+            self_prototype: self.self_prototype.clone(),
+            call_recorder: self.call_recorder.clone(),
+            max_recorded_calls: self.max_recorded_calls.clone(),
+            calls: self.calls.clone(),
+            interned_args: self.interned_args.clone(),
+            num_dropped_calls: self.num_dropped_calls.clone(),
+            returned_values: self.returned_values.clone(),
+            call_sources: self.call_sources.clone(),
+            call_times: self.call_times.clone(),
+            clock: self.clock.clone(),
+            call_timestamps: self.call_timestamps.clone(),
+            #[cfg(feature = "sync-mocks")]
+            call_signal: self.call_signal.clone(),
+            num_calls: self.num_calls.clone(),
+            num_calls_per_args: self.num_calls_per_args.clone(),
+            calls_reader: self.calls_reader.clone(),
+            record_calls: self.record_calls.clone(),
+        }
+    }
+}
+
+impl<C, R> IntoIterator for &Mock<C, R>
+    where C: Clone + Eq + Hash
+{
+    type Item = C;
+    type IntoIter = std::vec::IntoIter<C>;
+
+    /// Iterates over the arguments of each call, like `Mock::iter`.
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<C, R> Default for Mock<C, R>
+    where C: Clone + Eq + Hash,
+          R: Clone + Default
+{
+    /// Use `R::default()` as the initial return value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i64, i64>::default();
+    /// assert_eq!(mock.call(10), 0);
+    ///
+    /// let mock = Mock::<(), String>::default();
+    /// assert_eq!(&mock.call(()), "");
+    ///
+    /// let mock = Mock::<(i64, &str), Option<bool>>::default();
+    /// assert_eq!(mock.call((10, "test")), None);
+    /// ```
+    fn default() -> Self {
+        Self::new(R::default())
+    }
+}
+
+impl<C, R> Mock<C, R>
+    where C: Clone + Eq + Hash,
+          R: Clone + Default
+{
+    /// Revert the `Mock`'s default behaviour to returning `R::default()`.
+    ///
+    /// This clears any configured default function, closure, producer,
+    /// return value sequences and one-shot return value. Behaviour configured
+    /// for specific arguments is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, i32>::default();
+    /// mock.return_value(42);
+    /// mock.return_values(vec!(1, 2));
+    /// mock.use_closure(Box::new(|_| 7));
+    /// mock.return_value_for("banana", 5);
+    /// assert_eq!(mock.call("something"), 7);
+    ///
+    /// mock.return_default();
+    /// assert_eq!(mock.call("something"), 0);
+    /// assert_eq!(mock.call("banana"), 5);
+    /// ```
+    pub fn return_default(&self) {
+        self.use_value_only(R::default());
+    }
+}
+
+impl<C, R> Mock<C, R>
+    where C: Clone + Debug + Eq + Hash
+{
+    // ========================================================================
+    // * Exact Argument Checks
+    // ========================================================================
+
+    /// Returns true if the specified argument has been used for `Mock::call`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::new(());
+    /// mock.call("foo");
+    /// mock.call("bar");
+    ///
+    /// assert!(mock.called_with("foo"));
+    /// assert!(mock.called_with("bar"));
+    /// assert!(!mock.called_with("baz"));
+    /// ```
+    pub fn called_with<T: IntoCallArgs<C, M>, M>(&self, args: T) -> bool {
+        let expected_calls: Vec<T> = vec!(args);
+        self.get_match_info(expected_calls).expectations_matched()
+    }
+
+    /// Returns true if the specified argument has never been used for
+    /// `Mock::call`. This is the negation of `Mock::called_with`.
+    ///
+    /// Use `Mock::check_not_called_with` to find out which call used the
+    /// argument when this returns false.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::new(());
+    /// mock.call("guest");
+    /// mock.call("admin");
+    ///
+    /// assert!(mock.not_called_with("root"));
+    /// assert!(!mock.not_called_with("admin"));
+    ///
+    /// let mismatch = mock.check_not_called_with("admin").unwrap_err();
+    /// assert_eq!(mismatch.expected(), "no call with \"admin\", but call 1 matched");
+    /// ```
+    pub fn not_called_with<T: IntoCallArgs<C, M>, M>(&self, args: T) -> bool {
+        !self.called_with(args)
+    }
+
+    /// Returns true if `Mock::call` has not been called with any of the
+    /// specified `calls`. Unlike negating `Mock::has_calls`, which fails if
+    /// any of `calls` is missing, this fails if any of `calls` was made.
+    /// `Mock::check_has_no_calls` describes every forbidden call that was
+    /// made when this returns false.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::new(());
+    /// assert!(mock.has_no_calls(vec!("root", "admin")));
+    ///
+    /// mock.call("guest");
+    /// mock.call("admin");
+    /// mock.call("root");
+    ///
+    /// assert!(mock.has_no_calls(vec!("nobody")));
+    /// assert!(!mock.has_no_calls(vec!("root", "admin")));
+    ///
+    /// let mismatch = mock.check_has_no_calls(vec!("root", "admin")).unwrap_err();
+    /// assert_eq!(
+    ///     mismatch.expected(),
+    ///     "no calls with any of [\"root\", \"admin\"], but call 1 (\"admin\") and call 2 (\"root\") were made");
+    /// ```
+    pub fn has_no_calls<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> bool {
+        let calls: Vec<C> = calls.into_iter().map(|c| c.into_call_args()).collect();
+        self.violating_calls(&|args| calls.contains(args)).is_empty()
+    }
+
+    /// Returns true if `Mock::call` has been called with all of the specified
+    /// `calls`. The calls can be made in any order.  They don't have to be in
+    /// the order specified by `calls`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::new(());
+    /// mock.call("foo");
+    /// mock.call("bar");
+    ///
+    /// let expected_calls1 = vec!("foo", "bar");
+    /// assert!(mock.has_calls(expected_calls1));
+    /// let expected_calls2 = vec!("bar", "foo");
+    /// assert!(mock.has_calls(expected_calls2));
+    /// let expected_calls3 = vec!("foo");
+    /// assert!(mock.has_calls(expected_calls3));
+    /// let expected_calls4 = vec!("not_in_calls");
+    /// assert!(!mock.has_calls(expected_calls4));
+    /// let expected_calls5 = vec!("foo", "not_in_calls");
+    /// assert!(!mock.has_calls(expected_calls5));
+    /// ```
+    pub fn has_calls<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> bool {
+        self.get_match_info(calls).expectations_matched()
+    }
+
+    /// Returns true if `Mock::call` has been called with all of the specified
+    /// `calls`. The `calls` must be made in the order they are specified in
+    /// the vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i32, i32), ()>::new(());
+    /// mock.call((42, 0));
+    /// mock.call((42, 1));
+    /// mock.call((42, 0));  // called with same args as first call!
+    ///
+    /// assert!(mock.has_calls_in_order(vec!( (42, 0) )));
+    /// assert!(mock.has_calls_in_order(vec!( (42, 1) )));
+    /// assert!(mock.has_calls_in_order(vec!( (42, 0), (42, 1) )));
+    /// assert!(mock.has_calls_in_order(vec!( (42, 1), (42, 0) )));
+    /// assert!(mock.has_calls_in_order(vec!( (42, 0), (42, 1), (42, 0) )));
+    /// assert!(!mock.has_calls_in_order(vec!( (42, 0), (42, 0), (42, 1) )));
+    /// assert!(!mock.has_calls_in_order(vec!( (84, 0) )));
+    /// assert!(!mock.has_calls_in_order(vec!( (42, 0), (84, 0) )));
+    /// ```
+    pub fn has_calls_in_order<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> bool {
+        self.get_match_info(calls).expectations_matched_in_order()
+    }
+
+    /// Returns true if `Mock::call` has been called with all of the specified
+    /// `calls` and it has not been called any other times. The calls can be
+    /// made in any order. They don't have to be in the order specified by
+    /// `calls`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i32, i32), ()>::new(());
+    /// mock.call((42, 0));
+    /// mock.call((42, 1));
+    /// mock.call((42, 0));
+    ///
+    /// assert!(!mock.has_calls_exactly(vec!( (42, 0) )));
+    /// assert!(!mock.has_calls_exactly(vec!( (42, 1) )));
+    /// assert!(!mock.has_calls_exactly(vec!( (84, 0) )));
+    /// assert!(!mock.has_calls_exactly(vec!( (42, 0), (42, 1) )));
+    /// assert!(!mock.has_calls_exactly(vec!( (42, 1), (42, 0) )));
+    /// assert!(mock.has_calls_exactly(vec!( (42, 0), (42, 0), (42, 1) )));
+    /// assert!(mock.has_calls_exactly(vec!( (42, 0), (42, 1), (42, 0) )));
+    /// assert!(!mock.has_calls_exactly(vec!( (42, 0), (42, 1), (84, 0) )));
+    /// ```
+    pub fn has_calls_exactly<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> bool {
+        self.get_match_info(calls).expectations_matched_exactly()
+    }
+
+    /// Returns true if `Mock::call` has been called with all of the specified
+    /// `calls` and it has not been called any other times. The calls must be
+    /// made in the order they are specified in `calls`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::new(());
+    /// mock.call("foo");
+    /// mock.call("bar");
+    ///
+    /// let expected_calls1 = vec!("foo", "bar");
+    /// assert!(mock.has_calls_exactly_in_order(expected_calls1));
+    /// let expected_calls2 = vec!("bar", "foo");
+    /// assert!(!mock.has_calls_exactly_in_order(expected_calls2));
+    /// let expected_calls3 = vec!("foo");
+    /// assert!(!mock.has_calls_exactly_in_order(expected_calls3));
+    /// let expected_calls4 = vec!("bar");
+    /// assert!(!mock.has_calls_exactly_in_order(expected_calls4));
+    pub fn has_calls_exactly_in_order<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> bool {
+        self.get_match_info(calls).expectations_matched_in_order_exactly()
+    }
+
+    /// Returns an `ExpectedCalls` builder for the calls this mock should
+    /// have received.
+    ///
+    /// Unlike `Mock::has_calls` and friends, the builder's `call` method
+    /// takes exactly `C`, with no `Into` conversion. An expectation of the
+    /// wrong type is reported where it's written, instead of compiling and
+    /// then never matching.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(u32, i32), ()>::default();
+    /// mock.call((500, 250));
+    /// mock.call((0, 0));
+    ///
+    /// assert!(mock.expect().call((500, 250)).call((0, 0)).verify_in_order().is_ok());
+    /// assert!(mock.expect().call((0, 0)).verify_any_order().is_ok());
+    ///
+    /// let error = mock.expect().call((0, 0)).call((500, 250)).verify_in_order().unwrap_err();
+    /// assert_eq!(
+    ///     error,
+    ///     "Mock::has_calls_in_order failed\n  expected: [(0, 0), (500, 250)]\n  actual calls: [(500, 250), (0, 0)]");
+    /// ```
+    ///
+    /// ```compile_fail,E0308
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(u32, i32), ()>::default();
+    /// mock.expect().call((500u32, 250u32)).verify_in_order().unwrap();
+    /// ```
+    pub fn expect(&self) -> ExpectedCalls<'_, C, R> {
+        ExpectedCalls {
+            mock: self,
+            calls: Vec::new(),
+        }
+    }
+
+    /// Expect a call with `args` to be made before the next
+    /// `Mock::checkpoint`, which panics if the call wasn't made. Expected
+    /// calls can be made in any order, and other calls are allowed. Use
+    /// `Mock::check_expectations` to verify them without a checkpoint.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(u32, u32), ()>::default();
+    /// mock.expect_call((1, 2));
+    /// mock.expect_call((3, 4));
+    ///
+    /// mock.call((3, 4));
+    /// mock.call((5, 6));
+    /// mock.call((1, 2));
+    /// mock.checkpoint();
+    /// ```
+    pub fn expect_call<T: IntoCallArgs<C, M>, M>(&self, args: T) {
+        self.expected_calls.borrow_mut().push(args.into_call_args());
+        *self.expectations_check.borrow_mut() =
+            Some(Self::check_has_calls::<C, ConvertWhole>);
+    }
+
+    // ========================================================================
+    // * Pattern Matching Argument Checks
+    // ========================================================================
+
+    // There are apparently plans for the Rust compiler to support associated
+    // types in concrete `impl`s. This would allow the matcher function
+    // signature to be aliased, like below:
+    //
+    // type Matcher = dyn Fn(&C) -> bool;
+    //
+    // TODO: define the above type alias when possible and use that instead of
+    // explicitly defining the function signature everywhere.
+
+    /// Returns true if an argument set passed into `Mock::call` matches the
+    /// specified `pattern`.
+    ///
+    /// A `pattern` is defined a function that receives a tuple containing
+    /// all of a single call's arguments, checks the values of the arguments
+    /// and returns `true` if the args "matched" the pattern and `false`
+    /// otherwise. See the
+    /// [double repository's README.md](https://github.com/DonaldWhyte/double)
+    /// for more information on this.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i32, i32), ()>::new(());
+    /// mock.call((42, 0));
+    /// mock.call((42, 1));
+    ///
+    /// let pattern1 = |args: &(i32, i32)| args.0 == 42 && args.1 != 0;
+    /// let pattern2 = |args: &(i32, i32)| args.0 == 42 && args.1 == 0;
+    /// let pattern3 = |args: &(i32, i32)| args.0 == 84;
+    ///
+    /// assert!(mock.called_with_pattern(&pattern1));
+    /// assert!(mock.called_with_pattern(&pattern2));
+    /// assert!(!mock.called_with_pattern(&pattern3));
+    /// ```
+    pub fn called_with_pattern(&self, pattern: &dyn Fn(&C) -> bool) -> bool {
+        let patterns: Vec<&dyn Fn(&C) -> bool> = vec!(pattern);
+        self.get_match_info_pattern(patterns).expectations_matched()
+    }
+
+    /// Blocks until `Mock::call` has been called at least `n` times in
+    /// total, e.g. by a background thread, or until `timeout` has elapsed.
+    /// Returns `Ok` if the calls were made, or a `WaitTimeout` describing
+    /// the calls made so far if it timed out. Returns immediately if the
+    /// calls were made before it was called.
+    ///
+    /// Waiting threads are woken by each call, so tests don't need to poll
+    /// the mock in a loop. Requires the `sync-mocks` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let flush = Mock::<(), ()>::default();
+    /// let worker_flush = flush.clone();
+    /// let worker = thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(10));
+    ///     worker_flush.call(());
+    /// });
+    ///
+    /// assert!(flush.try_wait_for_calls(1, Duration::from_secs(2)).is_ok());
+    /// let timeout = flush.try_wait_for_calls(2, Duration::from_millis(10)).unwrap_err();
+    /// assert_eq!(timeout.observed(), 1);
+    /// worker.join().unwrap();
+    /// ```
+    #[cfg(feature = "sync-mocks")]
+    pub fn try_wait_for_calls(&self, n: usize, timeout: Duration) -> Result<(), WaitTimeout<C>> {
+        self.wait_until(timeout, || self.num_calls() >= n)
+            .map_err(|elapsed| self.wait_timeout(plural(n, "call", "calls"), elapsed))
+    }
+
+    /// Returns true if `Mock::try_wait_for_calls` returns `Ok`, i.e. if at
+    /// least `n` calls were made before `timeout` elapsed. Requires the
+    /// `sync-mocks` feature.
+    #[cfg(feature = "sync-mocks")]
+    pub fn wait_for_calls(&self, n: usize, timeout: Duration) -> bool {
+        self.try_wait_for_calls(n, timeout).is_ok()
+    }
+
+    /// Blocks until `Mock::call` has been called with args matching
+    /// `pattern`, or until `timeout` has elapsed. Returns `Ok` if such a call
+    /// was made, or a `WaitTimeout` describing the calls made so far if it
+    /// timed out. Returns immediately if a matching call was already
+    /// recorded. See `Mock::try_wait_for_calls`.
+    ///
+    /// Only recorded calls are matched against `pattern`. Requires the
+    /// `sync-mocks` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let write = Mock::<String, ()>::default();
+    /// let worker_write = write.clone();
+    /// let worker = thread::spawn(move || {
+    ///     worker_write.call("header".to_owned());
+    ///     worker_write.call("body".to_owned());
+    /// });
+    ///
+    /// assert!(write.try_wait_for_call_matching(&|args| args == "body", Duration::from_secs(2)).is_ok());
+    /// worker.join().unwrap();
+    /// ```
+    #[cfg(feature = "sync-mocks")]
+    pub fn try_wait_for_call_matching(&self, pattern: &dyn Fn(&C) -> bool, timeout: Duration)
+        -> Result<(), WaitTimeout<C>>
+    {
+        self.wait_until(timeout, || self.recorded_calls().iter().any(pattern))
+            .map_err(|elapsed| self.wait_timeout("a call matching the pattern".to_owned(), elapsed))
+    }
+
+    /// Returns true if `Mock::try_wait_for_call_matching` returns `Ok`, i.e.
+    /// if a call matching `pattern` was made before `timeout` elapsed.
+    /// Requires the `sync-mocks` feature.
+    #[cfg(feature = "sync-mocks")]
+    pub fn wait_for_call_matching(&self, pattern: &dyn Fn(&C) -> bool, timeout: Duration)
+        -> bool
+    {
+        self.try_wait_for_call_matching(pattern, timeout).is_ok()
+    }
+
+    /// Returns true if no call to `Mock::call` matches the specified
+    /// `pattern`. This is true if the `Mock` has not been called.
+    /// `Mock::check_has_no_calls_matching` describes every call that matched
+    /// when this returns false.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use] extern crate double;
+    /// use double::Mock;
+    /// use double::matcher::*;
+    ///
+    /// # fn main() {
+    /// let mock = Mock::<(i32, &str), ()>::new(());
+    /// mock.call((42, "first"));
+    /// mock.call((7, "second"));
+    ///
+    /// assert!(mock.has_no_calls_matching(matcher!(p!(lt, 0), p!(any))));
+    /// assert!(!mock.has_no_calls_matching(matcher!(p!(gt, 5), p!(any))));
+    /// # }
+    /// ```
+    pub fn has_no_calls_matching(&self, pattern: &dyn Fn(&C) -> bool) -> bool {
+        self.violating_calls(pattern).is_empty()
+    }
+
+    /// Returns true if the most recent call to `Mock::call` matches the
+    /// specified `pattern`. Returns false if the `Mock` has not been called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use] extern crate double;
+    /// use double::Mock;
+    /// use double::matcher::*;
+    ///
+    /// # fn main() {
+    /// let mock = Mock::<(i32, &str), ()>::new(());
+    /// assert!(!mock.last_call_matches(matcher!(p!(any), p!(any))));
+    ///
+    /// mock.call((42, "first"));
+    /// mock.call((7, "second"));
+    ///
+    /// assert!(mock.last_call_matches(matcher!(p!(lt, 10), p!(eq, "second"))));
+    /// assert!(!mock.last_call_matches(matcher!(p!(eq, 42), p!(any))));
+    /// # }
+    /// ```
+    pub fn last_call_matches(&self, pattern: &dyn Fn(&C) -> bool) -> bool {
+        match self.recorded_calls().last() {
+            Some(args) => pattern(args),
+            None => false,
+        }
+    }
+
+    /// Returns true if exactly one call to `Mock::call` matches the specified
+    /// `pattern`. Other calls that don't match the pattern are allowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use] extern crate double;
+    /// use double::Mock;
+    /// use double::matcher::*;
+    ///
+    /// # fn main() {
+    /// let mock = Mock::<(i32, &str), ()>::new(());
+    /// mock.call((42, "first"));
+    /// mock.call((7, "second"));
+    /// mock.call((42, "third"));
+    ///
+    /// assert!(mock.exactly_one_call_matches(matcher!(p!(lt, 10), p!(any))));
+    /// assert!(!mock.exactly_one_call_matches(matcher!(p!(eq, 42), p!(any))));
+    /// assert!(!mock.exactly_one_call_matches(matcher!(p!(any), p!(eq, "fourth"))));
+    /// # }
+    /// ```
+    pub fn exactly_one_call_matches(&self, pattern: &dyn Fn(&C) -> bool) -> bool {
+        self.recorded_calls().iter().filter(|args| pattern(args)).count() == 1
+    }
+
+    /// Returns true if `Mock::call` has been called with all of the specified
+    /// `patterns`. The calls can be made in any order. They don't have to be
+    /// in the order specified by `patterns`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i32, i32), ()>::new(());
+    /// mock.call((42, 0));
+    /// mock.call((42, 1));
+    ///
+    /// let pattern1 = |args: &(i32, i32)| args.0 == 42 && args.1 != 0;
+    /// let pattern2 = |args: &(i32, i32)| args.0 == 42 && args.1 == 0;
+    /// let pattern3 = |args: &(i32, i32)| args.0 == 84;
+    ///
+    /// assert!(mock.has_patterns(vec!(&pattern1)));
+    /// assert!(mock.has_patterns(vec!(&pattern2)));
+    /// assert!(mock.has_patterns(vec!(&pattern1, &pattern2)));
+    /// assert!(mock.has_patterns(vec!(&pattern2, &pattern1)));
+    /// assert!(!mock.has_patterns(vec!(&pattern3)));
+    /// assert!(!mock.has_patterns(vec!(&pattern1, &pattern3)));
+    /// ```
+    pub fn has_patterns(&self, patterns: Vec<&dyn Fn(&C) -> bool>) -> bool {
+        self.get_match_info_pattern(patterns).expectations_matched()
+    }
+
+    /// Returns true if `Mock::call` has been called with all of the specified
+    /// `patterns`. The `patterns` must be made in the order they are specified
+    /// in the input vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i32, i32), ()>::new(());
+    /// mock.call((42, 0));
+    /// mock.call((42, 1));
+    /// mock.call((42, 0));  // called with same args as first call!
+    ///
+    /// let pattern1 = |args: &(i32, i32)| args.0 == 42 && args.1 != 0;
+    /// let pattern2 = |args: &(i32, i32)| args.0 == 42 && args.1 == 0;
+    /// let pattern3 = |args: &(i32, i32)| args.0 == 84;
+    ///
+    /// assert!(mock.has_patterns_in_order(vec!(&pattern1)));
+    /// assert!(mock.has_patterns_in_order(vec!(&pattern2)));
+    /// assert!(mock.has_patterns_in_order(vec!(&pattern1, &pattern2)));
+    /// assert!(mock.has_patterns_in_order(vec!(&pattern2, &pattern1)));
+    /// assert!(mock.has_patterns_in_order(vec!(&pattern2, &pattern1, &pattern2)));
+    /// assert!(!mock.has_patterns_in_order(vec!(&pattern1, &pattern2, &pattern1)));
+    /// assert!(!mock.has_patterns_in_order(vec!(&pattern1, &pattern1, &pattern2)));
+    /// assert!(!mock.has_patterns_in_order(vec!(&pattern2, &pattern2, &pattern1)));
+    /// assert!(!mock.has_patterns_in_order(vec!(&pattern3)));
+    /// assert!(!mock.has_patterns_in_order(vec!(&pattern1, &pattern3)));
+    /// ```
+    pub fn has_patterns_in_order(&self, patterns: Vec<&dyn Fn(&C) -> bool>) -> bool {
+        self.get_match_info_pattern(patterns).expectations_matched_in_order()
+    }
+
+    /// Returns true if `Mock::call` has been called with all of the specified
+    /// `patterns` and it has not been called any other times. The calls can be
+    /// made in any order. They don't have to be in the order specified by
+    /// `patterns`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i32, i32), ()>::new(());
+    /// mock.call((42, 0));
+    /// mock.call((42, 1));
+    /// mock.call((42, 0));
+    ///
+    /// let pattern1 = |args: &(i32, i32)| args.0 == 42 && args.1 != 0;
+    /// let pattern2 = |args: &(i32, i32)| args.0 == 42 && args.1 == 0;
+    /// let pattern3 = |args: &(i32, i32)| args.0 == 84;
+    ///
+    /// assert!(!mock.has_patterns_exactly(vec!(&pattern1)));
+    /// assert!(!mock.has_patterns_exactly(vec!(&pattern2)));
+    /// assert!(!mock.has_patterns_exactly(vec!(&pattern3)));
+    /// assert!(!mock.has_patterns_exactly(vec!(&pattern1, &pattern2)));
+    /// assert!(!mock.has_patterns_exactly(vec!(&pattern2, &pattern1)));
+    /// assert!(mock.has_patterns_exactly(vec!(&pattern1, &pattern1, &pattern2)));
+    /// assert!(mock.has_patterns_exactly(vec!(&pattern1, &pattern2, &pattern1)));
+    /// assert!(!mock.has_patterns_exactly(vec!(&pattern1, &pattern2, &pattern3)));
+    /// ```
+    pub fn has_patterns_exactly(&self, patterns: Vec<&dyn Fn(&C) -> bool>) -> bool {
+        self.get_match_info_pattern(patterns).expectations_matched_exactly()
+    }
+
+    /// Returns true if `Mock::call` has been called with all of the specified
+    /// `patterns` and it has not been called any other times. The calls must
+    /// be made match the patterns in the same order as specified in the
+    /// `patterns` vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i32, i32), ()>::new(());
+    /// mock.call((42, 0));
+    /// mock.call((42, 1));
+    /// mock.call((42, 0));  // called with same args as first call!
+    ///
+    /// let pattern1 = |args: &(i32, i32)| args.0 == 42 && args.1 != 0;
+    /// let pattern2 = |args: &(i32, i32)| args.0 == 42 && args.1 == 0;
+    /// let pattern3 = |args: &(i32, i32)| args.0 == 84;
+    ///
+    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern1)));
+    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern2)));
+    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern1, &pattern2)));
+    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern2, &pattern1)));
+    /// assert!(mock.has_patterns_exactly_in_order(vec!(&pattern2, &pattern1, &pattern2)));
+    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern1, &pattern2, &pattern1)));
+    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern1, &pattern1, &pattern2)));
+    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern2, &pattern2, &pattern1)));
+    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern3)));
+    /// assert!(!mock.has_patterns_exactly_in_order(vec!(&pattern1, &pattern3)));
+    /// ```
+    pub fn has_patterns_exactly_in_order(&self, patterns: Vec<&dyn Fn(&C) -> bool>) -> bool {
+        self.get_match_info_pattern(patterns).expectations_matched_in_order_exactly()
+    }
+
+    // ========================================================================
+    // * Checks Returning `Result`
+    // ========================================================================
+
+    /// Returns `Ok` if `Mock::called` would return `true`. Otherwise, returns
+    /// a `CallMismatch` describing the failure.
+    ///
+    /// See the `failure` module for how the `bool`, `check_` and `assert_`
+    /// shapes of each check relate to each other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i32, ()>::default();
+    /// assert!(mock.check_called().is_err());
+    ///
+    /// mock.call(1);
+    /// assert!(mock.check_called().is_ok());
+    /// ```
+    pub fn check_called(&self) -> Result<(), CallMismatch> {
+        self.check("called", self.called(), "at least one call".to_owned())
+    }
+
+    /// Returns `Ok` if `Mock::calls_in_count_range` would return `true`.
+    /// Otherwise, returns a `CallMismatch` whose expectation states the
+    /// expected range and the number of calls actually made.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i32, ()>::default();
+    /// mock.call(1);
+    ///
+    /// assert!(mock.check_calls_in_count_range(1, 2).is_ok());
+    ///
+    /// let mismatch = mock.check_calls_in_count_range(2, 3).unwrap_err();
+    /// assert_eq!(mismatch.expected(), "between 2 and 3 calls, but 1 was made");
+    /// ```
+    pub fn check_calls_in_count_range(&self, min: usize, max: usize)
+        -> Result<(), CallMismatch>
+    {
+        let num_calls = self.num_calls();
+        let expected = format!(
+            "between {} and {} calls, but {} {} made",
+            min,
+            max,
+            num_calls,
+            if num_calls == 1 { "was" } else { "were" });
+        self.check("calls_in_count_range", self.calls_in_count_range(min, max), expected)
+    }
+
+    /// Returns `Ok` if `Mock::called_with` would return `true`. Otherwise,
+    /// returns a `CallMismatch` describing the failure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::default();
+    /// mock.call("foo");
+    ///
+    /// assert!(mock.check_called_with("foo").is_ok());
+    ///
+    /// let mismatch = mock.check_called_with("bar").unwrap_err();
+    /// assert_eq!(mismatch.expected(), "\"bar\"");
+    /// assert_eq!(mismatch.actual_calls(), ["\"foo\""]);
+    /// ```
+    ///
+    /// If `args` is a tuple and the closest call to it, as returned by
+    /// `Mock::closest_call`, matches some of its positions, the expectation
+    /// also names the positions which differ:
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i32, i32), ()>::default();
+    /// mock.call((100, 50));
+    /// mock.call((500, 251));
+    ///
+    /// let mismatch = mock.check_called_with((500, 250)).unwrap_err();
+    /// assert_eq!(
+    ///     mismatch.expected(),
+    ///     "(500, 250) (closest actual call: (500, 251) differs in position 1)");
+    /// ```
+    pub fn check_called_with<T: IntoCallArgs<C, M>, M>(&self, args: T) -> Result<(), CallMismatch> {
+        let args = args.into_call_args();
+        let mut expected = format!("{:?}", args);
+        if let Some(closest_call) = self.closest_call(args.clone()) {
+            let closest_call = format!("{:?}", closest_call);
+            let differing = differing_debug_positions(&expected, &closest_call);
+            let num_positions = debug_positions(&expected).len();
+            if num_positions > 1 && differing.len() < num_positions {
+                let positions: Vec<String> = differing.iter().map(|p| p.to_string()).collect();
+                expected = format!(
+                    "{} (closest actual call: {} differs in position{} {})",
+                    expected,
+                    closest_call,
+                    if positions.len() == 1 { "" } else { "s" },
+                    positions.join(", "));
+            }
+        }
+        self.check("called_with", self.called_with(args), expected)
+    }
+
+    /// Returns `Ok` if `Mock::not_called_with` would return `true`. Otherwise,
+    /// returns a `CallMismatch` whose expectation names the index of the
+    /// first call made with `args`.
+    pub fn check_not_called_with<T: IntoCallArgs<C, M>, M>(&self, args: T) -> Result<(), CallMismatch> {
+        let args = args.into_call_args();
+        let violating_call = self.recorded_calls().iter().position(|call| *call == args);
+        let expected = match violating_call {
+            Some(position) => format!(
+                "no call with {:?}, but call {} matched",
+                args,
+                position + *self.num_dropped_calls.borrow()),
+            None => format!("no call with {:?}", args),
+        };
+        self.check("not_called_with", violating_call.is_none(), expected)
+    }
+
+    /// Returns `Ok` if `Mock::has_no_calls` would return `true`. Otherwise,
+    /// returns a `CallMismatch` whose expectation lists the index and
+    /// arguments of every forbidden call that was made.
+    pub fn check_has_no_calls<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> Result<(), CallMismatch> {
+        let calls: Vec<C> = calls.into_iter().map(|c| c.into_call_args()).collect();
+        let violations = self.violating_calls(&|args| calls.contains(args));
+        let expected = format!("no calls with any of {:?}", calls);
+        self.check_no_violations("has_no_calls", expected, violations)
+    }
+
+    /// Returns `Ok` if `Mock::has_calls` would return `true`. Otherwise,
+    /// returns a `CallMismatch` describing the failure.
+    pub fn check_has_calls<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> Result<(), CallMismatch> {
+        let calls: Vec<C> = calls.into_iter().map(|c| c.into_call_args()).collect();
+        let expected = format!("{:?}", calls);
+        let match_info = self.get_match_info(calls);
+        self.check("has_calls", match_info.expectations_matched(), match_info.describe_expected(expected))
+    }
+
+    /// Returns `Ok` if `Mock::has_calls_in_order` would return `true`. Otherwise,
+    /// returns a `CallMismatch` describing the failure.
+    pub fn check_has_calls_in_order<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> Result<(), CallMismatch> {
+        let calls: Vec<C> = calls.into_iter().map(|c| c.into_call_args()).collect();
+        let expected = format!("{:?}", calls);
+        let match_info = self.get_match_info(calls);
+        self.check("has_calls_in_order", match_info.expectations_matched_in_order(), match_info.describe_expected(expected))
+    }
+
+    /// Returns `Ok` if `Mock::has_calls_exactly` would return `true`. Otherwise,
+    /// returns a `CallMismatch` describing the failure.
+    pub fn check_has_calls_exactly<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> Result<(), CallMismatch> {
+        let calls: Vec<C> = calls.into_iter().map(|c| c.into_call_args()).collect();
+        let expected = format!("{:?}", calls);
+        let match_info = self.get_match_info(calls);
+        self.check("has_calls_exactly", match_info.expectations_matched_exactly(), match_info.describe_expected(expected))
+    }
+
+    /// Returns `Ok` if `Mock::has_calls_exactly_in_order` would return `true`. Otherwise,
+    /// returns a `CallMismatch` describing the failure.
+    pub fn check_has_calls_exactly_in_order<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> Result<(), CallMismatch> {
+        let calls: Vec<C> = calls.into_iter().map(|c| c.into_call_args()).collect();
+        let expected = format!("{:?}", calls);
+        let match_info = self.get_match_info(calls);
+        self.check("has_calls_exactly_in_order", match_info.expectations_matched_in_order_exactly(), match_info.describe_expected(expected))
+    }
+
+    /// Returns `Ok` if `Mock::called_with_pattern` would return `true`.
+    /// Otherwise, returns a `CallMismatch` describing the failure.
+    pub fn check_called_with_pattern(&self, pattern: &dyn Fn(&C) -> bool)
+        -> Result<(), CallMismatch>
+    {
+        let expected = "a call matching the pattern".to_owned();
+        self.check("called_with_pattern", self.called_with_pattern(pattern), expected)
+    }
+
+    /// Returns `Ok` if `Mock::has_no_calls_matching` would return `true`.
+    /// Otherwise, returns a `CallMismatch` whose expectation lists the index
+    /// and arguments of every call that matched.
+    pub fn check_has_no_calls_matching(&self, pattern: &dyn Fn(&C) -> bool)
+        -> Result<(), CallMismatch>
+    {
+        let violations = self.violating_calls(pattern);
+        let expected = "no calls matching the pattern".to_owned();
+        self.check_no_violations("has_no_calls_matching", expected, violations)
+    }
+
+    /// Returns `Ok` if `Mock::has_patterns` would return `true`. Otherwise,
+    /// returns a `CallMismatch` describing the failure.
+    pub fn check_has_patterns(&self, patterns: Vec<&dyn Fn(&C) -> bool>)
+        -> Result<(), CallMismatch>
+    {
+        let expected = format!("calls matching {} patterns", patterns.len());
+        let match_info = self.get_match_info_pattern(patterns);
+        self.check("has_patterns", match_info.expectations_matched(), match_info.describe_expected(expected))
+    }
+
+    /// Returns `Ok` if `Mock::has_patterns_in_order` would return `true`. Otherwise,
+    /// returns a `CallMismatch` describing the failure.
+    pub fn check_has_patterns_in_order(&self, patterns: Vec<&dyn Fn(&C) -> bool>)
+        -> Result<(), CallMismatch>
+    {
+        let expected = format!("calls matching {} patterns", patterns.len());
+        let match_info = self.get_match_info_pattern(patterns);
+        self.check("has_patterns_in_order", match_info.expectations_matched_in_order(), match_info.describe_expected(expected))
+    }
+
+    /// Returns `Ok` if `Mock::has_patterns_exactly` would return `true`. Otherwise,
+    /// returns a `CallMismatch` describing the failure.
+    pub fn check_has_patterns_exactly(&self, patterns: Vec<&dyn Fn(&C) -> bool>)
+        -> Result<(), CallMismatch>
+    {
+        let expected = format!("calls matching {} patterns", patterns.len());
+        let match_info = self.get_match_info_pattern(patterns);
+        self.check("has_patterns_exactly", match_info.expectations_matched_exactly(), match_info.describe_expected(expected))
+    }
+
+    /// Returns `Ok` if `Mock::has_patterns_exactly_in_order` would return `true`. Otherwise,
+    /// returns a `CallMismatch` describing the failure.
+    pub fn check_has_patterns_exactly_in_order(&self, patterns: Vec<&dyn Fn(&C) -> bool>)
+        -> Result<(), CallMismatch>
+    {
+        let expected = format!("calls matching {} patterns", patterns.len());
+        let match_info = self.get_match_info_pattern(patterns);
+        self.check("has_patterns_exactly_in_order", match_info.expectations_matched_in_order_exactly(), match_info.describe_expected(expected))
+    }
+
+    // ========================================================================
+    // * Assertions
+    // ========================================================================
+
+    /// Panics if `Mock::called` would return `false`. The panic message
+    /// is the same as the description of the `CallMismatch` returned by
+    /// `Mock::check_called`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i32, ()>::default();
+    /// mock.call(1);
+    /// mock.assert_called();
+    /// ```
+    ///
+    /// ```should_panic
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i32, ()>::default();
+    /// mock.assert_called();
+    /// ```
+    pub fn assert_called(&self) {
+        failure::assert_check(self.check_called())
+    }
+
+    /// Panics if `Mock::calls_in_count_range` would return `false`.
+    pub fn assert_calls_in_count_range(&self, min: usize, max: usize) {
+        failure::assert_check(self.check_calls_in_count_range(min, max))
+    }
+
+    /// Panics if `Mock::called_with` would return `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::default();
+    /// mock.call("foo");
+    /// mock.assert_called_with("foo");
+    /// ```
+    pub fn assert_called_with<T: IntoCallArgs<C, M>, M>(&self, args: T) {
+        failure::assert_check(self.check_called_with(args))
+    }
+
+    /// Panics if `Mock::not_called_with` would return `false`.
+    pub fn assert_not_called_with<T: IntoCallArgs<C, M>, M>(&self, args: T) {
+        failure::assert_check(self.check_not_called_with(args))
+    }
+
+    /// Panics if `Mock::has_no_calls` would return `false`.
+    pub fn assert_has_no_calls<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) {
+        failure::assert_check(self.check_has_no_calls(calls))
+    }
+
+    /// Panics if `Mock::has_calls` would return `false`.
+    pub fn assert_has_calls<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) {
+        failure::assert_check(self.check_has_calls(calls))
+    }
+
+    /// Panics if `Mock::has_calls_in_order` would return `false`.
+    pub fn assert_has_calls_in_order<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) {
+        failure::assert_check(self.check_has_calls_in_order(calls))
+    }
+
+    /// Panics if `Mock::has_calls_exactly` would return `false`.
+    pub fn assert_has_calls_exactly<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) {
+        failure::assert_check(self.check_has_calls_exactly(calls))
+    }
+
+    /// Panics if `Mock::has_calls_exactly_in_order` would return `false`.
+    pub fn assert_has_calls_exactly_in_order<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) {
+        failure::assert_check(self.check_has_calls_exactly_in_order(calls))
+    }
+
+    /// Panics if `Mock::called_with_pattern` would return `false`.
+    pub fn assert_called_with_pattern(&self, pattern: &dyn Fn(&C) -> bool) {
+        failure::assert_check(self.check_called_with_pattern(pattern))
+    }
+
+    /// Panics if `Mock::has_no_calls_matching` would return `false`.
+    pub fn assert_has_no_calls_matching(&self, pattern: &dyn Fn(&C) -> bool) {
+        failure::assert_check(self.check_has_no_calls_matching(pattern))
+    }
+
+    /// Panics if `Mock::has_patterns` would return `false`.
+    pub fn assert_has_patterns(&self, patterns: Vec<&dyn Fn(&C) -> bool>) {
+        failure::assert_check(self.check_has_patterns(patterns))
+    }
+
+    /// Panics if `Mock::has_patterns_in_order` would return `false`.
+    pub fn assert_has_patterns_in_order(&self, patterns: Vec<&dyn Fn(&C) -> bool>) {
+        failure::assert_check(self.check_has_patterns_in_order(patterns))
+    }
+
+    /// Panics if `Mock::has_patterns_exactly` would return `false`.
+    pub fn assert_has_patterns_exactly(&self, patterns: Vec<&dyn Fn(&C) -> bool>) {
+        failure::assert_check(self.check_has_patterns_exactly(patterns))
+    }
+
+    /// Panics if `Mock::has_patterns_exactly_in_order` would return `false`.
+    pub fn assert_has_patterns_exactly_in_order(&self, patterns: Vec<&dyn Fn(&C) -> bool>) {
+        failure::assert_check(self.check_has_patterns_exactly_in_order(patterns))
+    }
+
+    /// Returns a guard that panics when it's dropped if any calls were made
+    /// to the mock after the guard was created. Unrecorded calls are
+    /// included in the number of calls, and the panic message lists the
+    /// recorded ones, like `Mock::assert_called`.
+    ///
+    /// The guard doesn't borrow the mock, so the mock can still be used by
+    /// the code under test. If the thread is already panicking when the guard
+    /// is dropped, e.g. because an assertion failed, the guard doesn't panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<&str, ()>::new(());
+    /// mock.call("login");
+    /// assert_eq!(mock.take_calls(), vec!("login"));
+    ///
+    /// let _guard = mock.assert_no_more_calls_guard();
+    /// // ...rest of the test, which must not call the mock...
+    /// ```
+    pub fn assert_no_more_calls_guard(&self) -> NoMoreCallsGuard<C> {
+        NoMoreCallsGuard {
+            num_calls: self.num_calls.clone(),
+            calls: self.calls.clone(),
+            interned_args: self.interned_args.clone(),
+            num_dropped_calls: self.num_dropped_calls.clone(),
+            initial_num_calls: *self.num_calls.borrow(),
+            initial_num_recorded_calls: self.total_recorded_calls(),
+        }
+    }
+
+    // ========================================================================
+    // * Debugging
+    // ========================================================================
+
+    /// Returns the recorded call whose arguments are the most similar to
+    /// `args`, or `None` if no calls have been recorded. Useful for finding
+    /// out why `Mock::called_with` failed.
+    ///
+    /// Similarity is judged using the `Debug` representations of the
+    /// arguments. If they're tuples, the call with the most positions equal
+    /// to the ones in `args` is the closest. Ties are broken by the edit
+    /// distance between the representations, then by picking the earliest
+    /// call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(&str, i32, bool), ()>::default();
+    /// mock.call(("apple", 1, true));
+    /// mock.call(("banana", 2, false));
+    /// mock.call(("banana", 3, true));
+    ///
+    /// assert_eq!(mock.closest_call(("banana", 4, false)), Some(("banana", 2, false)));
+    /// assert_eq!(mock.closest_call(("cherry", 1, true)), Some(("apple", 1, true)));
+    /// ```
+    pub fn closest_call<T: IntoCallArgs<C, M>, M>(&self, args: T) -> Option<C> {
+        let expected = format!("{:?}", args.into_call_args());
+        self.recorded_calls()
+            .iter()
+            .map(|call| {
+                let actual = format!("{:?}", call);
+                let num_differing = differing_debug_positions(&expected, &actual).len();
+                ((num_differing, edit_distance(&expected, &actual)), call)
+            })
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, call)| call.clone())
+    }
+
+    /// Returns a human-readable report of the `Mock`'s state, for debugging
+    /// failing tests. The report has three lines: the number of calls, each
+    /// distinct set of arguments used with the number of calls made with it,
+    /// and the behaviour configured for the `Mock`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(i32, i32), i32>::new(0);
+    /// mock.return_value_for((42, 1), 5);
+    /// mock.call((42, 0));
+    /// mock.call((42, 1));
+    /// mock.call((42, 0));
+    ///
+    /// assert_eq!(
+    ///     mock.summary(),
+    ///     "3 calls\n\
+    ///      args: (42, 0) ×2, (42, 1) ×1\n\
+    ///      behaviour: default return value, 1 return value for specific args");
+    /// ```
+    pub fn summary(&self) -> String {
+        let calls = self.recorded_calls();
+        let num_calls = *self.num_calls.borrow();
+        let mut summary = plural(num_calls, "call", "calls");
+        if num_calls > calls.len() {
+            summary += &format!(" ({} not recorded)", num_calls - calls.len());
+        }
+
+        // Distinct args, in the order they were first used.
+        let mut distinct_args: Vec<(&C, usize)> = vec!();
+        for args in calls.iter() {
+            match distinct_args.iter_mut().find(|&&mut (other, _)| other == args) {
+                Some(&mut (_, ref mut count)) => *count += 1,
+                None => distinct_args.push((args, 1)),
+            }
+        }
+        let args: Vec<String> = distinct_args
+            .iter()
+            .map(|&(args, count)| format!("{:?} ×{}", args, count))
+            .collect();
+        summary += &format!("\nargs: {}", if args.is_empty() {
+            "none".to_owned()
+        } else {
+            args.join(", ")
+        });
+
+        let mut behaviour = vec!();
+        if self.panic_message.borrow().is_some() {
+            behaviour.push("panic".to_owned());
+        }
+        if self.default_return_value.borrow().is_some() {
+            behaviour.push("default return value".to_owned());
+        }
+        if self.default_fn.borrow().is_some() {
+            behaviour.push("default fn".to_owned());
+        }
+        if self.default_closure.borrow().is_some() {
+            behaviour.push("default closure".to_owned());
+        }
+        if self.default_producer.borrow().is_some() {
+            behaviour.push("producer".to_owned());
+        }
+        if self.return_value_once.borrow().is_some() {
+            behaviour.push("one-shot return value".to_owned());
+        }
+        if let Some((n, _)) = *self.failure_after.borrow() {
+            behaviour.push(format!("failure after {}", plural(n, "call", "calls")));
+        }
+        let counts = [
+            (self.sequence_len(SequenceMode::Drain),
+                "remaining sequenced return value", "remaining sequenced return values"),
+            (self.sequence_len(SequenceMode::Cycle),
+                "cycled return value", "cycled return values"),
+            (self.return_values.borrow().len(),
+                "return value for specific args", "return values for specific args"),
+            (self.pattern_return_values.borrow().len(),
+                "return value for a pattern", "return values for patterns"),
+            (self.call_return_values.borrow().len(),
+                "return value for a specific call", "return values for specific calls"),
+            (self.nth_call_return_values.borrow().values().map(|values| values.len()).sum(),
+                "return value for the nth call with specific args",
+                "return values for the nth call with specific args"),
+            (self.fns.borrow().len(), "fn for specific args", "fns for specific args"),
+            (self.closures.borrow().len(),
+                "closure for specific args", "closures for specific args"),
+            (self.panic_messages.borrow().len(),
+                "panic for specific args", "panics for specific args"),
+            (self.call_hooks.borrow().len(), "call hook", "call hooks"),
+            (self.call_observers.borrow().len(), "call observer", "call observers"),
+        ];
+        for &(count, singular, plural_name) in counts.iter() {
+            if count > 0 {
+                behaviour.push(plural(count, singular, plural_name));
+            }
+        }
+        summary += &format!("\nbehaviour: {}", if behaviour.is_empty() {
+            "none".to_owned()
+        } else {
+            behaviour.join(", ")
+        });
+
+        summary
+    }
+
+    // ========================================================================
+    // * Private Helpers
+    // ========================================================================
+    fn check(&self, check: &str, passed: bool, expected: String)
+        -> Result<(), CallMismatch>
+    {
+        if passed {
+            Ok(())
+        } else {
+            let calls = self.recorded_calls();
+            let actual_calls = calls
+                .iter()
+                .map(|args| format!("{:?}", args))
+                .collect();
+            let num_dropped_calls = *self.num_dropped_calls.borrow();
+            let expected = if num_dropped_calls > 0 {
+                format!(
+                    "{} (only the last {} of {} recorded calls were kept)",
+                    expected,
+                    calls.len(),
+                    calls.len() + num_dropped_calls)
+            } else {
+                expected
+            };
+            Err(CallMismatch::new(check, expected, actual_calls))
+        }
+    }
+
+    // Returns the index, counting dropped calls, and arguments of every
+    // recorded call matching `pattern`.
+    fn violating_calls(&self, pattern: &dyn Fn(&C) -> bool) -> Vec<(usize, C)> {
+        let num_dropped_calls = *self.num_dropped_calls.borrow();
+        self.recorded_calls()
+            .iter()
+            .enumerate()
+            .filter(|(_, args)| pattern(args))
+            .map(|(i, args)| (i + num_dropped_calls, args.clone()))
+            .collect()
+    }
+
+    fn check_no_violations(&self, check: &str, expected: String, violations: Vec<(usize, C)>)
+        -> Result<(), CallMismatch>
+    {
+        let expected = if violations.is_empty() {
+            expected
+        } else {
+            let described: Vec<String> = violations
+                .iter()
+                .map(|(i, args)| format!("call {} ({:?})", i, args))
+                .collect();
+            let described = match described.split_last() {
+                Some((last, [])) => last.clone(),
+                Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+                None => unreachable!(),
+            };
+            format!(
+                "{}, but {} {} made",
+                expected,
+                described,
+                if violations.len() == 1 { "was" } else { "were" })
+        };
+        self.check(check, violations.is_empty(), expected)
+    }
+
+    fn get_match_info<T: IntoCallArgs<C, M>, M>(&self, expected_calls: Vec<T>) -> MatchInfo {
+        let expected_calls_c: Vec<C> = expected_calls
+            .into_iter()
+            .map(|r| r.into_call_args())
+            .collect();
+
+        // Build map from expected arg tuple (its index) to the indices of the
+        // actual calls made to the mock whose args match that tuple exactly.
+        let mut pattern_index_to_match_indices: HashMap<usize, Vec<usize>> =
+            HashMap::new();
+        for (call_index, call_args) in self.recorded_calls().iter().enumerate() {
+            for (expected_index, expected_args) in expected_calls_c.iter().enumerate() {
+                if call_args == expected_args {
+                    pattern_index_to_match_indices
+                        .entry(expected_index)
+                        .or_insert(vec!())
+                        .push(call_index);
+                }
+            }
+        }
+
+        MatchInfo {
+            num_expectations: expected_calls_c.len(),
+            num_actual_calls: self.num_recorded_calls(),
+            pattern_index_to_match_indices: pattern_index_to_match_indices,
+        }
+    }
+
+    fn get_match_info_pattern(&self, patterns: Vec<&dyn Fn(&C) -> bool>) -> MatchInfo {
+        // Build map from pattern (its index) to the indices of the actual
+        // calls made to the mock whose args match that pattern.
+        let mut pattern_index_to_match_indices: HashMap<usize, Vec<usize>> =
+            HashMap::new();
+        for (call_index, call_args) in self.recorded_calls().iter().enumerate() {
+            for (expected_index, pattern_fn) in patterns.iter().enumerate() {
+                if pattern_fn(call_args) {
+                    pattern_index_to_match_indices
+                        .entry(expected_index)
+                        .or_insert(vec!())
+                        .push(call_index);
+                }
+            }
+        }
+
+        MatchInfo {
+            num_expectations: patterns.len(),
+            num_actual_calls: self.num_recorded_calls(),
+            pattern_index_to_match_indices: pattern_index_to_match_indices,
+        }
+    }
+}
+
+impl<C, S> Mock<C, Option<S>>
+    where C: Clone + Eq + Hash,
+          S: Clone
+{
+    /// Return `Some(return_value)` from `Mock::call`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(), Option<i64>>::new(None);
+    /// mock.return_some(10);
+    ///
+    /// assert_eq!(mock.call(()), Some(10));
+    /// ```
+    pub fn return_some<T: Into<S>>(&self, return_value: T) {
+        self.return_value(Some(return_value.into()))
+    }
+
+    /// Return `None` from `Mock::call`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(), Option<i64>>::new(Some(42));
+    /// mock.return_none();
+    ///
+    /// assert_eq!(mock.call(()), None);
+    /// ```
+    pub fn return_none(&self) {
+        self.return_value(None)
+    }
+
+    /// Return `Some(return_value)` from `Mock::call` when the specified
+    /// `args` are passed in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i32, Option<&str>>::new(None);
+    /// mock.return_some_for(42, "Donald");
+    ///
+    /// assert_eq!(mock.call(42), Some("Donald"));
+    /// assert_eq!(mock.call(10), None);
+    /// ```
+    pub fn return_some_for<A: IntoCallArgs<C, M>, M, T: Into<S>>(&self, args: A, return_value: T) {
+        self.return_value_for(args, Some(return_value.into()))
+    }
+
+    /// Return `None` from `Mock::call` when the specified `args` are passed
+    /// in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i32, Option<&str>>::new(Some("guest"));
+    /// mock.return_none_for(0);
+    ///
+    /// assert_eq!(mock.call(0), None);
+    /// assert_eq!(mock.call(10), Some("guest"));
+    /// ```
+    pub fn return_none_for<A: IntoCallArgs<C, M>, M>(&self, args: A) {
+        self.return_value_for(args, None)
+    }
+}
+
+impl<C, O, E> Mock<C, Result<O, E>>
+    where C: Clone + Eq + Hash,
+          O: Clone,
+          E: Clone
+{
+    /// Return `Ok(return_value)` from `Mock::call`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(), Result<&str, &str>>::new(Err("oh no"));
+    /// mock.return_ok("success");
+    ///
+    /// assert_eq!(mock.call(()), Ok("success"));
+    /// ```
+    pub fn return_ok<T: Into<O>>(&self, return_value: T) {
+        self.return_value(Ok(return_value.into()))
+    }
+
+    /// Return `Err(return_value)` from `Mock::call`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(), Result<&str, &str>>::new(Ok("success"));
+    /// mock.return_err("oh no");
+    ///
+    /// assert_eq!(mock.call(()), Err("oh no"));
+    /// ```
+    pub fn return_err<T: Into<E>>(&self, return_value: T) {
+        self.return_value(Err(return_value.into()))
+    }
+
+    /// Return `Ok(return_value)` from `Mock::call` when the specified `args`
+    /// are passed in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct User {
+    ///     name: String,
+    /// }
+    ///
+    /// let get_user = Mock::<i32, Result<User, String>>::new(
+    ///     Err("no such user".to_owned()));
+    /// get_user.return_ok_for(42, User { name: "Donald".to_owned() });
+    ///
+    /// assert_eq!(get_user.call(42), Ok(User { name: "Donald".to_owned() }));
+    /// assert_eq!(get_user.call(10), Err("no such user".to_owned()));
+    /// ```
+    pub fn return_ok_for<A: IntoCallArgs<C, M>, M, T: Into<O>>(&self, args: A, return_value: T) {
+        self.return_value_for(args, Ok(return_value.into()))
+    }
+
+    /// Return `Err(return_value)` from `Mock::call` when the specified `args`
+    /// are passed in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let delete_user = Mock::<i32, Result<(), String>>::new(Ok(()));
+    /// delete_user.return_err_for(0, "cannot delete the admin user");
+    ///
+    /// assert_eq!(delete_user.call(0), Err("cannot delete the admin user".to_owned()));
+    /// assert_eq!(delete_user.call(42), Ok(()));
+    /// ```
+    pub fn return_err_for<A: IntoCallArgs<C, M>, M, T: Into<E>>(&self, args: A, return_value: T) {
+        self.return_value_for(args, Err(return_value.into()))
+    }
+
+    /// Return each of `results` from successive calls to `Mock::call`, like
+    /// `Mock::return_values`, converting the values inside each `Result`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(), Result<String, String>>::new(Ok("default".to_owned()));
+    /// mock.return_results(vec!(Err("timeout"), Ok("connected")));
+    ///
+    /// assert_eq!(mock.call(()), Err("timeout".to_owned()));
+    /// assert_eq!(mock.call(()), Ok("connected".to_owned()));
+    /// assert_eq!(mock.call(()), Ok("default".to_owned()));
+    /// ```
+    pub fn return_results<A: Into<O>, B: Into<E>>(&self, results: Vec<Result<A, B>>) {
+        self.return_values(results
+            .into_iter()
+            .map(|result| result.map(Into::into).map_err(Into::into))
+            .collect::<Vec<_>>())
+    }
+
+    /// Return `Err` with each of `errs` from successive calls to
+    /// `Mock::call`, then `Ok(ok)` from every call after them. This replaces
+    /// the default return value.
+    ///
+    /// This is useful for testing code that retries failed calls.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// // Code under test, which makes up to 3 attempts.
+    /// fn fetch_with_retries(fetch: &Mock<(), Result<u32, String>>) -> Result<u32, String> {
+    ///     let mut result = fetch.call(());
+    ///     for _ in 1..3 {
+    ///         if result.is_ok() {
+    ///             break;
+    ///         }
+    ///         result = fetch.call(());
+    ///     }
+    ///     result
+    /// }
+    ///
+    /// let fetch = Mock::<(), Result<u32, String>>::new(Ok(0));
+    /// fetch.return_errs_then_ok(vec!("timeout", "timeout"), 42u32);
+    /// assert_eq!(fetch_with_retries(&fetch), Ok(42));
+    /// assert_eq!(fetch.num_calls(), 3);
+    ///
+    /// fetch.reset_calls();
+    /// fetch.return_errs_then_ok(vec!("timeout"; 3), 42u32);
+    /// assert_eq!(fetch_with_retries(&fetch), Err("timeout".to_owned()));
+    /// assert_eq!(fetch.num_calls(), 3);
+    /// ```
+    pub fn return_errs_then_ok<A: Into<E>, B: Into<O>>(&self, errs: Vec<A>, ok: B) {
+        self.return_ok(ok);
+        self.return_values(errs.into_iter().map(|err| Err(err.into())).collect::<Vec<_>>());
+    }
+
+    /// Return `Ok` with each of `oks` from successive calls to `Mock::call`,
+    /// then `Err(err)` from every call after them. This replaces the default
+    /// return value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let read_chunk = Mock::<(), Result<Vec<u8>, String>>::new(Ok(vec!()));
+    /// read_chunk.return_oks_then_err(vec!(vec!(1, 2), vec!(3)), "connection reset");
+    ///
+    /// assert_eq!(read_chunk.call(()), Ok(vec!(1, 2)));
+    /// assert_eq!(read_chunk.call(()), Ok(vec!(3)));
+    /// assert_eq!(read_chunk.call(()), Err("connection reset".to_owned()));
+    /// assert_eq!(read_chunk.call(()), Err("connection reset".to_owned()));
+    /// ```
+    pub fn return_oks_then_err<A: Into<O>, B: Into<E>>(&self, oks: Vec<A>, err: B) {
+        self.return_err(err);
+        self.return_values(oks.into_iter().map(|ok| Ok(ok.into())).collect::<Vec<_>>());
+    }
+
+    /// Returns true if every call to `Mock::call` whose return value was
+    /// recorded returned `Ok`, including if there are no such calls. See
+    /// `Mock::returned_values` for which return values are recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<i32, Result<i32, String>>::new(Ok(0));
+    /// mock.return_err_for(13, "unlucky");
+    ///
+    /// mock.call(1);
+    /// mock.call(2);
+    /// assert!(mock.all_calls_returned_ok());
+    ///
+    /// mock.call(13);
+    /// assert!(!mock.all_calls_returned_ok());
+    /// ```
+    pub fn all_calls_returned_ok(&self) -> bool {
+        self.returned_values.borrow()
+            .iter()
+            .flatten()
+            .all(Result::is_ok)
+    }
+}
+
+impl<C, T> Mock<C, Box<dyn Iterator<Item = T>>>
+    where C: Clone + Eq + Hash,
+          T: Clone + ThreadSafe + 'static
+{
+    /// Return a new iterator over `items` from every call to `Mock::call`.
+    ///
+    /// Boxed iterators can't be copied, so `Mock::return_value` can't be used
+    /// for them. Instead, the items are stored and a fresh iterator over a
+    /// copy of them is constructed for each call, like `Mock::use_producer`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(), Box<dyn Iterator<Item = u64>>>::with_producer(
+    ///     Box::new(|| Box::new(std::iter::empty())));
+    /// mock.return_iter(vec!(1, 2, 3));
+    ///
+    /// assert_eq!(mock.call(()).sum::<u64>(), 6);
+    /// assert_eq!(mock.call(()).collect::<Vec<u64>>(), vec!(1, 2, 3));
+    /// ```
+    pub fn return_iter<I: IntoIterator<Item = T>>(&self, items: I) {
+        let items: Vec<T> = items.into_iter().collect();
+        self.use_producer(Box::new(move || Box::new(items.clone().into_iter())));
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<C, R> Mock<C, R>
+    where C: Clone + Eq + Hash
+{
+    /// Returns the arguments of every call made to the mock, in order from
+    /// first to last, as a JSON array. Requires the `serde` feature.
+    ///
+    /// This can be saved as a "golden" file and compared against later runs
+    /// of the same test using `Mock::verify_calls_against_json`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(&str, i32), ()>::default();
+    /// mock.call(("foo", 1));
+    /// mock.call(("bar", 2));
+    ///
+    /// assert_eq!(mock.calls_as_json().unwrap(), r#"[["foo",1],["bar",2]]"#);
+    /// ```
+    pub fn calls_as_json(&self) -> Result<String, serde_json::Error>
+        where C: Serialize
+    {
+        serde_json::to_string(&*self.recorded_calls())
+    }
+
+    /// Returns `Ok` if the calls made to the mock are the same as the calls
+    /// in `json`, which is a JSON array in the format produced by
+    /// `Mock::calls_as_json`. Otherwise, returns an error describing the
+    /// first call that differs. Requires the `serde` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use double::Mock;
+    ///
+    /// let mock = Mock::<(String, i32), ()>::default();
+    /// mock.call(("foo".to_owned(), 1));
+    /// mock.call(("bar".to_owned(), 2));
+    ///
+    /// assert!(mock.verify_calls_against_json(r#"[["foo",1],["bar",2]]"#).is_ok());
+    /// assert_eq!(
+    ///     mock.verify_calls_against_json(r#"[["foo",1],["baz",2]]"#),
+    ///     Err("call 1 differs: expected (\"baz\", 2), actual (\"bar\", 2)".to_owned()));
+    /// ```
+    pub fn verify_calls_against_json(&self, json: &str) -> Result<(), String>
+        where C: Debug + DeserializeOwned
+    {
+        let expected_calls: Vec<C> = serde_json::from_str(json)
+            .map_err(|err| format!("invalid JSON call history: {}", err))?;
+        let actual_calls = self.recorded_calls();
+
+        let num_calls = expected_calls.len().max(actual_calls.len());
+        for index in 0..num_calls {
+            match (expected_calls.get(index), actual_calls.get(index)) {
+                (Some(expected), Some(actual)) if expected != actual => {
+                    return Err(format!(
+                        "call {} differs: expected {:?}, actual {:?}",
+                        index, expected, actual));
+                }
+                (Some(expected), None) => {
+                    return Err(format!(
+                        "call {} differs: expected {:?}, actual no call",
+                        index, expected));
+                }
+                (None, Some(actual)) => {
+                    return Err(format!(
+                        "call {} differs: expected no call, actual {:?}",
+                        index, actual));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<C, R> Debug for Mock<C, R>
+    where C: Clone + Debug + Eq + Hash,
+          R: Debug
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Mock")
+            .field("default_return_value", &self.default_return_value)
+            .field("return_value_sequence", &self.return_value_sequence)
+            .field("return_values", &self.return_values)
+            .field("calls", &self.calls)
+            .field("interned_args", &self.interned_args)
+            .finish()
+    }
+}
+
+/// Formats the `Mock`'s calls as a log, with a header giving the number of
+/// calls followed by a line for each recorded call with its index and
+/// arguments. Use `Debug` to inspect the `Mock`'s configuration instead.
+///
+/// # Examples
+///
+/// ```
+/// use double::Mock;
+///
+/// let mock = Mock::<(i32, &str), ()>::default();
+/// mock.call((1, "apple"));
+/// mock.call((2, "banana"));
+///
+/// assert_eq!(format!("{}", mock), "2 calls\n  0: (1, \"apple\")\n  1: (2, \"banana\")");
+/// ```
+impl<C, R> fmt::Display for Mock<C, R>
+    where C: Clone + Debug + Eq + Hash
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let num_calls = self.num_calls();
+        let calls = self.recorded_calls();
+        write!(f, "{} call{}", num_calls, if num_calls == 1 { "" } else { "s" })?;
+        if calls.len() < num_calls {
+            write!(f, " ({} recorded)", calls.len())?;
+        }
+        let num_dropped_calls = *self.num_dropped_calls.borrow();
+        for (i, args) in calls.iter().enumerate() {
+            write!(f, "\n  {}: {:?}", i + num_dropped_calls, args)?;
+        }
+        Ok(())
+    }
+}
+
+/// Used in place of `Mock` for arguments that aren't `Eq` or `Hash`, such as
+/// floats.
+///
+/// A `KeyedMock` is constructed with a `key_fn`, which derives a key of type
+/// `K` from the arguments of each call. Calls are forwarded to a `Mock<K, R>`,
+/// so return values are looked up, and calls are compared, using their keys
+/// instead of their arguments. Two sets of arguments with the same key are
+/// treated as the same call.
+///
+/// Use `KeyedMock::key_mock` for the checks and configuration which
+/// `KeyedMock` doesn't provide. Functions and closures configured there are
+/// passed the key of each call instead of its arguments.
+///
+/// # Examples
+///
+/// ```
+/// use double::mock::KeyedMock;
+///
+/// // Compare prices to the nearest cent.
+/// let mock = KeyedMock::<f64, i64, bool>::new(
+///     false,
+///     Box::new(|price: &f64| (price * 100.0).round() as i64));
+/// mock.return_value_for(9.99, true);
+///
+/// assert!(mock.call(9.990001));
+/// assert!(!mock.call(10.0));
+///
+/// assert!(mock.called_with(9.99));
+/// assert!(!mock.called_with(9.98));
+/// assert_eq!(mock.calls(), vec!(9.990001, 10.0));
+/// assert!(mock.key_mock().has_calls_exactly_in_order(vec!(999, 1000)));
+/// ```
+pub struct KeyedMock<C, K, R>
+    where K: Clone + Eq + Hash
+{
+    key_fn: Shared<dyn_fn!(Fn(&C) -> K)>,
+    // The arguments of each call. `mock` records their keys.
+    calls: Ref<Vec<C>>,
+    mock: Mock<K, R>,
+}
+
+impl<C, K, R> KeyedMock<C, K, R>
+    where K: Clone + Eq + Hash,
+          R: Clone
+{
+    /// Creates a new `KeyedMock` that will return `return_value`, and key
+    /// the arguments of each call using `key_fn`.
+    pub fn new<T: Into<R>>(return_value: T, key_fn: Box<dyn_fn!(Fn(&C) -> K)>) -> Self {
+        KeyedMock {
+            key_fn: Shared::from(key_fn),
+            calls: Ref::new(Cell::new(Vec::new())),
+            mock: Mock::new(return_value),
+        }
+    }
+
+    /// Override the default return value, like `Mock::return_value`.
+    pub fn return_value<T: Into<R>>(&self, value: T) {
+        self.mock.return_value(value)
+    }
+
+    /// Return `return_value` for every call whose key is the same as the key
+    /// of `args`, like `Mock::return_value_for`. Only the key of `args` is
+    /// stored, so a later `return_value_for` with different arguments that
+    /// have the same key replaces `return_value`.
+    pub fn return_value_for<S: IntoCallArgs<C, M>, M, T: Into<R>>(&self, args: S, return_value: T) {
+        self.mock.return_value_for((self.key_fn)(&args.into_call_args()), return_value)
+    }
+}
+
+impl<C, K, R> KeyedMock<C, K, R>
+    where C: Clone,
+          K: Clone + Eq + Hash
+{
+    /// Records a call with `args` and returns the value configured for the
+    /// key of `args`, like `Mock::call`.
+    pub fn call(&self, args: C) -> R {
+        let key = self.key(&args);
+        self.calls.borrow_mut().push(args);
+        self.mock.call(key)
+    }
+
+    /// Returns the key `key_fn` derives from `args`.
+    pub fn key(&self, args: &C) -> K {
+        (self.key_fn)(args)
+    }
+
+    /// Returns the `Mock` which the `KeyedMock` forwards the key of each
+    /// call to.
+    pub fn key_mock(&self) -> &Mock<K, R> {
+        &self.mock
+    }
+
+    /// Returns true if `KeyedMock::call` has been called.
+    pub fn called(&self) -> bool {
+        self.mock.called()
+    }
+
+    /// Returns the number of times `KeyedMock::call` has been called.
+    pub fn num_calls(&self) -> usize {
+        self.mock.num_calls()
+    }
+
+    /// Returns the arguments of each call, in order from first to last.
+    pub fn calls(&self) -> Vec<C> {
+        self.calls.borrow().clone()
+    }
+
+    /// Reset the call history, like `Mock::reset_calls`.
+    pub fn reset_calls(&self) {
+        self.calls.borrow_mut().clear();
+        self.mock.reset_calls()
+    }
+
+    fn keys<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> Vec<K> {
+        calls.into_iter().map(|args| self.key(&args.into_call_args())).collect()
+    }
+}
+
+impl<C, K, R> KeyedMock<C, K, R>
+    where C: Clone,
+          K: Clone + Debug + Eq + Hash
+{
+    /// Returns true if a call's key is the same as the key of `args`.
+    pub fn called_with<T: IntoCallArgs<C, M>, M>(&self, args: T) -> bool {
+        self.mock.called_with(self.key(&args.into_call_args()))
+    }
+
+    /// Compares the keys of `calls` to the keys of the calls made, like
+    /// `Mock::has_calls`.
+    pub fn has_calls<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> bool {
+        self.mock.has_calls(self.keys(calls))
+    }
+
+    /// Compares the keys of `calls` to the keys of the calls made, like
+    /// `Mock::has_calls_in_order`.
+    pub fn has_calls_in_order<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> bool {
+        self.mock.has_calls_in_order(self.keys(calls))
+    }
+
+    /// Compares the keys of `calls` to the keys of the calls made, like
+    /// `Mock::has_calls_exactly`.
+    pub fn has_calls_exactly<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> bool {
+        self.mock.has_calls_exactly(self.keys(calls))
+    }
+
+    /// Compares the keys of `calls` to the keys of the calls made, like
+    /// `Mock::has_calls_exactly_in_order`.
+    pub fn has_calls_exactly_in_order<T: IntoCallArgs<C, M>, M>(&self, calls: Vec<T>) -> bool {
+        self.mock.has_calls_exactly_in_order(self.keys(calls))
+    }
+}
+
+impl<C, K, R> Clone for KeyedMock<C, K, R>
+    where K: Clone + Eq + Hash
+{
+    /// Clones share the same key function, configuration and calls.
+    fn clone(&self) -> Self {
+        KeyedMock {
+            key_fn: self.key_fn.clone(),
+            calls: self.calls.clone(),
+            mock: self.mock.clone(),
+        }
+    }
+}
+
+impl<C, K, R> Debug for KeyedMock<C, K, R>
+    where C: Debug,
+          K: Clone + Debug + Eq + Hash,
+          R: Debug
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("KeyedMock")
+            .field("calls", &*self.calls.borrow())
+            .field("mock", &self.mock)
+            .finish()
+    }
+}
+
+/// Returns true if every call to `a` was made before every call to `b`,
+/// i.e. the last call to `a` was made before the first call to `b`. Returns
+/// false if either mock has no recorded calls.
+///
+/// # Examples
+///
+/// ```
+/// use double::Mock;
+/// use double::mock::called_before;
+///
+/// let open = Mock::<&str, ()>::default();
+/// let read = Mock::<usize, ()>::default();
+/// assert!(!called_before(&open, &read));
+///
+/// open.call("file.txt");
+/// read.call(10);
+/// read.call(20);
+///
+/// assert!(called_before(&open, &read));
+/// assert!(!called_before(&read, &open));
+/// ```
+pub fn called_before<C1, R1, C2, R2>(a: &Mock<C1, R1>, b: &Mock<C2, R2>) -> bool
+    where C1: Clone + Eq + Hash,
+          C2: Clone + Eq + Hash
+{
+    let last_a = a.call_timestamps.borrow().last().cloned();
+    let first_b = b.call_timestamps.borrow().first().cloned();
+    match (last_a, first_b) {
+        (Some(last_a), Some(first_b)) => last_a < first_b,
+        _ => false,
+    }
+}
+
+/// Returns true if every call to `a` was made after every call to `b`, i.e.
+/// the first call to `a` was made after the last call to `b`. This is the
+/// same as `called_before(b, a)`. Returns false if either mock has no
+/// recorded calls.
+///
+/// # Examples
+///
+/// ```
+/// use double::Mock;
+/// use double::mock::called_after;
+///
+/// let write = Mock::<&str, ()>::default();
+/// let flush = Mock::<(), ()>::default();
+///
+/// write.call("foo");
+/// write.call("bar");
+/// flush.call(());
+///
+/// assert!(called_after(&flush, &write));
+/// assert!(!called_after(&write, &flush));
+/// ```
+pub fn called_after<C1, R1, C2, R2>(a: &Mock<C1, R1>, b: &Mock<C2, R2>) -> bool
+    where C1: Clone + Eq + Hash,
+          C2: Clone + Eq + Hash
+{
+    called_before(b, a)
+}
+
+/// Checks that calls were made to mocks in the given relative order. Each
+/// entry of `calls` is the name of a mock, used in the error message, and
+/// the mock's `Mock::call_timestamps`. A mock can be listed several times to
+/// check that it was called several times.
+///
+/// Returns `Ok` if there's a call to each listed mock, each made after the
+/// call chosen for the previous entry. Other calls made to the mocks are
+/// ignored. Otherwise, returns an error describing the first entry which
+/// couldn't be matched and the order the calls were actually made in.
+///
+/// Call timestamps are taken from a counter shared by every `Mock` in the
+/// process, so calls to any mocks can be compared. Calls recorded without
+/// invoking the mock don't have a timestamp, so they're ignored. The
+/// `assert_call_order!` macro is usually more convenient to use.
+///
+/// # Examples
+///
+/// ```
+/// use double::Mock;
+/// use double::mock::check_call_order;
+///
+/// let save = Mock::<&str, ()>::default();
+/// let notify = Mock::<(), ()>::default();
+/// save.call("draft");
+/// notify.call(());
+/// save.call("final");
+///
+/// let calls = [
+///     ("save", save.call_timestamps()),
+///     ("notify", notify.call_timestamps()),
+///     ("save", save.call_timestamps()),
+/// ];
+/// assert!(check_call_order(&calls).is_ok());
+///
+/// let calls = [
+///     ("notify", notify.call_timestamps()),
+///     ("save", save.call_timestamps()),
+///     ("notify", notify.call_timestamps()),
+/// ];
+/// assert_eq!(
+///     check_call_order(&calls),
+///     Err("expected call 3 to be to `notify`, but it wasn't called after call 2 \
+///          (calls were made in order: save, notify, save)".to_owned()));
+/// ```
+pub fn check_call_order(calls: &[(&str, Vec<usize>)]) -> Result<(), String> {
+    let mut previous: Option<usize> = None;
+    for (index, &(name, ref timestamps)) in calls.iter().enumerate() {
+        // The earliest matching call leaves the most calls for the entries
+        // that follow.
+        let next = timestamps
+            .iter()
+            .cloned()
+            .find(|&timestamp| match previous {
+                Some(previous) => timestamp > previous,
+                None => true,
+            });
+        match next {
+            Some(timestamp) => previous = Some(timestamp),
+            None if index == 0 || timestamps.is_empty() => {
+                return Err(format!(
+                    "expected call {} to be to `{}`, but it was never called",
+                    index + 1, name));
+            }
+            None => {
+                return Err(format!(
+                    "expected call {} to be to `{}`, but it wasn't called after call {} \
+                     (calls were made in order: {})",
+                    index + 1, name, index, actual_call_order(calls)));
+            }
+        }
+    }
+    Ok(())
+}
+
+// Returns the names of the mocks in `calls`, in the order they were called.
+fn actual_call_order(calls: &[(&str, Vec<usize>)]) -> String {
+    let mut seen_names = HashSet::new();
+    let mut ordered_calls: Vec<(usize, &str)> = calls
+        .iter()
+        .filter(|&&(name, _)| seen_names.insert(name))
+        .flat_map(|&(name, ref timestamps)| timestamps.iter().map(move |&t| (t, name)))
+        .collect();
+    ordered_calls.sort();
+    ordered_calls
+        .iter()
+        .map(|&(_, name)| name)
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+/// The configured behaviour used to produce the return value of a call to a
+/// `Mock`, as reported by `Mock::call_sources` and `Mock::explain_next_call`.
+///
+/// Variants are listed in order of precedence, from highest to lowest. See
+/// `Mock::call`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BehaviourSource {
+    /// Panicked, as configured using `Mock::panic_with_for` or
+    /// `Mock::panic_with`.
+    Panic,
+    /// The failure configured using `Mock::fail_after`.
+    FailAfter,
+    /// A closure configured for the args using `Mock::use_closure_for` or
+    /// `Mock::use_closure_with_index_for`.
+    ClosureFor,
+    /// A value configured for the call's index using
+    /// `Mock::return_value_for_call`.
+    ValueForCall,
+    /// A value configured for the nth call with the args using
+    /// `Mock::return_value_for_nth`.
+    ValueForNth,
+    /// A function configured for the args using `Mock::use_fn_for`.
+    FnFor,
+    /// A value configured for the args using `Mock::return_value_for`.
+    ValueFor,
+    /// A value configured for a matching pattern using
+    /// `Mock::return_value_for_matching`.
+    Pattern,
+    /// The value configured using `Mock::return_value_once`.
+    Once,
+    /// The closure configured using `Mock::use_closure` or
+    /// `Mock::use_closure_with_index`.
+    DefaultClosure,
+    /// The function configured using `Mock::use_fn`.
+    DefaultFn,
+    /// The producer configured using `Mock::use_producer`.
+    Producer,
+    /// The next value in the sequence configured using `Mock::return_values`,
+    /// or using `Mock::return_values_in_mode` with `SequenceMode::Drain`.
+    Sequence,
+    /// The value at the cursor of the sequence configured using
+    /// `Mock::return_values_cycled`, or using `Mock::return_values_in_mode`
+    /// with `SequenceMode::Cycle`.
+    Cycled,
+    /// The default return value.
+    DefaultValue,
+    /// Nothing was configured, so the call panicked.
+    Unconfigured,
+    /// The call was recorded without invoking the mock, so no behaviour was
+    /// used.
+    NotInvoked,
+}
+
+/// How the values configured using `Mock::return_values_in_mode` are
+/// returned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SequenceMode {
+    /// Each value is returned once, in order. Once every value has been
+    /// returned, the `Mock` falls back to its default return value.
+    Drain,
+    /// The values are repeated forever. A cursor tracks the next value, and
+    /// only moves when a value from the sequence is returned.
+    Cycle,
+}
+
+/// The calls passed to the closure given to `Mock::with_calls`, which are
+/// borrowed from the `Mock` rather than copied.
+///
+/// Calls can be indexed, like a slice, or iterated over in order from first
+/// to last.
+pub struct BorrowedCalls<'a, C> {
+    args: &'a [C],
+    // The index into `args` of each call's args, if they're interned.
+    indices: Option<&'a [usize]>,
+}
+
+impl<'a, C> BorrowedCalls<'a, C> {
+    /// Returns the number of calls.
+    pub fn len(&self) -> usize {
+        match self.indices {
+            Some(indices) => indices.len(),
+            None => self.args.len(),
+        }
+    }
+
+    /// Returns true if there are no calls.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the arguments of the call at `index`, or `None` if there are
+    /// no more than `index` calls.
+    pub fn get(&self, index: usize) -> Option<&'a C> {
+        match self.indices {
+            Some(indices) => indices.get(index).map(|&i| &self.args[i]),
+            None => self.args.get(index),
+        }
+    }
+
+    /// Returns an iterator over the arguments of each call, in order from
+    /// first to last.
+    pub fn iter(&self) -> impl Iterator<Item = &'a C> + 'a {
+        let args = self.args;
+        let indices = self.indices;
+        (0..self.len()).map(move |index| match indices {
+            Some(indices) => &args[indices[index]],
+            None => &args[index],
+        })
+    }
+}
+
+impl<'a, C> std::ops::Index<usize> for BorrowedCalls<'a, C> {
+    type Output = C;
+
+    fn index(&self, index: usize) -> &C {
+        self.get(index).expect("call index out of range")
+    }
+}
+
+impl<'a, C: Debug> Debug for BorrowedCalls<'a, C> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Handle to the arguments captured by `Mock::capture_args` or
+/// `Mock::capture_args_matching`.
+///
+/// Clones of an `ArgCapture` share the same captured arguments.
+#[derive(Clone)]
+pub struct ArgCapture<C> {
+    args: Ref<Vec<C>>,
+}
+
+impl<C: Clone> ArgCapture<C> {
+    /// Returns the arguments of the most recent captured call, or `None` if
+    /// no calls have been captured.
+    pub fn last(&self) -> Option<C> {
+        self.args.borrow().last().cloned()
+    }
+
+    /// Returns the arguments of every captured call, in order from first to
+    /// last.
+    pub fn all(&self) -> Vec<C> {
+        self.args.borrow().clone()
+    }
+
+    /// Returns the arguments of every captured call, in order from first to
+    /// last, and clears them. Calls made afterwards are still captured.
+    pub fn take(&self) -> Vec<C> {
+        self.args.borrow_mut().drain(..).collect()
+    }
+}
+
+impl<C: Debug> Debug for ArgCapture<C> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("ArgCapture")
+            .field("args", &*self.args.borrow())
+            .finish()
+    }
+}
+
+/// Guard returned by `Mock::assert_no_more_calls_guard`, which panics when
+/// it's dropped if the mock was called after the guard was created.
+pub struct NoMoreCallsGuard<C>
+    where C: Clone + Debug + Eq + Hash
+{
+    // Shares the mock's call history, so the guard doesn't borrow the mock.
+    num_calls: Ref<usize>,
+    calls: Ref<Vec<C>>,
+    interned_args: OptionalRef<InternedArgs<C>>,
+    num_dropped_calls: Ref<usize>,
+    initial_num_calls: usize,
+    initial_num_recorded_calls: usize,
+}
+
+impl<C> NoMoreCallsGuard<C>
+    where C: Clone + Debug + Eq + Hash
+{
+    // Formats the calls recorded after the guard was created, which are at
+    // the end of the mock's call history.
+    fn unexpected_calls(&self) -> Vec<String> {
+        let num_dropped_calls = *self.num_dropped_calls.borrow();
+        match *self.interned_args.borrow() {
+            Some(ref interned_args) => {
+                let calls = &interned_args.calls;
+                calls[self.num_kept_calls(calls.len(), num_dropped_calls)..]
+                    .iter()
+                    .map(|&index| format!("{:?}", interned_args.args[index]))
+                    .collect()
+            }
+            None => {
+                let calls = self.calls.borrow();
+                calls[self.num_kept_calls(calls.len(), num_dropped_calls)..]
+                    .iter()
+                    .map(|args| format!("{:?}", args))
+                    .collect()
+            }
+        }
+    }
+
+    // Returns the number of recorded calls which were made before the guard
+    // was created and haven't been dropped since.
+    fn num_kept_calls(&self, num_recorded_calls: usize, num_dropped_calls: usize) -> usize {
+        let num_new_calls = (num_recorded_calls + num_dropped_calls)
+            .saturating_sub(self.initial_num_recorded_calls);
+        num_recorded_calls - num_new_calls.min(num_recorded_calls)
+    }
+}
+
+impl<C> Drop for NoMoreCallsGuard<C>
+    where C: Clone + Debug + Eq + Hash
+{
+    fn drop(&mut self) {
+        let num_calls = *self.num_calls.borrow();
+        if num_calls <= self.initial_num_calls || thread::panicking() {
+            return;
+        }
+        let expected = format!(
+            "no calls after the guard was created, but {} made",
+            plural(num_calls - self.initial_num_calls, "call was", "calls were"));
+        panic!(
+            "{}",
+            failure::format_mismatch("no_more_calls_guard", &expected, &self.unexpected_calls()));
+    }
+}
+
+impl<C> Debug for NoMoreCallsGuard<C>
+    where C: Clone + Debug + Eq + Hash
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("NoMoreCallsGuard")
+            .field("initial_num_calls", &self.initial_num_calls)
+            .finish()
+    }
+}
+
+/// Builder returned by `Mock::expect`, which collects the calls a mock
+/// should have received and then verifies them.
+pub struct ExpectedCalls<'a, C, R>
+    where C: Clone + Eq + Hash
+{
+    mock: &'a Mock<C, R>,
+    calls: Vec<C>,
+}
+
+impl<'a, C, R> ExpectedCalls<'a, C, R>
+    where C: Clone + Debug + Eq + Hash
+{
+    /// Adds a call with `args` to the expected calls.
+    pub fn call(mut self, args: C) -> Self {
+        self.calls.push(args);
+        self
+    }
+
+    /// Returns `Ok` if the expected calls were made in the order they were
+    /// added. Other calls may be made before, after or between them.
+    /// Otherwise, returns the text of the `CallMismatch` returned by
+    /// `Mock::check_has_calls_in_order`.
+    pub fn verify_in_order(&self) -> Result<(), String> {
+        self.mock.check_has_calls_in_order(self.calls.clone()).map_err(|m| m.to_string())
+    }
+
+    /// Returns `Ok` if the expected calls were made, in any order. Otherwise,
+    /// returns the text of the `CallMismatch` returned by
+    /// `Mock::check_has_calls`.
+    pub fn verify_any_order(&self) -> Result<(), String> {
+        self.mock.check_has_calls(self.calls.clone()).map_err(|m| m.to_string())
+    }
+
+    /// Returns `Ok` if the expected calls, and no others, were made in any
+    /// order. Otherwise, returns the text of the `CallMismatch` returned by
+    /// `Mock::check_has_calls_exactly`.
+    pub fn verify_exactly(&self) -> Result<(), String> {
+        self.mock.check_has_calls_exactly(self.calls.clone()).map_err(|m| m.to_string())
+    }
+
+    /// Returns `Ok` if the expected calls, and no others, were made in the
+    /// order they were added. Otherwise, returns the text of the
+    /// `CallMismatch` returned by `Mock::check_has_calls_exactly_in_order`.
+    pub fn verify_exactly_in_order(&self) -> Result<(), String> {
+        self.mock
+            .check_has_calls_exactly_in_order(self.calls.clone())
+            .map_err(|m| m.to_string())
+    }
+}
+
+impl<'a, C, R> Debug for ExpectedCalls<'a, C, R>
+    where C: Clone + Debug + Eq + Hash
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("ExpectedCalls")
+            .field("calls", &self.calls)
+            .finish()
+    }
+}
+
+struct MatchInfo {
+    num_expectations: usize,
+    num_actual_calls: usize,
+    // Maps actual call index to the indices of patterns that match the call
+    pattern_index_to_match_indices: HashMap<usize, Vec<usize>>,
+}
+
+impl MatchInfo {
+    pub fn expectations_matched(&self) -> bool {
+        self.unmatched_expectations().is_empty()
+    }
+
+    pub fn expectations_matched_in_order(&self) -> bool {
+        self.expectations_matched() && self.matches_are_in_order()
+    }
+
+    pub fn expectations_matched_exactly(&self) -> bool {
+        self.expectations_matched() &&
+            self.num_expectations_equal_num_actual_calls()
+    }
+
+    pub fn expectations_matched_in_order_exactly(&self) -> bool {
+        self.expectations_matched_in_order() &&
+            self.num_expectations_equal_num_actual_calls()
+    }
+
+    fn matches_are_in_order(&self) -> bool {
+        // If all the expectations are met, use the indices of all matching
+        // calls (for each pattern) to determine if the calls were made in
+        // the order specified by the expectated patterns.
+        //
+        // This is more difficult than one might think. Each expected pattern
+        // can match multiple calls. Additionally, the total set of
+        // expectations can be smaller than the total number of calls. Both of
+        // two aspects make this problem tricky.
+        //
+        // The following algorithm is used for the check:
+        //
+        // 1. For each pattern, construct a list containing the indices of the
+        //    calls that match it
+        // 2. Generate all permutations of the sequence of actual calls that
+        //    matched each of the N patterns (uses the lists from (1))
+        // 3. For each permutation, check if the call indices in the
+        //    permutation are strictly increasing. If so, we've found a
+        //    permutation that occurred where the call order and the expected
+        //    pattern order match. This means the expectations were indeed
+        //    matched in order and return true.
+        // 4. If none of the permutations are strictly increasing, the
+        //    expected patterns were matched, but not in the expected order.
+        //    Return false.
+        //
+        //
+        // The complexity is O(N!), where N is the number of patterns in the
+        // expected sequence. The factorial complexity is caused by the
+        // generation of all permutations of matching call index sequences in.
+        // step (2). The O(N!) complexity is currently not a concern for two
+        // reasons:
+        //
+        // * Most ordered checks run by clients involve less than 5 patterns,
+        //   so the upper bound typically won't exceed 5!.
+        // * The constant factor is almost always very low (most of the time
+        //   a pattern will only ever match one call arg, meaning the number
+        //   of permutations is very small, even if N is high).
+        //
+        // This algorithm will only be revised if a legitmate performance issue
+        // is found.
+        if self.expectations_matched() {
+            let permutation_constraints = self.pattern_index_to_match_indices
+                .iter()
+                .sorted_by(|a, b| a.0.cmp(&b.0))
+                .map(
+                    |(_, matching_call_indices)| matching_call_indices.clone())
+                .collect();
+            for permutation in generate_permutations(&permutation_constraints) {
+                if is_strictly_increasing(permutation.as_slice()) {
+                    return true;
+                }
+            }
+            false
+        } else {
+            false
+        }
+    }
+
+    // Appends the indices of the expectations which no call matched to
+    // `expected`, the description of all expectations in a `CallMismatch`.
+    pub fn describe_expected(&self, expected: String) -> String {
+        let unmatched: Vec<String> = self.unmatched_expectations()
+            .iter()
+            .map(|index| index.to_string())
+            .collect();
+        match unmatched.len() {
+            0 => expected,
+            1 => format!(
+                "{}, but no call matched the expectation at index {}",
+                expected,
+                unmatched[0]),
+            _ => format!(
+                "{}, but no call matched the expectations at indices {}",
+                expected,
+                unmatched.join(", ")),
+        }
+    }
+
+    fn unmatched_expectations(&self) -> Vec<usize> {
+        (0..self.num_expectations)
+            .filter(|index| !self.pattern_index_to_match_indices.contains_key(index))
+            .collect()
+    }
+
+    fn num_expectations_equal_num_actual_calls(&self) -> bool {
+        self.num_expectations == self.num_actual_calls
+    }
+}
+
+fn generate_permutations(constraints: &Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+    let mut output: Vec<Vec<usize>> = vec!();
+    if !constraints.is_empty() {
+        let mut permutation_buffer: Vec<usize> = vec!();
+        permutation_buffer.resize(constraints.len(), 0);
+
+        generate_permutations_impl(
+            &mut output, &mut permutation_buffer, constraints, 0);
+    }
+    output
+}
+
+fn generate_permutations_impl(
+    output_permutations: &mut Vec<Vec<usize>>,
+    permutation_buffer: &mut Vec<usize>,
+    constraints: &Vec<Vec<usize>>,
+    current_index: usize)
+{
+    if current_index < permutation_buffer.len() {
+        for val in &constraints[current_index] {
+            permutation_buffer[current_index] = val.clone();
+            generate_permutations_impl(
+                output_permutations,
+                permutation_buffer,
+                constraints,
+                current_index + 1)
+        }
+    } else {
+        output_permutations.push(permutation_buffer.clone());
+    }
+}
+
+// Splits the `Debug` representation of a tuple into the representations of
+// its elements. Representations of other types have a single position.
+fn debug_positions(debug: &str) -> Vec<&str> {
+    if !(debug.starts_with('(') && debug.ends_with(')')) || debug.len() < 2 {
+        return vec!(debug);
+    }
+
+    let inner = &debug[1..debug.len() - 1];
+    let mut positions = vec!();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (index, c) in inner.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                positions.push(inner[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = inner[start..].trim();
+    if !last.is_empty() {
+        positions.push(last);
+    }
+    positions
+}
+
+// Returns the positions of the tuples represented by `expected` and `actual`
+// which differ. Every position differs if they have different lengths.
+fn differing_debug_positions(expected: &str, actual: &str) -> Vec<usize> {
+    let expected = debug_positions(expected);
+    let actual = debug_positions(actual);
+    if expected.len() != actual.len() {
+        return (0..expected.len().max(actual.len())).collect();
+    }
+    (0..expected.len()).filter(|&i| expected[i] != actual[i]).collect()
+}
+
+// Levenshtein distance between `a` and `b`, counting `char`s.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..b.len() + 1).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec!(i + 1);
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn is_strictly_increasing(sequence: &[usize]) -> bool {
+    for window in sequence.windows(2) {
+        if window[0] >= window[1] {
+            return false;
+        }
+    }
+    true
 }
 
-fn generate_permutations_impl(
-    output_permutations: &mut Vec<Vec<usize>>,
-    permutation_buffer: &mut Vec<usize>,
-    constraints: &Vec<Vec<usize>>,
-    current_index: usize)
-{
-    if current_index < permutation_buffer.len() {
-        for val in &constraints[current_index] {
-            permutation_buffer[current_index] = val.clone();
-            generate_permutations_impl(
-                output_permutations,
-                permutation_buffer,
-                constraints,
-                current_index + 1)
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    #[should_panic(expected = "connection lost")]
+    fn panic_with_panics_for_any_args() {
+        let mock = Mock::<i32, i32>::new(0);
+        mock.return_value_for(1, 1);
+        mock.panic_with("connection lost");
+        mock.call(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "unlucky")]
+    fn panic_with_for_panics_for_given_args() {
+        let mock = Mock::<i32, i32>::new(0);
+        mock.panic_with("connection lost");
+        mock.panic_with_for(13, "unlucky");
+        mock.call(13);
+    }
+
+    #[test]
+    fn call_times_use_fake_clock() {
+        let now = Ref::new(Cell::new(100u64));
+        let clock = now.clone();
+        let mock = Mock::<i32, ()>::default();
+        mock.call(0);
+        mock.set_clock(Box::new(move || *clock.borrow()));
+
+        mock.call(1);
+        *now.borrow_mut() += 30;
+        mock.call_without_recording(2);
+        mock.call(3);
+
+        let times = mock.call_times();
+        assert_eq!(times, vec!(0, 100, 130));
+        assert!(times[2] - times[1] <= 50);
+
+        mock.reset_calls();
+        assert!(mock.call_times().is_empty());
+    }
+
+    #[test]
+    fn return_value_for_matching_prefers_higher_priority() {
+        let mock = Mock::<i32, &str>::new("default");
+        mock.return_value_for_matching(0, Box::new(|x: &i32| *x > 0), "broad");
+        mock.return_value_for_matching(10, Box::new(|x: &i32| *x > 0 && *x < 10), "narrow");
+        mock.return_value_for_matching(0, Box::new(|x: &i32| *x > 5), "broad, later");
+
+        assert_eq!(mock.call(5), "narrow");
+        assert_eq!(mock.call(50), "broad");
+        assert_eq!(mock.call(0), "default");
+
+        // the copy keeps the configured patterns
+        let copy = mock.deep_clone();
+        assert_eq!(copy.call(5), "narrow");
+    }
+
+    // Counts how many times it has been cloned.
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    struct CloneCounted(i32);
+
+    // Per thread, so tests running in parallel don't affect each other.
+    thread_local! {
+        static NUM_CLONE_COUNTED_CLONES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    impl Clone for CloneCounted {
+        fn clone(&self) -> Self {
+            NUM_CLONE_COUNTED_CLONES.with(|clones| clones.set(clones.get() + 1));
+            CloneCounted(self.0)
+        }
+    }
+
+    fn num_clone_counted_clones() -> usize {
+        NUM_CLONE_COUNTED_CLONES.with(|clones| clones.get())
+    }
+
+    #[test]
+    fn into_calls_moves_calls_out_of_mock() {
+        let mock = Mock::<CloneCounted, ()>::new(());
+        mock.call(CloneCounted(1));
+        mock.call(CloneCounted(2));
+
+        let clones_before = num_clone_counted_clones();
+        assert_eq!(mock.into_calls(), vec!(CloneCounted(1), CloneCounted(2)));
+        assert_eq!(num_clone_counted_clones(), clones_before);
+    }
+
+    #[test]
+    fn with_calls_and_fold_calls_borrow_calls_without_copying() {
+        let mock = Mock::<CloneCounted, ()>::new(());
+        for i in 0..1000 {
+            mock.call(CloneCounted(i));
+        }
+
+        let clones_before = num_clone_counted_clones();
+        assert!(mock.with_calls(|calls| calls.iter().all(|call| call.0 < 1000)));
+        assert_eq!(mock.with_calls(|calls| calls.len()), 1000);
+        assert_eq!(mock.fold_calls(0, |total, call| total + call.0), 499_500);
+        assert_eq!(num_clone_counted_clones(), clones_before);
+    }
+
+    #[test]
+    fn with_calls_borrows_interned_calls_without_copying() {
+        let mock = Mock::<CloneCounted, ()>::new(());
+        mock.enable_arg_interning();
+        for i in 0..1000 {
+            mock.call(CloneCounted(i % 10));
+        }
+
+        let clones_before = num_clone_counted_clones();
+        assert_eq!(mock.with_calls(|calls| calls.len()), 1000);
+        assert_eq!(mock.with_calls(|calls| calls[11].0), 1);
+        assert_eq!(mock.fold_calls(0, |total, call| total + call.0), 4500);
+        assert_eq!(num_clone_counted_clones(), clones_before);
+    }
+
+    #[test]
+    fn arg_interning_copies_each_distinct_args_once() {
+        let plain = Mock::<CloneCounted, ()>::new(());
+        let clones_before = num_clone_counted_clones();
+        for i in 0..1000 {
+            plain.call(CloneCounted(i % 10));
+        }
+        assert_eq!(num_clone_counted_clones() - clones_before, 1000);
+
+        let interned = Mock::<CloneCounted, ()>::new(());
+        interned.enable_arg_interning();
+        let clones_before = num_clone_counted_clones();
+        for i in 0..1000 {
+            interned.call(CloneCounted(i % 10));
+        }
+        // once for the interned args, and once for the key used to find them
+        assert_eq!(num_clone_counted_clones() - clones_before, 20);
+        assert_eq!(interned.calls(), plain.calls());
+    }
+
+    #[test]
+    fn arg_interning_does_not_change_checks() {
+        type Lookup = (String, u64);
+        let key = |name: &str, n: u64| (name.to_owned(), n);
+        let plain = Mock::<Lookup, Option<u64>>::default();
+        plain.call(key("a", 1));
+        let interned = plain.deep_clone();
+        interned.call(key("a", 1));
+        interned.enable_arg_interning();
+        for mock in &[&plain, &interned] {
+            mock.call(key("b", 2));
+            mock.call(key("a", 1));
+            mock.call_without_recording(key("c", 3));
+        }
+        let interned_copy = interned.deep_clone();
+        interned_copy.call(key("d", 4));
+        assert_eq!(interned.closest_call(key("a", 2)), Some(key("a", 1)));
+        assert!(interned.check_called_with(key("b", 1)).unwrap_err().to_string()
+            .contains("closest actual call"));
+
+        let checks = |mock: &Mock<Lookup, Option<u64>>| vec!(
+            format!("{:?}", mock.calls()),
+            format!("{:?}", mock.num_calls()),
+            format!("{:?}", mock.called_with(key("a", 1))),
+            format!("{:?}", mock.called_with(key("c", 3))),
+            format!("{:?}", mock.has_calls(vec!(key("b", 2), key("a", 1)))),
+            format!("{:?}", mock.has_calls_in_order(vec!(key("a", 1), key("b", 2)))),
+            format!("{:?}", mock.has_calls_exactly(vec!(key("a", 1), key("b", 2), key("a", 1)))),
+            format!("{:?}", mock.has_calls_exactly_in_order(vec!(key("a", 1), key("b", 2), key("a", 1)))),
+            format!("{:?}", mock.has_patterns(vec!(&|args: &Lookup| args.1 > 1))),
+            mock.check_has_calls_exactly(vec!(key("b", 2))).unwrap_err().to_string(),
+            format!("{:?}", mock.call_log()),
+            format!("{:?}", mock.last_n_calls(2)),
+            format!("{:?}", mock.closest_call(key("a", 2))),
+            mock.check_called_with(key("b", 1)).unwrap_err().to_string(),
+            format!("{:?}", mock.with_calls(|calls| calls.iter().cloned().collect::<Vec<_>>())),
+            format!("{}", mock));
+        assert_eq!(checks(&interned), checks(&plain));
+        assert_eq!(interned_copy.calls(), vec!(key("d", 4)));
+
+        for mock in &[&plain, &interned] {
+            mock.set_max_recorded_calls(2);
+            mock.call(key("e", 5));
+        }
+        assert_eq!(checks(&interned), checks(&plain));
+        assert_eq!(interned.take_calls(), plain.take_calls());
+        assert!(interned.calls().is_empty());
+
+        interned.call(key("f", 6));
+        assert_eq!(interned.clone().into_calls(), vec!(key("f", 6)));
+    }
+
+    #[test]
+    fn calling_mock_while_calls_are_borrowed_panics_clearly() {
+        let mock = Mock::<i32, ()>::new(());
+        let handle = mock.clone();
+        mock.call(1);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            mock.fold_calls((), |_, _| handle.call(2))
+        }));
+        let message = result.unwrap_err();
+        assert_eq!(
+            message.downcast_ref::<String>().map(String::as_str),
+            Some("Mock::call was called while the mock's calls were borrowed by \
+                  Mock::with_calls or Mock::fold_calls"));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            mock.with_calls(|_| handle.with_calls(|calls| calls.len()))
+        }));
+        let message = result.unwrap_err();
+        assert!(message.downcast_ref::<String>().unwrap().starts_with(
+            "Mock::with_calls was called while"));
+
+        // the borrow ends when the closure panics
+        mock.call(3);
+        assert_eq!(mock.calls(), vec!(1, 3));
+    }
+
+    #[test]
+    fn into_calls_copies_calls_shared_with_clones() {
+        let mock = Mock::<i32, ()>::new(());
+        let handle = mock.clone();
+        mock.call(1);
+
+        assert_eq!(mock.into_calls(), vec!(1));
+        assert_eq!(handle.calls(), vec!(1));
+    }
+
+    #[test]
+    fn no_more_calls_guard_passes_without_calls() {
+        let mock = Mock::<i32, ()>::new(());
+        mock.call(1);
+        assert_eq!(mock.take_calls(), vec!(1));
+
+        let guard = mock.assert_no_more_calls_guard();
+        assert!(mock.calls().is_empty());
+        drop(guard);
+
+        // the mock can be used again once the guard is dropped
+        mock.call(2);
+        assert_eq!(mock.take_calls(), vec!(2));
+    }
+
+    #[test]
+    fn no_more_calls_guard_panics_on_later_calls() {
+        let mock = Mock::<i32, ()>::new(());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _guard = mock.assert_no_more_calls_guard();
+            mock.call(1);
+            mock.call_without_recording(2);
+        }));
+
+        let error = result.unwrap_err();
+        let message = error.downcast_ref::<String>().unwrap();
+        assert_eq!(
+            message,
+            "Mock::no_more_calls_guard failed\n  \
+             expected: no calls after the guard was created, but 2 calls were made\n  \
+             actual calls: [1]");
+    }
+
+    #[test]
+    fn no_more_calls_guard_lists_only_calls_made_after_it() {
+        let mock = Mock::<i32, ()>::new(());
+        mock.set_max_recorded_calls(2);
+        mock.call(1);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _guard = mock.assert_no_more_calls_guard();
+            mock.call(2);
+            mock.call(3);
+            mock.call(4);
+        }));
+
+        let error = result.unwrap_err();
+        let message = error.downcast_ref::<String>().unwrap();
+        assert!(message.ends_with(
+            "expected: no calls after the guard was created, but 3 calls were made\n  \
+             actual calls: [3, 4]"));
+    }
+
+    #[test]
+    fn no_more_calls_guard_lists_interned_calls() {
+        let mock = Mock::<i32, ()>::new(());
+        mock.enable_arg_interning();
+        mock.call(1);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _guard = mock.assert_no_more_calls_guard();
+            mock.call(1);
+            mock.call(2);
+        }));
+
+        let error = result.unwrap_err();
+        let message = error.downcast_ref::<String>().unwrap();
+        assert!(message.ends_with("actual calls: [1, 2]"));
+    }
+
+    #[test]
+    fn no_more_calls_guard_does_not_keep_behaviour_alive() {
+        let return_value = std::sync::Arc::new(());
+        let mock = Mock::<i32, std::sync::Arc<()>>::new(return_value.clone());
+        let guard = mock.assert_no_more_calls_guard();
+        drop(mock);
+
+        // the guard only shares the mock's calls
+        assert_eq!(std::sync::Arc::strong_count(&return_value), 1);
+        drop(guard);
+    }
+
+    #[test]
+    fn no_more_calls_guard_does_not_panic_while_panicking() {
+        let mock = Mock::<i32, ()>::new(());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _guard = mock.assert_no_more_calls_guard();
+            mock.call(1);
+            panic!("assertion failed");
+        }));
+
+        let error = result.unwrap_err();
+        assert_eq!(error.downcast_ref::<&str>(), Some(&"assertion failed"));
+    }
+
+    #[test]
+    fn check_call_order_with_interleaved_mocks() {
+        let save = Mock::<i32, ()>::default();
+        let notify = Mock::<&str, ()>::default();
+        save.call(1);
+        save.call(2);
+        notify.call("saved");
+        save.call(3);
+
+        let order = |names: &[&'static str]| {
+            let calls: Vec<(&str, Vec<usize>)> = names
+                .iter()
+                .map(|&name| match name {
+                    "save" => (name, save.call_timestamps()),
+                    _ => (name, notify.call_timestamps()),
+                })
+                .collect();
+            check_call_order(&calls)
+        };
+
+        assert!(order(&["save", "notify", "save"]).is_ok());
+        assert!(order(&["save", "save", "notify"]).is_ok());
+        assert!(order(&["save", "save", "save"]).is_ok());
+        assert_eq!(
+            order(&["notify", "save", "save"]),
+            Err("expected call 3 to be to `save`, but it wasn't called after call 2 \
+                 (calls were made in order: save, save, notify, save)".to_owned()));
+        assert_eq!(
+            order(&["save", "notify", "notify"]),
+            Err("expected call 3 to be to `notify`, but it wasn't called after call 2 \
+                 (calls were made in order: save, save, notify, save)".to_owned()));
+
+        let unused = Mock::<(), ()>::default();
+        assert_eq!(
+            check_call_order(&[("save", save.call_timestamps()), ("unused", unused.call_timestamps())]),
+            Err("expected call 2 to be to `unused`, but it was never called".to_owned()));
+    }
+
+    #[test]
+    fn explain_next_call_follows_documented_precedence() {
+        use self::BehaviourSource::*;
+
+        type Configure = Box<dyn Fn(&Mock<i32, i32>)>;
+
+        // Each behaviour takes precedence over all those configured before it.
+        let mock = Mock::<i32, i32>::unconfigured();
+        let steps: Vec<(Configure, BehaviourSource)> = vec!(
+            (Box::new(|_| ()), Unconfigured),
+            (Box::new(|m| m.return_value(1)), DefaultValue),
+            (Box::new(|m| m.return_values(vec!(3))), Sequence),
+            (Box::new(|m| m.return_values_cycled(vec!(10))), Cycled),
+            (Box::new(|m| m.use_producer(Box::new(|| 4))), Producer),
+            (Box::new(|m| m.use_fn(|x| x)), DefaultFn),
+            (Box::new(|m| m.use_closure(Box::new(|x| x))), DefaultClosure),
+            (Box::new(|m| m.return_value_once(5)), Once),
+            (Box::new(|m| m.return_value_for_matching(0, Box::new(|x| *x > 0), 6)), Pattern),
+            (Box::new(|m| m.return_value_for(7, 7)), ValueFor),
+            (Box::new(|m| m.use_fn_for(7, |x| x)), FnFor),
+            (Box::new(|m| m.return_value_for_nth(7, 0, 8)), ValueForNth),
+            (Box::new(|m| m.return_value_for_call(0, 9)), ValueForCall),
+            (Box::new(|m| m.use_closure_for(7, Box::new(|x| x))), ClosureFor),
+            (Box::new(|m| m.fail_after(0, -1)), FailAfter),
+            (Box::new(|m| m.panic_with_for(7, "boom")), Panic),
+        );
+        for (configure, expected_source) in steps {
+            configure(&mock);
+            assert_eq!(mock.explain_next_call(7), expected_source);
         }
-    } else {
-        output_permutations.push(permutation_buffer.clone());
+        assert!(!mock.called());
     }
-}
 
-fn is_strictly_increasing(sequence: &[usize]) -> bool {
-    for window in sequence.windows(2) {
-        if window[0] >= window[1] {
-            return false;
+    #[test]
+    fn fail_after_returns_failure_once_calls_are_exhausted() {
+        let mock = Mock::<&str, Result<u32, String>>::new(Ok(1));
+        mock.fail_after(2, Err("broken".to_owned()));
+
+        assert_eq!(mock.call("a"), Ok(1));
+        assert_eq!(mock.call_without_recording("b"), Ok(1));
+        assert_eq!(mock.call("c"), Err("broken".to_owned()));
+        assert_eq!(mock.call("d"), Err("broken".to_owned()));
+        assert_eq!(mock.call_sources(), vec!(
+            BehaviourSource::DefaultValue,
+            BehaviourSource::FailAfter,
+            BehaviourSource::FailAfter));
+
+        // resetting the calls restarts the count
+        mock.reset_calls();
+        assert_eq!(mock.call("e"), Ok(1));
+        assert_eq!(mock.call("f"), Ok(1));
+        assert_eq!(mock.call("g"), Err("broken".to_owned()));
+    }
+
+    #[test]
+    fn max_recorded_calls_keeps_most_recent_calls() {
+        let mock = Mock::<i32, i32>::new(0);
+        mock.use_closure(Box::new(|x| x * 10));
+        mock.call(1);
+        mock.call(2);
+        mock.call(3);
+
+        // existing calls are dropped straight away
+        mock.set_max_recorded_calls(2);
+        assert_eq!(mock.calls(), vec!(2, 3));
+
+        for i in 4..8 {
+            mock.call(i);
         }
+        assert_eq!(mock.num_calls(), 7);
+        assert_eq!(mock.calls(), vec!(6, 7));
+        assert_eq!(mock.returned_values(), vec!(60, 70));
+        assert_eq!(mock.call_sources(), vec!(BehaviourSource::DefaultClosure; 2));
+        assert_eq!(mock.call_times(), vec!(5, 6));
+        assert!(mock.has_calls_exactly_in_order(vec!(6, 7)));
+
+        let mismatch = mock.check_has_calls(vec!(1, 7)).unwrap_err();
+        assert_eq!(
+            mismatch.expected(),
+            "[1, 7], but no call matched the expectation at index 0 \
+             (only the last 2 of 7 recorded calls were kept)");
+        let mismatch = mock.check_not_called_with(7).unwrap_err();
+        assert_eq!(
+            mismatch.expected(),
+            "no call with 7, but call 6 matched (only the last 2 of 7 recorded calls were kept)");
+
+        mock.reset_calls();
+        mock.call(8);
+        assert_eq!(mock.calls(), vec!(8));
+        assert!(mock.check_not_called_with(9).is_ok());
     }
-    true
-}
 
+    #[test]
+    fn max_recorded_calls_keeps_results_of_nested_calls_aligned() {
+        let mock = Mock::<i32, i32>::new(0);
+        let handle = mock.clone();
+        mock.set_max_recorded_calls(1);
+        // the outer call is dropped while it's being invoked
+        mock.use_closure_for(1, Box::new(move |_| handle.call(2) + 1));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        assert_eq!(mock.call(1), 1);
+        assert_eq!(mock.calls(), vec!(2));
+        assert_eq!(mock.returned_values(), vec!(0));
+        assert_eq!(mock.call_sources(), vec!(BehaviourSource::DefaultValue));
+
+        mock.set_max_recorded_calls(0);
+        assert_eq!(mock.call(3), 0);
+        assert!(mock.calls().is_empty());
+        assert_eq!(mock.num_calls(), 3);
+    }
+
+    #[test]
+    fn call_recorder_transforms_recorded_args() {
+        type Upload = (u32, Vec<u8>);
+        let mock = Mock::<Upload, usize>::new(0usize);
+        mock.use_closure(Box::new(|(_, payload)| payload.len()));
+        mock.return_value_for((7, vec!(1; 1000)), 7usize);
+        mock.set_call_recorder(Box::new(|&(id, ref payload)| {
+            (id, payload.iter().take(2).cloned().collect())
+        }));
+
+        // behaviour is chosen using the original args
+        assert_eq!(mock.call((1, vec!(9; 500))), 500);
+        assert_eq!(mock.call((7, vec!(1; 1000))), 7);
+        assert_eq!(mock.calls(), vec!((1, vec!(9, 9)), (7, vec!(1, 1))));
+        assert!(mock.called_with((7, vec!(1, 1))));
+        assert!(!mock.called_with((7, vec!(1; 1000))));
+
+        // settings are copied by deep clones
+        let copy = mock.deep_clone();
+        copy.call((2, vec!(3, 4, 5)));
+        assert_eq!(copy.calls(), vec!((2, vec!(3, 4))));
+    }
+
+    #[test]
+    fn call_hooks_observe_calls_alongside_default_closure() {
+        let mock = Mock::<i32, i32>::new(0);
+        let num_observed: Ref<usize> = Ref::new(Cell::new(0));
+        let order: Ref<Vec<&str>> = Ref::new(Cell::new(vec!()));
+
+        let observed = num_observed.clone();
+        let first_order = order.clone();
+        mock.on_call(Box::new(move |_| {
+            *observed.borrow_mut() += 1;
+            first_order.borrow_mut().push("first");
+        }));
+        let second_order = order.clone();
+        mock.on_call(Box::new(move |_| second_order.borrow_mut().push("second")));
+        let closure_order = order.clone();
+        mock.use_closure(Box::new(move |x| {
+            closure_order.borrow_mut().push("closure");
+            x * 2
+        }));
+
+        assert_eq!(mock.call(1), 2);
+        assert_eq!(*num_observed.borrow(), 1);
+        assert_eq!(*order.borrow(), vec!("first", "second", "closure"));
+
+        mock.use_fn(|x| x + 1);
+        assert_eq!(mock.call(2), 3);
+        mock.call_without_recording(3);
+        assert_eq!(*num_observed.borrow(), 3);
+    }
+
+    #[test]
+    fn call_observers_are_invoked_in_registration_order() {
+        let mock = Mock::<i32, i32>::new(0);
+        let num_observed: Ref<usize> = Ref::new(Cell::new(0));
+        let order: Ref<Vec<&str>> = Ref::new(Cell::new(vec!()));
+
+        let first_observed = num_observed.clone();
+        let first_order = order.clone();
+        mock.add_call_observer(Box::new(move |_| {
+            *first_observed.borrow_mut() += 1;
+            first_order.borrow_mut().push("first");
+        }));
+        let second_observed = num_observed.clone();
+        let second_order = order.clone();
+        mock.add_call_observer(Box::new(move |_| {
+            *second_observed.borrow_mut() += 1;
+            second_order.borrow_mut().push("second");
+        }));
+        let closure_order = order.clone();
+        mock.use_closure(Box::new(move |x| {
+            closure_order.borrow_mut().push("closure");
+            x * 2
+        }));
+
+        assert_eq!(mock.call(1), 2);
+        assert_eq!(*num_observed.borrow(), 2);
+        assert_eq!(*order.borrow(), vec!("first", "second", "closure"));
+
+        // stubbing doesn't remove observers
+        mock.use_fn(|x| x + 1);
+        assert_eq!(mock.call(2), 3);
+        assert_eq!(*num_observed.borrow(), 4);
+    }
+
+    #[test]
+    fn try_call_returns_panics_as_errors() {
+        let mock = Mock::<i32, i32>::new(0);
+        mock.use_closure(Box::new(|x| {
+            if x == 13 {
+                panic!("unlucky {}", x);
+            }
+            x * 2
+        }));
+        mock.panic_with_for(7, "configured panic");
+
+        assert_eq!(mock.try_call(2).ok(), Some(4));
+        let error = mock.try_call(13).unwrap_err();
+        assert_eq!(error.downcast_ref::<String>().map(String::as_str), Some("unlucky 13"));
+        assert!(mock.try_call(7).is_err());
+
+        // the mock can still be used after a panic
+        assert_eq!(mock.call(3), 6);
+        assert_eq!(mock.calls(), vec!(2, 13, 7, 3));
+        assert_eq!(mock.num_calls(), 4);
+    }
+
+    #[test]
+    fn live_handler_is_passed_live_args() {
+        let mock = Mock::<i32, i32>::new(0);
+        let received: Ref<Vec<i32>> = Ref::new(Cell::new(vec!()));
+        let sink = received.clone();
+        let on_value = move |value| sink.borrow_mut().push(value);
+
+        // without a handler, live args are ignored
+        assert_eq!(mock.call_with_live_args(1, on_value.clone()), 0);
+        assert!(received.borrow().is_empty());
+
+        mock.return_value(5);
+        mock.use_live_handler(Box::new(|x: i32, (on_value, times): (Box<dyn_fn!(Fn(i32))>, usize)| {
+            for i in 0..times {
+                on_value(x + i as i32);
+            }
+        }));
+        assert_eq!(mock.call_with_live_args(10, (Box::new(on_value) as Box<dyn_fn!(Fn(i32))>, 2usize)), 5);
+        assert_eq!(*received.borrow(), vec!(10, 11));
+        assert_eq!(mock.calls(), vec!(1, 10));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| mock.call_with_live_args(2, "wrong")));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn call_sources_records_behaviour_of_each_call() {
+        use self::BehaviourSource::*;
+
+        let mock = Mock::<i32, i32>::new(0);
+        mock.return_values(vec!(1));
+        mock.return_value_once(2);
+        mock.return_value_for_nth(5, 1, 50);
+        mock.panic_with_for(13, "unlucky");
+
+        mock.call(5);
+        mock.call(5);
+        mock.call_without_recording(6);
+        mock.call(7);
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| mock.call(13))).is_err());
+
+        assert_eq!(mock.call_sources(), vec!(Once, ValueForNth, DefaultValue, Panic));
+        assert_eq!(mock.returned_values(), vec!(2, 50, 0));
+        mock.reset_calls();
+        assert!(mock.call_sources().is_empty());
+    }
+
+    #[test]
+    fn return_value_for_nth_returns_value_once_per_args() {
+        let mock = Mock::<(i32, &str), Result<(), String>>::new(Ok(()));
+        mock.return_value_for_nth((1, "a"), 0, Err("first".to_owned()));
+
+        assert_eq!(mock.call((2, "a")), Ok(()));
+        assert_eq!(mock.call((1, "a")), Err("first".to_owned()));
+        assert_eq!(mock.call((1, "a")), Ok(()));
+
+        mock.reset_calls();
+        assert_eq!(mock.call_without_recording((1, "a")), Err("first".to_owned()));
+        assert_eq!(mock.call((1, "a")), Ok(()));
+        assert_eq!(mock.deep_clone().call((1, "a")), Err("first".to_owned()));
+    }
+
+    #[test]
+    fn closest_call_prefers_most_equal_positions() {
+        let mock = Mock::<(i32, String, bool), ()>::default();
+        assert_eq!(mock.closest_call((1, "a".to_owned(), true)), None);
+
+        mock.call((500, "aaaaaaaaaa".to_owned(), true));
+        mock.call((1, "b".to_owned(), false));
+        mock.call((2, "a, b".to_owned(), true));
+
+        // two positions equal beats a smaller edit distance
+        assert_eq!(
+            mock.closest_call((500, "b".to_owned(), true)),
+            Some((500, "aaaaaaaaaa".to_owned(), true)));
+        assert_eq!(
+            mock.closest_call((2, "a, b".to_owned(), false)),
+            Some((2, "a, b".to_owned(), true)));
+        // ties are broken by edit distance
+        assert_eq!(
+            mock.closest_call((1, "c".to_owned(), true)),
+            Some((1, "b".to_owned(), false)));
+    }
+
+    #[test]
+    fn check_called_with_names_differing_positions() {
+        let mock = Mock::<(i32, (i32, i32), &str), ()>::default();
+        mock.call((1, (2, 3), "x"));
+
+        let mismatch = mock.check_called_with((1, (2, 4), "y")).unwrap_err();
+        assert_eq!(
+            mismatch.expected(),
+            "(1, (2, 4), \"y\") (closest actual call: (1, (2, 3), \"x\") \
+             differs in positions 1, 2)");
+
+        // no hint if nothing matches
+        let mismatch = mock.check_called_with((0, (0, 0), "z")).unwrap_err();
+        assert_eq!(mismatch.expected(), "(0, (0, 0), \"z\")");
+    }
+
+    #[test]
+    fn tuple_args_are_converted_element_by_element() {
+        let mock = Mock::<(String, String), i32>::default();
+        mock.return_value_for(("a", "b"), 1);
+        mock.use_fn_for(("c", "d"), |_| 2);
+        mock.use_closure_for(("e", "f"), Box::new(|_| 3));
+        assert_eq!(mock.call(("a".to_owned(), "b".to_owned())), 1);
+        assert_eq!(mock.call(("c".to_owned(), "d".to_owned())), 2);
+        assert_eq!(mock.call(("e".to_owned(), "f".to_owned())), 3);
+
+        assert!(mock.called_with(("a", "b")));
+        assert!(!mock.called_with(("b", "a")));
+        // elements can be a mix of converted and owned arguments
+        assert!(mock.called_with(("c", "d".to_owned())));
+        assert!(mock.has_calls_in_order(vec!(("a", "b"), ("e", "f"))));
+        mock.assert_called_with(("e", "f"));
+
+        let mock = Mock::<(i32, Vec<i32>), ()>::default();
+        mock.call((1, vec!(2, 3)));
+        assert!(mock.called_with((1, &[2, 3][..])));
+        assert!(mock.called_with((1, vec!(2, 3))));
+    }
+
+    #[test]
+    fn check_has_calls_names_unmatched_expectations() {
+        let mock = Mock::<i32, ()>::default();
+        mock.call(1);
+        mock.call(2);
+
+        let mismatch = mock.check_has_calls(vec!(3, 1, 4)).unwrap_err();
+        assert_eq!(
+            mismatch.expected(),
+            "[3, 1, 4], but no call matched the expectations at indices 0, 2");
+        let mismatch = mock.check_has_patterns(vec!(&|x: &i32| *x > 1, &|x: &i32| *x > 2))
+            .unwrap_err();
+        assert_eq!(
+            mismatch.expected(),
+            "calls matching 2 patterns, but no call matched the expectation at index 1");
+
+        // every expectation matched, but the calls were made in another order
+        let mismatch = mock.check_has_calls_in_order(vec!(2, 1)).unwrap_err();
+        assert_eq!(mismatch.expected(), "[2, 1]");
+    }
+
+    #[test]
+    fn calls_in_count_range_reports_actual_count() {
+        let mock = Mock::<i32, ()>::default();
+        mock.call(1);
+
+        // below min
+        assert!(!mock.calls_in_count_range(2, 4));
+        let mismatch = mock.check_calls_in_count_range(2, 4).unwrap_err();
+        assert_eq!(mismatch.check(), "calls_in_count_range");
+        assert_eq!(mismatch.expected(), "between 2 and 4 calls, but 1 was made");
+        assert_eq!(mismatch.actual_calls(), ["1"]);
+
+        // in range, including both bounds
+        mock.call(2);
+        assert!(mock.calls_in_count_range(2, 4));
+        assert!(mock.check_calls_in_count_range(2, 4).is_ok());
+        mock.call(3);
+        mock.call(4);
+        assert!(mock.calls_in_count_range(2, 4));
+        mock.assert_calls_in_count_range(2, 4);
+
+        // above max
+        mock.call(5);
+        assert!(!mock.calls_in_count_range(2, 4));
+        let mismatch = mock.check_calls_in_count_range(2, 4).unwrap_err();
+        assert_eq!(mismatch.expected(), "between 2 and 4 calls, but 5 were made");
+    }
+
+    #[test]
+    #[should_panic(expected = "between 0 and 0 calls, but 1 was made")]
+    fn assert_calls_in_count_range_panics_outside_range() {
+        let mock = Mock::<i32, ()>::default();
+        mock.call(1);
+        mock.assert_calls_in_count_range(0, 0);
+    }
+
+    #[test]
+    fn has_no_calls_reports_every_forbidden_call() {
+        let mock = Mock::<(i32, &str), ()>::default();
+
+        // vacuously true without calls
+        assert!(mock.has_no_calls(vec!((1, "a"))));
+        assert!(mock.has_no_calls_matching(&|_| true));
+        mock.assert_has_no_calls(vec!((1, "a")));
+        mock.assert_has_no_calls_matching(&|_| true);
+
+        mock.call((1, "a"));
+        mock.call((2, "b"));
+        mock.call((1, "a"));
+        mock.call((3, "c"));
+        assert!(mock.has_no_calls(Vec::<(i32, &str)>::new()));
+        assert!(mock.has_no_calls(vec!((4, "d"))));
+        assert!(!mock.has_no_calls(vec!((1, "a"), (3, "c"))));
+
+        let mismatch = mock.check_has_no_calls(vec!((1, "a"), (3, "c"))).unwrap_err();
+        assert_eq!(
+            mismatch.expected(),
+            "no calls with any of [(1, \"a\"), (3, \"c\")], but call 0 ((1, \"a\")), \
+             call 2 ((1, \"a\")) and call 3 ((3, \"c\")) were made");
+
+        let mismatch = mock.check_has_no_calls_matching(&|args| args.0 == 2).unwrap_err();
+        assert_eq!(mismatch.expected(), "no calls matching the pattern, but call 1 ((2, \"b\")) was made");
+
+        // indices count calls that are no longer recorded
+        mock.set_max_recorded_calls(1);
+        mock.call((2, "b"));
+        let mismatch = mock.check_has_no_calls_matching(&|args| args.0 == 2).unwrap_err();
+        assert!(mismatch.expected().starts_with("no calls matching the pattern, but call 4 ((2, \"b\")) was made"));
+    }
+
+    #[test]
+    fn expected_calls_agree_with_has_calls() {
+        let mock = Mock::<(u32, i32), ()>::default();
+        mock.call((500, 250));
+        mock.call((0, 0));
+        mock.call((500, 250));
+
+        let in_order = mock.expect().call((0, 0)).call((500, 250));
+        assert!(in_order.verify_in_order().is_ok());
+        assert!(in_order.verify_any_order().is_ok());
+        assert!(in_order.verify_exactly().is_err());
+        assert!(mock.has_calls_in_order(vec!((0, 0), (500, 250))));
+        assert!(!mock.has_calls_exactly(vec!((0, 0), (500, 250))));
+
+        let out_of_order = mock.expect().call((0, 0)).call((500, 250)).call((500, 250)).call((0, 0));
+        assert!(out_of_order.verify_exactly().is_err());
+        assert_eq!(
+            out_of_order.verify_in_order().unwrap_err(),
+            mock.check_has_calls_in_order(vec!((0, 0), (500, 250), (500, 250), (0, 0)))
+                .unwrap_err()
+                .to_string());
+
+        let exact = mock.expect().call((500, 250)).call((500, 250)).call((0, 0));
+        assert!(exact.verify_exactly().is_ok());
+        assert!(mock.has_calls_exactly(vec!((500, 250), (500, 250), (0, 0))));
+        assert_eq!(
+            exact.verify_exactly_in_order().unwrap_err(),
+            "Mock::has_calls_exactly_in_order failed\n  \
+             expected: [(500, 250), (500, 250), (0, 0)]\n  \
+             actual calls: [(500, 250), (0, 0), (500, 250)]");
+        assert!(mock.expect().call((500, 250)).call((0, 0)).call((500, 250))
+            .verify_exactly_in_order()
+            .is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "but call 0 (5) was made")]
+    fn assert_has_no_calls_matching_panics_on_matching_call() {
+        let mock = Mock::<i32, ()>::default();
+        mock.call(5);
+        mock.assert_has_no_calls_matching(&|x| *x > 1);
+    }
+
+    #[test]
+    fn display_lists_each_recorded_call() {
+        let mock = Mock::<(i32, String), ()>::default();
+        assert_eq!(format!("{}", mock), "0 calls");
+
+        mock.call((1, "first".to_owned()));
+        assert_eq!(format!("{}", mock), "1 call\n  0: (1, \"first\")");
+
+        mock.call_without_recording((2, "second".to_owned()));
+        mock.call((3, "third".to_owned()));
+        let formatted = format!("{}", mock);
+        assert!(formatted.starts_with("3 calls (2 recorded)"));
+        assert!(formatted.contains("0: (1, \"first\")"));
+        assert!(!formatted.contains("second"));
+        assert!(formatted.contains("1: (3, \"third\")"));
+
+        // indices count calls that are no longer recorded
+        mock.set_max_recorded_calls(1);
+        assert_eq!(format!("{}", mock), "3 calls (1 recorded)\n  1: (3, \"third\")");
+    }
+
+    #[test]
+    fn debug_positions_splits_top_level_elements() {
+        assert_eq!(debug_positions("42"), vec!("42"));
+        assert_eq!(debug_positions("(1, [2, 3], \"a, \\\"b\")"), vec!("1", "[2, 3]", "\"a, \\\"b\""));
+        assert_eq!(debug_positions("(5,)"), vec!("5"));
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn summary_reports_calls_and_behaviour() {
+        let mock = Mock::<(i32, &str), i32>::new(0);
+        assert_eq!(mock.summary(), "0 calls\nargs: none\nbehaviour: default return value");
+
+        mock.return_value_for((1, "a"), 5);
+        mock.return_value_for((2, "b"), 6);
+        mock.use_closure(Box::new(|_| 7));
+        mock.return_values(vec!(1, 2, 3));
+        mock.call((1, "a"));
+        mock.call((3, "c"));
+        mock.call((1, "a"));
+        mock.call_without_recording((4, "d"));
+
+        let summary = mock.summary();
+        assert!(summary.starts_with("4 calls (1 not recorded)\n"));
+        assert!(summary.contains("args: (1, \"a\") ×2, (3, \"c\") ×1\n"));
+        assert!(summary.contains("default closure"));
+        assert!(summary.contains("2 return values for specific args"));
+        assert!(summary.contains("3 remaining sequenced return values"));
+        assert!(!summary.contains("producer"));
+    }
+
+    #[test]
+    fn return_values_cycled_advances_only_when_used() {
+        let mock = Mock::<i32, &str>::new("default");
+        mock.return_values_cycled(vec!("a", "b", "c"));
+        mock.return_value_for(0, "zero");
+
+        assert_eq!(mock.call(1), "a");
+        // calls using other behaviour don't skip values
+        assert_eq!(mock.call(0), "zero");
+        assert_eq!(mock.call(2), "b");
+        assert_eq!(
+            mock.call_sources(),
+            vec!(BehaviourSource::Cycled, BehaviourSource::ValueFor, BehaviourSource::Cycled));
+
+        // the cursor survives resetting the calls and cloning
+        mock.reset_calls();
+        assert_eq!(mock.deep_clone().call(3), "c");
+        assert_eq!(mock.clone().call(3), "c");
+        assert_eq!(mock.call(4), "a");
+
+        mock.reset_behaviour_cursor();
+        assert_eq!(mock.call(5), "a");
+
+        // configuring a new sequence restarts from its first value
+        mock.return_values_cycled(vec!("x", "y"));
+        assert_eq!(mock.call(6), "x");
+
+        mock.return_values_cycled(Vec::<&str>::new());
+        assert_eq!(mock.call(7), "default");
+    }
+
+    #[test]
+    fn return_values_in_mode_replaces_the_sequence() {
+        let mock = Mock::<i32, &str>::new("default");
+        mock.return_values(vec!("drained"));
+        mock.return_values_cycled(vec!("a", "b"));
+        assert_eq!(mock.call(0), "a");
+        assert_eq!(mock.call(1), "b");
+
+        mock.return_values_in_mode(vec!("x", "y"), SequenceMode::Drain);
+        assert_eq!(mock.call(2), "x");
+        assert_eq!(mock.call(3), "y");
+        assert_eq!(mock.call(4), "default");
+
+        // a checkpoint drops the values left to drain, but keeps a cycle
+        mock.return_values(vec!("x"));
+        mock.checkpoint();
+        assert_eq!(mock.call(5), "default");
+        mock.return_values_cycled(vec!("a"));
+        mock.checkpoint();
+        assert_eq!(mock.call(6), "a");
+    }
+
+    #[test]
+    fn use_value_only_replaces_default_behaviour() {
+        let mock = Mock::<i32, i32>::new(0);
+        mock.use_closure(Box::new(|x| x * 2));
+        mock.return_value_for(3, 30);
+
+        // the closure still wins over a new default return value
+        mock.return_value(100);
+        assert_eq!(mock.call(1), 2);
+
+        mock.use_value_only(100);
+        assert_eq!(mock.call(1), 100);
+        assert_eq!(mock.call(3), 30);
+        assert_eq!(mock.call_sources().last(), Some(&BehaviourSource::ValueFor));
+
+        mock.use_fn(|x| x + 1);
+        mock.use_producer(Box::new(|| -1));
+        mock.return_values(vec!(5));
+        mock.return_values_cycled(vec!(6, 7));
+        mock.return_value_once(8);
+        mock.use_value_only(200);
+        assert_eq!(mock.call(1), 200);
+        assert_eq!(mock.call(2), 200);
+        assert_eq!(mock.call_sources().last(), Some(&BehaviourSource::DefaultValue));
+    }
+
+    #[test]
+    fn closure_with_index_counts_every_call() {
+        let mock = Mock::<i32, usize>::new(100usize);
+        mock.use_closure_with_index(Box::new(|index, _| index));
+
+        assert_eq!(mock.call(1), 0);
+        assert_eq!(mock.call_without_recording(2), 1);
+        assert_eq!(mock.call(3), 2);
+
+        // the copy starts counting its own calls from zero
+        let copy = mock.deep_clone();
+        assert_eq!(copy.call(4), 0);
+
+        mock.reset_calls();
+        assert_eq!(mock.call(5), 0);
+
+        mock.use_closure(Box::new(|x| x as usize));
+        assert_eq!(mock.call(7), 7);
+    }
+
+    #[test]
+    fn checkpoint_isolates_test_phases() {
+        let mock = Mock::<i32, i32>::new(-1);
+        mock.return_value_for(7, 70);
+        mock.use_fn_for(8, |x| x * 100);
+        mock.return_values(vec!(1, 2));
+        mock.return_value_once(10);
+
+        // phase 1
+        assert_eq!(mock.call(1), 10);
+        assert_eq!(mock.call(7), 70);
+        mock.checkpoint();
+
+        // phase 2
+        assert!(!mock.called());
+        assert_eq!(mock.call(1), -1);
+        assert_eq!(mock.call(7), 70);
+        assert_eq!(mock.call(8), 800);
+        assert_eq!(mock.calls(), vec!(1, 7, 8));
+        assert_eq!(mock.num_calls(), 3);
+    }
+
+    #[test]
+    fn checkpoint_verifies_expected_calls_before_clearing() {
+        let mock = Mock::<i32, i32>::new(0);
+        mock.expect_call(1);
+        mock.expect_call(2);
+
+        // phase 1
+        mock.call(2);
+        mock.call(1);
+        mock.checkpoint();
+
+        // phase 2 expects nothing from phase 1
+        assert!(mock.check_expectations().is_ok());
+        mock.expect_call(3);
+        mock.call(4);
+        let error = mock.check_expectations().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Mock::expect_call failed\n  expected: [3], but no call matched the expectation at index 0\n  actual calls: [4]");
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| mock.checkpoint()));
+        assert!(result.is_err());
+        // a failed checkpoint leaves the phase's calls for inspection
+        assert_eq!(mock.calls(), vec!(4));
+    }
+
+    #[test]
+    fn merged_calls_interleaves_histories() {
+        let read = Mock::<(&str, i32), ()>::default();
+        let write = Mock::<(&str, i32), ()>::default();
+
+        write.call(("write", 1));
+        read.call(("read", 2));
+        read.call(("read", 3));
+        write.call(("write", 4));
+
+        assert_eq!(
+            Mock::merged_calls(&[&read, &write]),
+            vec!(("write", 1), ("read", 2), ("read", 3), ("write", 4)));
+        assert_eq!(Mock::merged_calls(&[&read]), vec!(("read", 2), ("read", 3)));
+        assert!(Mock::<i32, ()>::merged_calls(&[]).is_empty());
+    }
+
+    #[test]
+    fn called_before_and_after_interleaved_calls() {
+        let write = Mock::<&str, ()>::default();
+        let flush = Mock::<(), ()>::default();
+
+        write.call("foo");
+        flush.call(());
+        write.call("bar");
+
+        // calls are interleaved, so neither mock was called entirely before
+        // the other
+        assert!(!called_before(&write, &flush));
+        assert!(!called_before(&flush, &write));
+        assert!(!called_after(&write, &flush));
+        assert!(!called_after(&flush, &write));
+
+        write.reset_calls();
+        write.call("baz");
+        assert!(called_before(&flush, &write));
+        assert!(called_after(&write, &flush));
+        assert!(!called_before(&write, &flush));
+        assert!(!called_after(&flush, &write));
+
+        flush.reset_calls();
+        assert!(!called_before(&write, &flush));
+        assert!(!called_after(&write, &flush));
+    }
+
+    #[test]
+    fn sequential_captures_see_disjoint_calls() {
+        let mock = Mock::<i32, ()>::default();
+        mock.call(0);
+
+        let first = mock.capture_args();
+        mock.call(1);
+        mock.call(2);
+
+        let second = mock.capture_args();
+        mock.call(3);
+        let first_phase = first.take();
+        mock.call(4);
+
+        assert_eq!(first_phase, vec!(1, 2, 3));
+        assert_eq!(first.all(), vec!(4));
+        assert_eq!(second.all(), vec!(3, 4));
+        assert_eq!(second.last(), Some(4));
+        assert_eq!(mock.calls(), vec!(0, 1, 2, 3, 4));
+    }
+
+    #[test]
+    fn captures_are_not_cleared_by_reset_calls() {
+        let mock = Mock::<i32, ()>::default();
+        let evens = mock.capture_args_matching(Box::new(|x| x % 2 == 0));
+        mock.call(1);
+        mock.call(2);
+        mock.reset_calls();
+        mock.call(4);
+
+        assert_eq!(evens.all(), vec!(2, 4));
+        assert_eq!(mock.calls(), vec!(4));
+    }
+
+    #[test]
+    fn iterating_over_calls_allows_calling_the_mock() {
+        let mock = Mock::<i32, ()>::default();
+        mock.call(1);
+        mock.call(2);
+        mock.call(3);
+
+        let mut seen = vec!();
+        for args in &mock {
+            mock.call(args * 10);
+            seen.push(args);
+        }
+
+        assert_eq!(seen, vec!(1, 2, 3));
+        assert_eq!(mock.iter().collect::<Vec<i32>>(), vec!(1, 2, 3, 10, 20, 30));
+    }
+
+    #[test]
+    fn configured_return_for_ignores_other_behaviour() {
+        let mock = Mock::<i32, i32>::new(0);
+        mock.return_values(vec!(1, 2));
+        mock.use_fn_for(5, |x| x * 10);
+        mock.return_value_for(7, 70);
+
+        assert_eq!(mock.configured_return_for(7), Some(70));
+        assert_eq!(mock.configured_return_for(5), None);
+        assert_eq!(mock.configured_return_for(1), None);
+        assert_eq!(mock.num_calls(), 0);
+        assert!(mock.calls().is_empty());
+
+        // the configured values are unaffected
+        assert_eq!(mock.call(7), 70);
+        assert_eq!(mock.call(1), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+    struct Order {
+        item: String,
+        quantity: u32,
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn calls_as_json_round_trips_tuple_args() {
+        let mock = Mock::<(String, i32), ()>::default();
+        mock.call(("foo".to_owned(), 1));
+        mock.call(("bar".to_owned(), -2));
+
+        let json = mock.calls_as_json().unwrap();
+        assert_eq!(json, r#"[["foo",1],["bar",-2]]"#);
+        assert_eq!(mock.verify_calls_against_json(&json), Ok(()));
+
+        let copy = Mock::<(String, i32), ()>::default();
+        copy.call(("foo".to_owned(), 1));
+        copy.call(("bar".to_owned(), -2));
+        assert_eq!(copy.verify_calls_against_json(&json), Ok(()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn calls_as_json_round_trips_struct_args() {
+        let mock = Mock::<Order, ()>::default();
+        mock.call(Order { item: "apple".to_owned(), quantity: 3 });
+
+        let json = mock.calls_as_json().unwrap();
+        assert_eq!(json, r#"[{"item":"apple","quantity":3}]"#);
+        assert_eq!(mock.verify_calls_against_json(&json), Ok(()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn verify_calls_against_json_reports_first_difference() {
+        let mock = Mock::<Order, ()>::default();
+        mock.call(Order { item: "apple".to_owned(), quantity: 3 });
+        mock.call(Order { item: "pear".to_owned(), quantity: 1 });
+
+        assert_eq!(
+            mock.verify_calls_against_json(
+                r#"[{"item":"apple","quantity":3},{"item":"pear","quantity":2}]"#),
+            Err("call 1 differs: expected Order { item: \"pear\", quantity: 2 }, \
+                 actual Order { item: \"pear\", quantity: 1 }".to_owned()));
+        assert_eq!(
+            mock.verify_calls_against_json(r#"[{"item":"apple","quantity":3}]"#),
+            Err("call 1 differs: expected no call, \
+                 actual Order { item: \"pear\", quantity: 1 }".to_owned()));
+        assert!(mock.verify_calls_against_json("not json").is_err());
+
+        mock.reset_calls();
+        assert_eq!(
+            mock.verify_calls_against_json(r#"[{"item":"apple","quantity":3}]"#),
+            Err("call 0 differs: expected Order { item: \"apple\", quantity: 3 }, \
+                 actual no call".to_owned()));
+    }
+
+    #[test]
+    fn panic_with_for_does_not_panic_for_other_args() {
+        let mock = Mock::<i32, i32>::new(0);
+        mock.panic_with_for(13, "unlucky");
+        assert_eq!(mock.call(12), 0);
+        assert!(mock.called_with(12));
+    }
 
     #[test]
     fn generate_permutations_no_constraints() {
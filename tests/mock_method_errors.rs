@@ -0,0 +1,9 @@
+// Checks the errors reported when the argument types listed in `mock_trait!`
+// don't match the arguments passed to `mock_method!`. The expected errors are
+// in the `.stderr` file next to each test case. Run with `TRYBUILD=overwrite`
+// to update them.
+#[test]
+fn mismatched_argument_types() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
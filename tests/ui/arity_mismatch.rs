@@ -0,0 +1,16 @@
+#[macro_use]
+extern crate double;
+
+trait Calculator {
+    fn add(&self, a: i32, b: i32) -> i32;
+}
+
+mock_trait!(
+    MockCalculator,
+    add(i32) -> i32);  // should be `add(i32, i32)`
+
+impl Calculator for MockCalculator {
+    mock_method!(add(&self, a: i32, b: i32) -> i32);
+}
+
+fn main() {}
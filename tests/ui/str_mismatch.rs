@@ -0,0 +1,16 @@
+#[macro_use]
+extern crate double;
+
+trait Store {
+    fn put(&self, key: u32, value: &str);
+}
+
+mock_trait!(
+    MockStore,
+    put(u32, i32) -> ());  // should be `put(u32, String)`
+
+impl Store for MockStore {
+    mock_method!(put(&self, key: u32, value: &str));
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+#[macro_use]
+extern crate double;
+
+use double::Mock;
+
+trait Calculator {
+    fn add(&self, a: i32, b: i32) -> i32;
+}
+
+// Written by hand, but without `mock_method_args!(MockCalculator, add(i32, i32))`.
+struct MockCalculator {
+    m_add: Mock<(i32, i32), i32>,
+}
+
+impl Calculator for MockCalculator {
+    mock_method!(add(&self, a: i32, b: i32) -> i32);
+}
+
+fn main() {}